@@ -1,144 +1,243 @@
 use dotenv::dotenv;
-use rust_trading_system::trading::TestnetTrader;
+use rust_trading_system::output::{emit_json, emit_json_error, OutputMode};
+use rust_trading_system::trading::{AccountType, OrderId, TestnetTrader};
+use serde_json::json;
 use std::env;
-use tracing_subscriber;
+use std::io::Write;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
-    tracing_subscriber::fmt::init();
+    let output = OutputMode::detect();
+    output.init_logging();
 
     let api_key = env::var("TESTNET_BINANCE_VISION_API_KEY")
         .expect("TESTNET_BINANCE_VISION_API_KEY must be set in .env file");
     let secret_key = env::var("TESTNET_BINANCE_VISION_SECRET_KEY")
         .expect("TESTNET_BINANCE_VISION_SECRET_KEY must be set in .env file");
 
-    let trader = TestnetTrader::new(api_key, secret_key);
+    let mut trader = TestnetTrader::new(api_key, secret_key);
+    if let (Ok(backup_key), Ok(backup_secret)) = (
+        env::var("TESTNET_BINANCE_VISION_API_KEY_2"),
+        env::var("TESTNET_BINANCE_VISION_SECRET_KEY_2"),
+    ) {
+        trader = trader.with_additional_credentials(backup_key, backup_secret);
+    }
+
+    // Scripts get a single machine-readable snapshot instead of the interactive menu
+    if output.is_json() {
+        print_json_snapshot(&trader).await;
+        return Ok(());
+    }
 
     loop {
         println!("📊 Testnet Order Monitor");
         println!("========================");
         println!();
 
-        // Get all open orders
-        match trader.get_open_orders(None).await {
-            Ok(orders) => {
-                if orders.is_empty() {
-                    println!("📭 No open orders found");
-                } else {
-                    println!("📋 Open Orders ({} total):", orders.len());
-                    println!();
-                    
-                    for order in &orders {
-                        println!("🔸 Order #{}", order.order_id);
-                        println!("   Symbol: {}", order.symbol);
-                        println!("   Side: {}", order.side);
-                        println!("   Type: {}", order.order_type);
-                        println!("   Status: {:?}", order.status);
-                        println!("   Quantity: {} (Executed: {})", order.orig_qty, order.executed_qty);
-                        println!("   Price: ${}", order.price);
-                        println!("   Time: {}", 
-                                if let Some(time) = order.transact_time.or(order.time) {
-                                    chrono::DateTime::from_timestamp_millis(time as i64)
-                                        .unwrap_or_default()
-                                        .format("%Y-%m-%d %H:%M:%S UTC")
-                                        .to_string()
-                                } else {
-                                    "Unknown".to_string()
-                                });
-                        println!();
-                    }
+        // Get all open orders; OCO legs are shown under their list instead
+        let orders = match trader.get_open_orders(None).await {
+            Ok(orders) => orders,
+            Err(e) => {
+                println!("❌ Failed to get orders: {}", e);
+                if pause().is_none() {
+                    return goodbye();
                 }
+                continue;
+            }
+        };
+
+        let oco_lists = match trader.get_open_oco_orders().await {
+            Ok(lists) => lists,
+            Err(e) => {
+                println!("⚠️  Failed to get OCO order lists: {}", e);
+                Vec::new()
+            }
+        };
+        let plain_orders: Vec<_> = orders.iter().filter(|o| o.order_list_id == -1).collect();
 
-                // Interactive menu
-                println!("🎛️  What would you like to do?");
-                println!("1. Refresh orders");
-                println!("2. Cancel an order");
-                println!("3. Check account balance");
-                println!("4. Get current Bitcoin price");
-                println!("5. Exit");
+        if orders.is_empty() {
+            println!("📭 No open orders found");
+        } else if !plain_orders.is_empty() {
+            println!("📋 Open Orders ({} total):", plain_orders.len());
+            println!();
+
+            for order in &plain_orders {
+                println!("🔸 Order #{}", order.order_id);
+                println!("   Symbol: {}", order.symbol);
+                println!("   Side: {}", order.side);
+                println!("   Type: {}", order.order_type);
+                println!("   Status: {:?}", order.status);
+                println!("   Quantity: {} (Executed: {})", order.orig_qty, order.executed_qty);
+                println!("   Price: ${}", order.price);
+                println!("   Time: {}", 
+                        order.datetime()
+                            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+                            .unwrap_or_else(|| "Unknown".to_string()));
                 println!();
-                print!("Enter choice (1-5): ");
-
-                let mut input = String::new();
-                std::io::stdin().read_line(&mut input)?;
-                
-                match input.trim() {
-                    "1" => {
-                        println!("🔄 Refreshing...");
-                        println!();
-                        continue; // Loop back to refresh orders
+            }
+        }
+
+        if !oco_lists.is_empty() {
+            println!("🔗 Open OCO Lists ({} total) - canceling either leg cancels the whole list:", oco_lists.len());
+            println!();
+
+            for list in &oco_lists {
+                println!("🔗 OCO List #{} ({})", list.order_list_id, list.symbol);
+                println!("   Status: {}", list.list_order_status);
+                for leg in &list.orders {
+                    match orders.iter().find(|o| o.order_id == leg.order_id) {
+                        Some(order) => println!("   ↳ Order #{}: {} {} {} @ ${} (Executed: {})",
+                                                order.order_id, order.side, order.order_type,
+                                                order.orig_qty, order.price, order.executed_qty),
+                        None => println!("   ↳ Order #{}", leg.order_id),
                     }
-                    "2" => {
-                        if !orders.is_empty() {
-                            println!("Enter order ID to cancel:");
-                            let mut order_id_input = String::new();
-                            std::io::stdin().read_line(&mut order_id_input)?;
-                            
-                            if let Ok(order_id) = order_id_input.trim().parse::<u64>() {
-                                // Find the symbol for this order
-                                if let Some(order) = orders.iter().find(|o| o.order_id == order_id) {
-                                    match trader.cancel_order(&order.symbol, order_id).await {
-                                        Ok(_) => println!("✅ Order {} canceled successfully!", order_id),
-                                        Err(e) => println!("❌ Failed to cancel order: {}", e),
-                                    }
-                                } else {
-                                    println!("❌ Order ID not found in your open orders");
-                                }
-                            } else {
-                                println!("❌ Invalid order ID");
+                }
+                println!();
+            }
+        }
+
+        // Interactive menu
+        println!("🎛️  What would you like to do?");
+        println!("1. Refresh orders");
+        println!("2. Cancel an order");
+        println!("3. Check account balance");
+        println!("4. Get current Bitcoin price");
+        println!("5. Exit");
+        println!();
+
+        // Keep asking until we get a valid choice; EOF (piped input ran out) exits
+        let choice = loop {
+            let Some(input) = prompt("Enter choice (1-5): ") else {
+                return goodbye();
+            };
+            match input.parse::<u8>() {
+                Ok(choice @ 1..=5) => break choice,
+                _ => println!("❌ Invalid choice '{}', please enter a number from 1 to 5", input),
+            }
+        };
+
+        match choice {
+            1 => {
+                println!("🔄 Refreshing...");
+                println!();
+                continue; // Loop back to refresh orders
+            }
+            2 => {
+                if orders.is_empty() {
+                    println!("❌ No orders to cancel");
+                } else {
+                    // Ask until the id is one of the open orders; an empty line goes back to the menu
+                    let order = loop {
+                        let Some(input) = prompt("Enter order ID to cancel (blank to go back): ") else {
+                            return goodbye();
+                        };
+                        if input.is_empty() {
+                            break None;
+                        }
+                        match input.parse::<OrderId>() {
+                            Ok(order_id) => match orders.iter().find(|o| o.order_id == order_id) {
+                                Some(order) => break Some(order),
+                                None => println!("❌ Order ID not found in your open orders"),
+                            },
+                            Err(_) => println!("❌ Invalid order ID '{}', expected a number", input),
+                        }
+                    };
+
+                    // An OCO leg can't be canceled on its own - cancel its list by orderListId
+                    match order.map(|o| (o, oco_lists.iter().find(|l| l.contains(o.order_id)))) {
+                        Some((order, Some(list))) => {
+                            match trader.cancel_oco_order(&list.symbol, list.order_list_id).await {
+                                Ok(_) => println!("✅ OCO list #{} (including order {}) canceled successfully!",
+                                                  list.order_list_id, order.order_id),
+                                Err(e) => println!("❌ Failed to cancel OCO list: {}", e),
                             }
-                        } else {
-                            println!("❌ No orders to cancel");
                         }
-                        println!("Press Enter to continue...");
-                        let mut _dummy = String::new();
-                        std::io::stdin().read_line(&mut _dummy)?;
-                    }
-                    "3" => {
-                        match trader.get_account_info().await {
-                            Ok(account) => {
-                                println!("💳 Main Balances:");
-                                let important_assets = ["BTC", "ETH", "USDT", "BNB"];
-                                for balance in &account.balances {
-                                    if important_assets.contains(&balance.asset.as_str()) && balance.free > 0.0 {
-                                        println!("   {} {:.8} (Free: {:.8})", balance.asset, balance.free + balance.locked, balance.free);
-                                    }
-                                }
+                        Some((order, None)) => {
+                            match trader.cancel_order(&order.symbol, order.order_id).await {
+                                Ok(_) => println!("✅ Order {} canceled successfully!", order.order_id),
+                                Err(e) => println!("❌ Failed to cancel order: {}", e),
                             }
-                            Err(e) => println!("❌ Failed to get account info: {}", e),
                         }
-                        println!("Press Enter to continue...");
-                        let mut _dummy = String::new();
-                        std::io::stdin().read_line(&mut _dummy)?;
+                        None => {}
                     }
-                    "4" => {
-                        match trader.get_current_price("BTCUSDT").await {
-                            Ok(price) => println!("💰 Current BTC/USDT: ${:.2}", price),
-                            Err(e) => println!("❌ Failed to get price: {}", e),
+                }
+            }
+            3 => {
+                match trader.get_account_info(&[AccountType::Spot]).await {
+                    Ok(account) => {
+                        println!("💳 Main Balances:");
+                        let important_assets = ["BTC", "ETH", "USDT", "BNB"];
+                        for balance in account.wallet(AccountType::Spot).unwrap_or_default() {
+                            if important_assets.contains(&balance.asset.as_str()) && balance.free > 0.0 {
+                                println!("   {} {:.8} (Free: {:.8})", balance.asset, balance.free + balance.locked, balance.free);
+                            }
                         }
-                        println!("Press Enter to continue...");
-                        let mut _dummy = String::new();
-                        std::io::stdin().read_line(&mut _dummy)?;
-                    }
-                    "5" => {
-                        println!("👋 Goodbye!");
-                        return Ok(());
-                    }
-                    _ => {
-                        println!("❌ Invalid choice");
-                        println!("Press Enter to continue...");
-                        let mut _dummy = String::new();
-                        std::io::stdin().read_line(&mut _dummy)?;
                     }
+                    Err(e) => println!("❌ Failed to get account info: {}", e),
                 }
             }
-            Err(e) => {
-                println!("❌ Failed to get orders: {}", e);
-                println!("Press Enter to continue...");
-                let mut _dummy = String::new();
-                std::io::stdin().read_line(&mut _dummy)?;
+            4 => {
+                match trader.get_current_price("BTCUSDT").await {
+                    Ok(price) => println!("💰 Current BTC/USDT: ${:.2}", price),
+                    Err(e) => println!("❌ Failed to get price: {}", e),
+                }
             }
+            _ => return goodbye(),
+        }
+
+        if pause().is_none() {
+            return goodbye();
         }
     }
-}
\ No newline at end of file
+}
+
+fn goodbye() -> Result<(), Box<dyn std::error::Error>> {
+    println!("👋 Goodbye!");
+    Ok(())
+}
+
+/// Print `message` without a newline and read the trimmed answer
+/// None at EOF (or if stdin can't be read), so callers can exit cleanly
+fn prompt(message: &str) -> Option<String> {
+    print!("{}", message);
+    let _ = std::io::stdout().flush();
+
+    let mut input = String::new();
+    match std::io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
+fn pause() -> Option<String> {
+    prompt("Press Enter to continue...")
+}
+
+/// Open orders, OCO lists, balances and the BTC price as JSON lines
+async fn print_json_snapshot(trader: &TestnetTrader) {
+    match trader.get_open_orders(None).await {
+        Ok(orders) => emit_json("orders", &orders),
+        Err(e) => emit_json_error(&format!("Failed to get orders: {}", e)),
+    }
+
+    match trader.get_open_oco_orders().await {
+        Ok(lists) => emit_json("oco_orders", &lists),
+        Err(e) => emit_json_error(&format!("Failed to get OCO order lists: {}", e)),
+    }
+
+    match trader.get_account_info(&[AccountType::Spot]).await {
+        Ok(account) => {
+            let balances: Vec<_> = account.wallet(AccountType::Spot).unwrap_or_default().iter()
+                .filter(|b| b.free + b.locked > 0.0)
+                .collect();
+            emit_json("balances", &balances);
+        }
+        Err(e) => emit_json_error(&format!("Failed to get account info: {}", e)),
+    }
+
+    match trader.get_current_price("BTCUSDT").await {
+        Ok(price) => emit_json("price", &json!({ "symbol": "BTCUSDT", "price": price })),
+        Err(e) => emit_json_error(&format!("Failed to get price: {}", e)),
+    }
+}
@@ -1,15 +1,27 @@
 use dotenv::dotenv;
-use rust_trading_system::trading::{TestnetTrader, OrderSide};
+use rust_trading_system::orders::place_and_manage;
+use rust_trading_system::output::{emit_json, emit_json_error, OutputMode};
+use rust_trading_system::trading::{TestnetTrader, OrderSide, OrderType, NewOrderRequest};
+use serde_json::json;
 use std::env;
-use tracing_subscriber;
+use std::time::Duration;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load environment variables from .env file
     dotenv().ok();
     
-    // Initialize logging
-    tracing_subscriber::fmt::init();
+    // `--json` (or TRADING_OUTPUT=json) swaps the decorated text for JSON lines
+    let output = OutputMode::detect();
+    let json = output.is_json();
+
+    // Human-readable text, printed only in the default output mode
+    macro_rules! say {
+        ($($arg:tt)*) => { if !json { println!($($arg)*); } };
+    }
+
+    // Initialize logging (on stderr in JSON mode, so stdout stays parseable)
+    output.init_logging();
 
     // Get API credentials from environment
     let api_key = env::var("TESTNET_BINANCE_VISION_API_KEY")
@@ -18,25 +30,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .expect("TESTNET_BINANCE_VISION_SECRET_KEY must be set in .env file");
 
     // Create trader instance
-    let trader = TestnetTrader::new(api_key, secret_key);
+    let mut trader = TestnetTrader::new(api_key, secret_key);
+
+    // Optional backup key pair - used automatically if the first one gets rate-limited or rejected
+    if let (Ok(backup_key), Ok(backup_secret)) = (
+        env::var("TESTNET_BINANCE_VISION_API_KEY_2"),
+        env::var("TESTNET_BINANCE_VISION_SECRET_KEY_2"),
+    ) {
+        trader = trader.with_additional_credentials(backup_key, backup_secret);
+    }
 
-    println!("🎮 Welcome to Testnet Trading Demo!");
-    println!("💰 All trades use FAKE MONEY - completely risk-free!");
-    println!();
+    say!("🎮 Welcome to Testnet Trading Demo!");
+    say!("💰 All trades use FAKE MONEY - completely risk-free!");
+    say!();
 
     // Check account info and balances
-    println!("📊 Getting account information...");
-    match trader.get_account_info().await {
+    say!("📊 Getting account information...");
+    match trader.get_spot_account().await {
         Ok(account) => {
-            println!("✅ Account Status: {}", if account.can_trade { "Trading Enabled" } else { "Trading Disabled" });
-            println!();
-            println!("💳 Your Fake Balances:");
+            if json {
+                emit_json("account", &json!({ "can_trade": account.can_trade }));
+                let balances: Vec<_> = account.balances.iter()
+                    .filter(|b| b.free + b.locked > 0.0)
+                    .collect();
+                emit_json("balances", &balances);
+            }
+            say!("✅ Account Status: {}", if account.can_trade { "Trading Enabled" } else { "Trading Disabled" });
+            say!();
+            say!("💳 Your Fake Balances:");
             
             // Show main balances (BTC, ETH, USDT, etc.)
             let important_assets = ["BTC", "ETH", "USDT", "BNB"];
             for balance in &account.balances {
                 if important_assets.contains(&balance.asset.as_str()) && balance.free > 0.0 {
-                    println!("   {} {:.8} {} (Free: {:.8}, Locked: {:.8})", 
+                    say!("   {} {:.8} {} (Free: {:.8}, Locked: {:.8})", 
                             if balance.asset == "USDT" { "💵" } else { "🪙" },
                             balance.free + balance.locked,
                             balance.asset,
@@ -46,62 +73,92 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         Err(e) => {
-            println!("❌ Failed to get account info: {}", e);
+            if json { emit_json_error(&format!("Failed to get account info: {}", e)); }
+            say!("❌ Failed to get account info: {}", e);
             return Ok(());
         }
     }
 
-    println!();
+    say!();
 
     // Get current Bitcoin price
-    println!("📈 Getting current Bitcoin price...");
+    say!("📈 Getting current Bitcoin price...");
     match trader.get_current_price("BTCUSDT").await {
         Ok(price) => {
-            println!("💰 Current BTC/USDT Price: ${:.2}", price);
+            if json { emit_json("price", &json!({ "symbol": "BTCUSDT", "price": price })); }
+            say!("💰 Current BTC/USDT Price: ${:.2}", price);
             
-            // Demo: Place a small limit buy order (will likely not fill immediately)
-            println!();
-            println!("🛒 Demo: Placing a limit BUY order for 0.001 BTC at 10% below market price...");
+            // Demo: Place a small limit buy order and manage it until it fills or times out
+            say!();
+            say!("🛒 Demo: Placing a limit BUY order for 0.001 BTC at 10% below market price...");
             let buy_price = price * 0.9; // 10% below market
             let quantity = 0.001; // Small amount for testing
-            
-            match trader.place_limit_order("BTCUSDT", OrderSide::Buy, quantity, buy_price).await {
+            let fill_timeout = Duration::from_secs(10);
+
+            let order = NewOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                order_type: OrderType::Limit,
+                time_in_force: None,
+                quantity: Some(quantity),
+                quote_order_qty: None,
+                price: Some(buy_price),
+                timestamp: None,
+            };
+
+            say!("⏳ Waiting up to {}s for the order to fill at ${:.2}...", fill_timeout.as_secs(), buy_price);
+            let result = place_and_manage(
+                &trader,
+                &order,
+                fill_timeout,
+                |filled| {
+                    if json { emit_json("order_filled", filled); }
+                    say!("✅ Order {} filled: {} BTC for ${} USDT",
+                             filled.order_id, filled.executed_qty, filled.cummulative_quote_qty);
+                },
+                |canceled| {
+                    if json { emit_json("order_timed_out", canceled); }
+                    say!("⌛ Order {} didn't fill in time and was canceled", canceled.order_id);
+                    say!("   Filled before cancel: {} of {} BTC", canceled.executed_qty, canceled.orig_qty);
+                    say!("   (Expected - it was priced 10% below market)");
+                },
+            ).await;
+
+            match result {
                 Ok(order) => {
-                    println!("✅ Limit order placed successfully!");
-                    println!("   Order ID: {}", order.order_id);
-                    println!("   Status: {:?}", order.status);
-                    println!("   Quantity: {} BTC", order.orig_qty);
-                    println!("   Price: ${}", order.price);
-                    println!("   Side: {}", order.side);
-                    println!();
-                    println!("💡 This order will only fill if Bitcoin drops to ${:.2}", buy_price);
-                    println!("   (Since it's 10% below market, it probably won't fill immediately)");
+                    if json { emit_json("order", &order); }
+                    say!("   Final status: {:?}", order.status);
+                    if let Some(reason) = order.reject_reason() {
+                        say!("   Reason: {}", reason);
+                    }
                 }
                 Err(e) => {
-                    println!("❌ Failed to place order: {}", e);
-                    println!("   This might be due to insufficient balance or API issues.");
+                    if json { emit_json_error(&format!("Order lifecycle failed: {}", e)); }
+                    say!("❌ Order lifecycle failed: {}", e);
+                    say!("   This might be due to insufficient balance or API issues.");
                 }
             }
 
             // Alternative demo: Show what a market order would look like (but don't execute)
-            println!();
-            println!("📝 What a MARKET order would look like:");
-            println!("   - Market BUY 0.0001 BTC would execute immediately at ~${:.2}", price);
-            println!("   - Market SELL would execute immediately at current bid price");
-            println!("   (Not executing to preserve your fake balance)");
+            say!();
+            say!("📝 What a MARKET order would look like:");
+            say!("   - Market BUY 0.0001 BTC would execute immediately at ~${:.2}", price);
+            say!("   - Market SELL would execute immediately at current bid price");
+            say!("   (Not executing to preserve your fake balance)");
         }
         Err(e) => {
-            println!("❌ Failed to get current price: {}", e);
+            if json { emit_json_error(&format!("Failed to get current price: {}", e)); }
+            say!("❌ Failed to get current price: {}", e);
         }
     }
 
-    println!();
-    println!("🎯 Next Steps:");
-    println!("   1. Check your orders on https://testnet.binance.vision/");
-    println!("   2. Try different trading strategies");
-    println!("   3. Add LLM integration for AI trading decisions!");
-    println!();
-    println!("🚀 Ready to build an AI trading bot with this foundation!");
+    say!();
+    say!("🎯 Next Steps:");
+    say!("   1. Check your orders on https://testnet.binance.vision/");
+    say!("   2. Try different trading strategies");
+    say!("   3. Add LLM integration for AI trading decisions!");
+    say!();
+    say!("🚀 Ready to build an AI trading bot with this foundation!");
 
     Ok(())
 }
\ No newline at end of file
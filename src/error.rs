@@ -0,0 +1,200 @@
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+/// Errors produced by the trading client and the market data stream
+/// Returned directly by their public methods, so callers can `match` on the cause,
+/// e.g. `TradingError::ApiError { code: -2010, .. }` for insufficient balance
+#[derive(Debug)]
+pub enum TradingError {
+    /// The HTTP request failed before Binance answered (DNS, connect, TLS, timeout...)
+    Network(reqwest::Error),
+    /// Binance rejected the request, e.g. `{"code":-2011,"msg":"Unknown order sent."}`
+    ApiError { code: i32, msg: String },
+    /// Binance rejected the request signature (-1022), usually a wrong secret key
+    Signature(String),
+    /// A response body didn't have the expected shape
+    Deserialize(serde_json::Error),
+    /// The symbol isn't listed in the exchange info
+    InvalidSymbol(String),
+    /// The WebSocket connection failed or was closed with an error
+    WebSocket(String),
+    /// Order rejected locally because its notional value exceeds the configured cap
+    OrderTooLarge { notional: f64, limit: f64 },
+    /// A request parameter failed local validation, so nothing was sent
+    InvalidParameter(String),
+    /// The request (retries included) didn't finish within the configured deadline
+    DeadlineExceeded { budget: Duration },
+    /// The symbol isn't in TRADING status (halted, break, delisted...), so nothing was sent
+    SymbolHalted { symbol: String, status: String },
+    /// A SELL asked for more of the base asset than we hold (strict `SellGuard`)
+    InsufficientHoldings { asset: String, requested: f64, available: f64 },
+    /// The exchange answered with a 5xx: its problem, not a rejection of the request
+    /// Transient (the testnet does this a lot), but for orders the outcome is unknown
+    ServerError { status: u16 },
+    /// Any other failure, kept as its message
+    Other(String),
+}
+
+/// Shape of the JSON body Binance sends back with a failed request
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    code: i32,
+    msg: String,
+}
+
+impl TradingError {
+    /// Binance error code for canceling/querying an order that isn't open anymore
+    pub const UNKNOWN_ORDER: i32 = -2011;
+
+    /// Binance error code for a timestamp outside recvWindow (or ahead of the server clock)
+    pub const TIMESTAMP_OUT_OF_WINDOW: i32 = -1021;
+
+    /// Binance error code for a request whose signature doesn't match
+    pub const INVALID_SIGNATURE: i32 = -1022;
+
+    /// Binance error code for querying an order that doesn't exist
+    pub const NO_SUCH_ORDER: i32 = -2013;
+
+    /// "Timeout waiting for response from backend server. Send status unknown; execution status unknown."
+    pub const SEND_STATUS_UNKNOWN: i32 = -1007;
+
+    /// Human-readable reason for an exchange rejection, e.g.
+    /// "Account has insufficient balance for requested action. (code -2010)"
+    /// None for errors that didn't come from the exchange
+    pub fn reject_reason(&self) -> Option<String> {
+        match self {
+            TradingError::ApiError { code, msg } => Some(format!("{} (code {})", msg, code)),
+            _ => None,
+        }
+    }
+
+    /// Whether trying again later may well succeed (5xx, deadline), as opposed to a rejection
+    pub fn is_transient(&self) -> bool {
+        matches!(self, TradingError::ServerError { .. } | TradingError::DeadlineExceeded { .. })
+    }
+
+    /// Recover a `TradingError` from a boxed error, or wrap its message in `Other`
+    pub fn from_boxed(err: Box<dyn std::error::Error>) -> TradingError {
+        match err.downcast::<TradingError>() {
+            Ok(trading_error) => *trading_error,
+            Err(err) => TradingError::Other(err.to_string()),
+        }
+    }
+
+    /// Try to parse a Binance error body into an `ApiError` (or `Signature` for -1022)
+    /// Returns None when the body isn't the usual `{"code", "msg"}` JSON
+    /// (e.g. an HTML page from a proxy or a gateway error)
+    pub fn from_response_body(body: &str) -> Option<TradingError> {
+        serde_json::from_str::<ApiErrorBody>(body)
+            .ok()
+            .map(|b| match b.code {
+                TradingError::INVALID_SIGNATURE => TradingError::Signature(b.msg),
+                code => TradingError::ApiError { code, msg: b.msg },
+            })
+    }
+}
+
+impl fmt::Display for TradingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TradingError::Network(e) => write!(f, "Network error: {}", e),
+            TradingError::ApiError { code, msg } => write!(f, "API Error {}: {}", code, msg),
+            TradingError::Signature(msg) => {
+                write!(f, "API Error {}: {}", TradingError::INVALID_SIGNATURE, msg)
+            }
+            TradingError::Deserialize(e) => write!(f, "Unexpected response: {}", e),
+            TradingError::InvalidSymbol(symbol) => write!(f, "Unknown symbol: {}", symbol),
+            TradingError::WebSocket(msg) => write!(f, "WebSocket error: {}", msg),
+            TradingError::OrderTooLarge { notional, limit } => {
+                write!(f, "Order notional {:.2} exceeds the maximum of {:.2}", notional, limit)
+            }
+            TradingError::InvalidParameter(reason) => write!(f, "Invalid parameter: {}", reason),
+            TradingError::DeadlineExceeded { budget } => {
+                write!(f, "Request did not complete within its {:?} deadline", budget)
+            }
+            TradingError::SymbolHalted { symbol, status } => {
+                write!(f, "{} is not trading (status {})", symbol, status)
+            }
+            TradingError::InsufficientHoldings { asset, requested, available } => {
+                write!(f, "Refusing to sell {} {}: only {} available", requested, asset, available)
+            }
+            TradingError::ServerError { status } => {
+                write!(f, "Exchange server error (HTTP {}), not a rejection; the request may be retried", status)
+            }
+            TradingError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for TradingError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TradingError::Network(e) => Some(e),
+            TradingError::Deserialize(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Errors that wrap a library error are equal when their messages are
+impl PartialEq for TradingError {
+    fn eq(&self, other: &Self) -> bool {
+        use TradingError::*;
+        match (self, other) {
+            (Network(a), Network(b)) => a.to_string() == b.to_string(),
+            (ApiError { code: a, msg: m }, ApiError { code: b, msg: n }) => a == b && m == n,
+            (Signature(a), Signature(b)) => a == b,
+            (Deserialize(a), Deserialize(b)) => a.to_string() == b.to_string(),
+            (InvalidSymbol(a), InvalidSymbol(b)) => a == b,
+            (WebSocket(a), WebSocket(b)) => a == b,
+            (OrderTooLarge { notional: a, limit: l }, OrderTooLarge { notional: b, limit: m }) => a == b && l == m,
+            (InvalidParameter(a), InvalidParameter(b)) => a == b,
+            (DeadlineExceeded { budget: a }, DeadlineExceeded { budget: b }) => a == b,
+            (SymbolHalted { symbol: a, status: s }, SymbolHalted { symbol: b, status: t }) => a == b && s == t,
+            (
+                InsufficientHoldings { asset: a, requested: r, available: v },
+                InsufficientHoldings { asset: b, requested: q, available: w },
+            ) => a == b && r == q && v == w,
+            (ServerError { status: a }, ServerError { status: b }) => a == b,
+            (Other(a), Other(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for TradingError {
+    fn from(e: reqwest::Error) -> Self {
+        TradingError::Network(e)
+    }
+}
+
+impl From<serde_json::Error> for TradingError {
+    fn from(e: serde_json::Error) -> Self {
+        TradingError::Deserialize(e)
+    }
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for TradingError {
+    fn from(e: tokio_tungstenite::tungstenite::Error) -> Self {
+        TradingError::WebSocket(e.to_string())
+    }
+}
+
+impl From<Box<dyn std::error::Error>> for TradingError {
+    fn from(e: Box<dyn std::error::Error>) -> Self {
+        TradingError::from_boxed(e)
+    }
+}
+
+impl From<String> for TradingError {
+    fn from(msg: String) -> Self {
+        TradingError::Other(msg)
+    }
+}
+
+impl From<&str> for TradingError {
+    fn from(msg: &str) -> Self {
+        TradingError::Other(msg.to_string())
+    }
+}
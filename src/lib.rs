@@ -1,15 +1,20 @@
+pub mod error;        // Error types returned by the trading client
 pub mod market_data;  // Real-time price data and WebSocket connections
-pub mod portfolio;    // Portfolio management (currently just stubs)
-pub mod orders;       // Order management (currently just stubs)
-pub mod strategies;   // Trading strategies (currently just stubs)
+pub mod portfolio;    // Local position and P&L tracking
+pub mod orders;       // Order lifecycle helpers built on the trading client
+pub mod output;       // Human vs JSON-lines output for the binaries
+pub mod strategies;   // Strategy trait, signals and the strategy runner
 pub mod trading;      // Main trading client and types
 
+/// User-Agent sent on REST requests and the WebSocket handshake (overridable on each client)
+pub const DEFAULT_USER_AGENT: &str = concat!("rust-trading-system/", env!("CARGO_PKG_VERSION"));
+
 // Unit tests - these run when you do `cargo test --lib`
 // The #[cfg(test)] attribute means this code only compiles during testing
 #[cfg(test)]
 mod tests {
     // Import the types we need for testing
-    use crate::trading::{Balance, OrderSide, TestnetTrader};
+    use crate::trading::{Balance, OrderId, OrderSide, TestnetTrader};
     use std::collections::HashMap;
 
     /// Tests for trading data types (Balance, OrderSide, etc.)
@@ -54,6 +59,303 @@ mod tests {
             let _expired = OrderStatus::Expired;
             let _partially_filled = OrderStatus::PartiallyFilled;
         }
+
+        /// Test that OrderResponse::timestamp prefers transactTime and falls back to time
+        #[test]
+        fn test_order_response_timestamp() {
+            use crate::trading::OrderResponse;
+
+            let placed = r#"{"symbol":"BTCUSDT","orderId":1,"orderListId":-1,"clientOrderId":"a",
+                "transactTime":1640995200000,"time":1,"price":"0","origQty":"1","executedQty":"0",
+                "cummulativeQuoteQty":"0","status":"NEW","timeInForce":"GTC","type":"LIMIT","side":"BUY"}"#;
+            let listed = r#"{"symbol":"BTCUSDT","orderId":1,"orderListId":-1,"clientOrderId":"a",
+                "time":1640995260000,"price":"0","origQty":"1","executedQty":"0",
+                "cummulativeQuoteQty":"0","status":"NEW","timeInForce":"GTC","type":"LIMIT","side":"BUY"}"#;
+
+            let placed: OrderResponse = serde_json::from_str(placed).unwrap();
+            let listed: OrderResponse = serde_json::from_str(listed).unwrap();
+
+            assert_eq!(placed.timestamp(), Some(1640995200000));
+            assert_eq!(listed.timestamp(), Some(1640995260000));
+            assert_eq!(
+                listed.datetime().unwrap().format("%Y-%m-%d %H:%M:%S").to_string(),
+                "2022-01-01 00:01:00"
+            );
+        }
+
+        /// Test that responses with extra unknown fields and missing optional fields still parse
+        /// Binance adds fields from time to time; that must never break order handling
+        #[test]
+        fn test_order_response_tolerates_schema_changes() {
+            use crate::trading::{OrderResponse, OrderStatus};
+
+            // Unknown fields (workingTime, selfTradePreventionMode, fills, someFutureField),
+            // no transactTime/time and no orderListId
+            let json = r#"{"symbol":"BTCUSDT","orderId":28,"clientOrderId":"6gCrw2kRUAF9CvJDGP16IP",
+                "price":"0.00000000","origQty":"10.00000000","executedQty":"10.00000000",
+                "cummulativeQuoteQty":"10.00000000","status":"FILLED","timeInForce":"GTC",
+                "type":"MARKET","side":"SELL","workingTime":1507725176595,
+                "selfTradePreventionMode":"NONE","someFutureField":{"nested":true},
+                "fills":[{"price":"4000.00000000","qty":"1.00000000","commission":"4.00000000",
+                          "commissionAsset":"USDT","tradeId":56}]}"#;
+
+            let order: OrderResponse = serde_json::from_str(json).unwrap();
+            assert_eq!(order.order_id, OrderId(28));
+            assert_eq!(order.order_list_id, -1);
+            assert_eq!(order.status, OrderStatus::Filled);
+            assert_eq!(order.timestamp(), None);
+        }
+
+        /// Test that an order status we don't know about doesn't fail parsing
+        #[test]
+        fn test_unknown_order_status() {
+            use crate::trading::OrderStatus;
+
+            let status: OrderStatus = serde_json::from_str(r#""SOME_NEW_STATUS""#).unwrap();
+            assert_eq!(status, OrderStatus::Unknown);
+            let status: OrderStatus = serde_json::from_str(r#""EXPIRED_IN_MATCH""#).unwrap();
+            assert_eq!(status, OrderStatus::ExpiredInMatch);
+        }
+
+        /// Test that the full account response (with many fields we don't model) parses
+        #[test]
+        fn test_account_info_ignores_extra_fields() {
+            use crate::trading::AccountInfo;
+
+            let json = r#"{"makerCommission":15,"takerCommission":15,"canTrade":true,
+                "canWithdraw":true,"canDeposit":true,"brokered":false,"accountType":"SPOT",
+                "balances":[{"asset":"BTC","free":"4723846.89208129","locked":"0.00000000"}],
+                "permissions":["SPOT"],"uid":354937868}"#;
+
+            let account: AccountInfo = serde_json::from_str(json).unwrap();
+            assert_eq!(account.balances.len(), 1);
+            assert!(account.can_trade);
+        }
+
+        /// Test reject_reason on orders that come back with status REJECTED
+        #[test]
+        fn test_order_response_reject_reason() {
+            use crate::trading::OrderResponse;
+
+            let base = r#""symbol":"BTCUSDT","orderId":1,"clientOrderId":"a","price":"0","origQty":"1",
+                "executedQty":"0","cummulativeQuoteQty":"0","timeInForce":"GTC","type":"LIMIT","side":"BUY""#;
+
+            let with_reason: OrderResponse = serde_json::from_str(
+                &format!(r#"{{{},"status":"REJECTED","rejectReason":"INSUFFICIENT_BALANCE"}}"#, base)).unwrap();
+            let without_reason: OrderResponse = serde_json::from_str(
+                &format!(r#"{{{},"status":"REJECTED"}}"#, base)).unwrap();
+            let accepted: OrderResponse = serde_json::from_str(
+                &format!(r#"{{{},"status":"NEW"}}"#, base)).unwrap();
+
+            assert_eq!(with_reason.reject_reason(), Some("INSUFFICIENT_BALANCE"));
+            assert_eq!(without_reason.reject_reason(), Some("no reason provided by the exchange"));
+            assert_eq!(accepted.reject_reason(), None);
+        }
+
+        /// Test that OrderId works as a set key and serializes as a bare number
+        #[test]
+        fn test_order_id_newtype() {
+            use std::collections::HashSet;
+
+            let mut seen: HashSet<OrderId> = HashSet::new();
+            seen.insert(OrderId::from(28));
+            assert!(seen.contains(&OrderId(28)));
+            assert!(!seen.insert(OrderId(28)));
+
+            assert_eq!(serde_json::to_string(&OrderId(28)).unwrap(), "28");
+            assert_eq!(serde_json::from_str::<OrderId>("28").unwrap(), OrderId(28));
+            assert_eq!(" 28\n".parse::<OrderId>().unwrap(), OrderId(28));
+            assert_eq!(OrderId(28).to_string(), "28");
+        }
+
+        /// Test that a partial JSON config fills in defaults and builds a matching trader
+        #[test]
+        fn test_trader_config_from_json() {
+            use crate::trading::{TimeUnit, TraderConfig};
+
+            let config: TraderConfig = serde_json::from_str(r#"{
+                "api_key": "key_1",
+                "secret_key": "secret_1",
+                "backup_keys": [{"api_key": "key_2", "secret_key": "secret_2"}],
+                "dry_run": true,
+                "time_unit": "microsecond"
+            }"#).unwrap();
+
+            assert_eq!(config.base_url, "https://testnet.binance.vision");
+            assert_eq!(config.max_order_notional, None);
+            // Debug output must not contain the secret
+            assert!(!format!("{:?}", config).contains("secret_1"));
+
+            let trader = TestnetTrader::from_config(config);
+            assert!(trader.is_dry_run());
+            assert_eq!(trader.key_count(), 2);
+            assert_eq!(trader.time_unit(), TimeUnit::Microsecond);
+            assert_eq!(trader.user_agent(), crate::DEFAULT_USER_AGENT);
+
+            // Missing or empty keys fail at load time, not on the first signed call
+            assert!(serde_json::from_str::<TraderConfig>(r#"{"dry_run": true}"#).is_err());
+            assert!(serde_json::from_str::<TraderConfig>(r#"{"api_key": "", "secret_key": "s"}"#).is_err());
+        }
+
+        /// Test that commission rates parse from account info and drive the standard fee estimate
+        #[test]
+        fn test_fee_estimator_standard_rates() {
+            use crate::trading::{AccountInfo, FeeEstimator};
+
+            let account: AccountInfo = serde_json::from_str(r#"{
+                "balances": [], "canTrade": true, "canWithdraw": true, "canDeposit": true,
+                "commissionRates": {"maker": "0.00100000", "taker": "0.00150000", "buyer": "0.00000000", "seller": "0.00010000"}
+            }"#).unwrap();
+            let fees = FeeEstimator::from_account(&account, false).unwrap();
+
+            assert!((fees.effective_fee(OrderSide::Buy, true) - 0.001).abs() < 1e-12);
+            assert!((fees.effective_fee(OrderSide::Buy, false) - 0.0015).abs() < 1e-12);
+            // The seller rate is added on top of maker/taker
+            assert!((fees.effective_fee(OrderSide::Sell, false) - 0.0016).abs() < 1e-12);
+            assert!((fees.estimate(OrderSide::Buy, false, 1000.0) - 1.5).abs() < 1e-9);
+        }
+
+        /// Test that paying fees in BNB applies the discount to the effective rate
+        #[test]
+        fn test_fee_estimator_bnb_discount() {
+            use crate::trading::{CommissionRates, FeeEstimator};
+
+            let rates = CommissionRates { maker: 0.001, taker: 0.001, buyer: 0.0, seller: 0.0 };
+            let fees = FeeEstimator::new(rates, true);
+            assert!((fees.effective_fee(OrderSide::Buy, false) - 0.00075).abs() < 1e-12);
+            assert!((fees.effective_fee(OrderSide::Sell, true) - 0.00075).abs() < 1e-12);
+
+            // A custom discount replaces the default 25%
+            let half_off = fees.with_bnb_discount(0.5);
+            assert!((half_off.effective_fee(OrderSide::Buy, false) - 0.0005).abs() < 1e-12);
+        }
+
+        /// Test that AccountSnapshot only reports a change when a balance differs
+        #[test]
+        fn test_account_snapshot_apply() {
+            use crate::trading::{AccountSnapshot, OutboundAccountPosition};
+
+            let update: OutboundAccountPosition = serde_json::from_str(
+                r#"{"e":"outboundAccountPosition","E":2,"u":2,"B":[{"a":"ETH","f":"2.5","l":"0"}]}"#
+            ).unwrap();
+
+            let mut snapshot = AccountSnapshot::default();
+            assert!(snapshot.apply(&update));
+            assert_eq!(snapshot.free("ETH"), 2.5);
+            // Same balances again - nothing to re-render
+            assert!(!snapshot.apply(&update));
+
+            // Older updates are ignored
+            let stale: OutboundAccountPosition = serde_json::from_str(
+                r#"{"e":"outboundAccountPosition","E":1,"u":1,"B":[{"a":"ETH","f":"9","l":"0"}]}"#
+            ).unwrap();
+            assert!(!snapshot.apply(&stale));
+            assert_eq!(snapshot.free("ETH"), 2.5);
+        }
+
+        /// Test OrderSide conveniences: flipping, Binance spelling and lenient parsing
+        #[test]
+        fn test_order_side_conveniences() {
+            assert_eq!(OrderSide::Buy.opposite(), OrderSide::Sell);
+            assert_eq!(OrderSide::Sell.opposite().opposite(), OrderSide::Sell);
+            assert_eq!(OrderSide::Buy.as_str(), "BUY");
+            assert_eq!(OrderSide::Sell.as_str(), "SELL");
+
+            assert_eq!("BUY".parse::<OrderSide>().unwrap(), OrderSide::Buy);
+            assert_eq!(" sell ".parse::<OrderSide>().unwrap(), OrderSide::Sell);
+            assert_eq!(OrderSide::try_from("Buy").unwrap(), OrderSide::Buy);
+            assert!("hold".parse::<OrderSide>().is_err());
+        }
+
+        /// Test direct, inverse and one-hop conversion rates
+        #[test]
+        fn test_price_graph_conversions() {
+            use crate::trading::{ConversionLeg, PriceGraph};
+
+            let mut graph = PriceGraph::new();
+            graph.add_pair("BTCUSDT", "BTC", "USDT", 50000.0);
+            graph.add_pair("ETHBTC", "ETH", "BTC", 0.05);
+            graph.add_pair("SHIBDOGE", "SHIB", "DOGE", 0.001);
+
+            // Direct: the pair exists as written
+            assert_eq!(graph.rate("BTC", "USDT"), Some(50000.0));
+            // Inverse: only BTCUSDT exists, so USDT -> BTC divides
+            assert_eq!(graph.rate("USDT", "BTC"), Some(1.0 / 50000.0));
+            // One hop: ETH -> BTC -> USDT
+            let path = graph.path("ETH", "USDT").unwrap();
+            assert_eq!(path.legs.len(), 2);
+            assert!(matches!(&path.legs[0], ConversionLeg::Direct { symbol, .. } if symbol == "ETHBTC"));
+            assert!((path.rate - 2500.0).abs() < 1e-9);
+            // ...and back, inverting both legs
+            assert!((graph.rate("USDT", "ETH").unwrap() - 1.0 / 2500.0).abs() < 1e-12);
+            assert_eq!(graph.convert(2.0, "ETH", "ETH"), Some(2.0));
+
+            // Nothing connects SHIB to USDT
+            assert_eq!(graph.rate("SHIB", "USDT"), None);
+        }
+
+        /// Test that placed and open-order responses normalize to the same shape
+        #[test]
+        fn test_order_response_normalize() {
+            use crate::trading::{NormalizedOrder, OrderResponse, OrderSide};
+
+            let placed = r#"{"symbol":"BTCUSDT","orderId":7,"orderListId":-1,"clientOrderId":"a",
+                "transactTime":1640995200000,"price":"45000.00","origQty":"0.002","executedQty":"0.001",
+                "cummulativeQuoteQty":"45.00","status":"PARTIALLY_FILLED","timeInForce":"GTC","type":"LIMIT","side":"BUY"}"#;
+            let listed = r#"{"symbol":"BTCUSDT","orderId":7,"orderListId":-1,"clientOrderId":"a",
+                "time":1640995200000,"updateTime":1640995260000,"price":"45000.00","origQty":"0.002","executedQty":"0.001",
+                "cummulativeQuoteQty":"45.00","status":"PARTIALLY_FILLED","timeInForce":"GTC","type":"LIMIT","side":"BUY"}"#;
+
+            let placed: OrderResponse = serde_json::from_str(placed).unwrap();
+            let listed: OrderResponse = serde_json::from_str(listed).unwrap();
+            let normalized = placed.normalize().unwrap();
+
+            assert_eq!(normalized, listed.normalize().unwrap());
+            assert_eq!(normalized.timestamp, 1640995200000);
+            assert_eq!(normalized.side, OrderSide::Buy);
+            assert_eq!(normalized.price, 45000.0);
+            assert!((normalized.remaining_qty() - 0.001).abs() < 1e-12);
+            assert_eq!(normalized.average_fill_price(), Some(45000.0));
+
+            // No timestamp at all, or an amount that isn't a number, is an error
+            let mut undated = listed.clone();
+            undated.time = None;
+            assert!(NormalizedOrder::try_from(&undated).is_err());
+            let mut garbled = listed;
+            garbled.executed_qty = "n/a".to_string();
+            assert!(garbled.normalize().is_err());
+        }
+
+        /// Test the sliding-window limiter: waits for the oldest request to expire, keeps usage across set_limits
+        #[tokio::test(start_paused = true)]
+        async fn test_rate_limiter_window() {
+            use crate::trading::{RateLimit, RateLimitInterval, RateLimitType, RateLimiter, RateLimits};
+            use std::time::Duration;
+
+            let weight = |limit: u32| RateLimit {
+                rate_limit_type: RateLimitType::RequestWeight,
+                interval: RateLimitInterval::Second,
+                interval_num: 10,
+                limit,
+            };
+            let limiter = RateLimiter::new(&RateLimits::from(vec![weight(5)]));
+
+            assert!(limiter.try_acquire(3, false).is_ok());
+            tokio::time::advance(Duration::from_secs(4)).await;
+            assert!(limiter.try_acquire(2, false).is_ok());
+            // Full: 3 more fits once the first request (weight 3) leaves the window in 6s
+            assert_eq!(limiter.try_acquire(3, false), Err(Duration::from_secs(6)));
+
+            let started = tokio::time::Instant::now();
+            limiter.acquire(3, false).await;
+            assert_eq!(started.elapsed(), Duration::from_secs(6));
+            // Heavier than the whole limit: waits until the window is empty (the last request leaves in 10s)
+            assert_eq!(limiter.try_acquire(50, false), Err(Duration::from_secs(10)));
+
+            // Same limit stays counted after set_limits; a new one starts empty
+            limiter.set_limits(&RateLimits::from(vec![weight(5), weight(100)]));
+            assert_eq!(limiter.usage().iter().map(|(_, used)| *used).collect::<Vec<_>>(), vec![5, 0]);
+        }
     }
 
     /// Tests for cryptographic signature functionality
@@ -127,57 +429,4171 @@ mod tests {
             assert!(signature.chars().all(|c| c.is_ascii_hexdigit()));
             assert_eq!(signature.len(), 64);
         }
-    }
 
-    /// Tests for market data functionality
-    /// These verify our real-time data structures work correctly
-    mod market_data_tests {
-        use crate::market_data::{Ticker, MarketDataEvent, TradeSide};
+        /// Test that rotate_key cycles through all key pairs and wraps around
+        #[test]
+        fn test_rotate_key_wraps_around() {
+            let trader = create_test_trader()
+                .with_additional_credentials("backup_key".to_string(), "backup_secret".to_string());
 
-        /// Test basic Ticker struct creation and field access
+            assert_eq!(trader.key_count(), 2);
+            assert_eq!(trader.active_api_key(), "test_api_key");
+            assert_eq!(trader.rotate_key(), 1);
+            assert_eq!(trader.active_api_key(), "backup_key");
+            assert_eq!(trader.rotate_key(), 0);
+            assert_eq!(trader.active_api_key(), "test_api_key");
+        }
+
+        /// Test that signatures follow the active key's secret
         #[test]
-        fn test_ticker_creation() {
-            let ticker = Ticker {
+        fn test_sign_uses_active_secret() {
+            let trader = create_test_trader()
+                .with_additional_credentials("backup_key".to_string(), "backup_secret".to_string());
+            let query = "symbol=BTCUSDT&timestamp=1640995200000";
+
+            let first = trader.sign(query);
+            trader.rotate_key();
+            let second = trader.sign(query);
+
+            assert_ne!(first, second, "Different secrets must produce different signatures");
+        }
+
+        /// Test the offline signing self-test, including a secret pasted with a trailing newline
+        #[test]
+        fn test_verify_signing() {
+            assert!(create_test_trader().verify_signing());
+
+            let bad = TestnetTrader::new("test_api_key".to_string(), "test_secret_key\n".to_string());
+            assert!(!bad.verify_signing());
+        }
+    }
+
+    /// Tests for TestnetTrader REST endpoints
+    /// These run against a local wiremock server, so no API keys or network are needed
+    mod trading_client_tests {
+        use super::*;
+        use serde_json::json;
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        /// Helper to create a trader pointed at the mock server
+        fn mock_trader(server: &MockServer) -> TestnetTrader {
+            TestnetTrader::new(
+                "test_api_key".to_string(),
+                "test_secret_key".to_string(),
+            ).with_base_url(server.uri())
+        }
+
+        /// Helper to build an order JSON body the way Binance returns it
+        fn order_json(order_id: u64, status: &str) -> serde_json::Value {
+            json!({
+                "symbol": "BTCUSDT",
+                "orderId": order_id,
+                "orderListId": -1,
+                "clientOrderId": "test_client_id",
+                "transactTime": 1640995200000u64,
+                "price": "45000.00",
+                "origQty": "0.00100000",
+                "executedQty": "0.00000000",
+                "cummulativeQuoteQty": "0.00000000",
+                "status": status,
+                "timeInForce": "GTC",
+                "type": "LIMIT",
+                "side": "BUY"
+            })
+        }
+
+        /// Test that canceling an open order reports it as canceled
+        #[tokio::test]
+        async fn test_cancel_order_if_open_cancels() {
+            use crate::trading::CancelOutcome;
+
+            let mock_server = MockServer::start().await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(42, "CANCELED")))
+                .mount(&mock_server)
+                .await;
+
+            let outcome = mock_trader(&mock_server).cancel_order_if_open("BTCUSDT", OrderId(42)).await.unwrap();
+            match outcome {
+                CancelOutcome::Canceled(order) => assert_eq!(order.order_id, OrderId(42)),
+                CancelOutcome::AlreadyGone => panic!("Expected Canceled"),
+            }
+        }
+
+        /// Test that -2011 "Unknown order sent." (order already filled) is not an error
+        #[tokio::test]
+        async fn test_cancel_order_if_open_already_filled() {
+            use crate::trading::CancelOutcome;
+
+            let mock_server = MockServer::start().await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "code": -2011,
+                    "msg": "Unknown order sent."
+                })))
+                .mount(&mock_server)
+                .await;
+
+            let trader = mock_trader(&mock_server);
+            let outcome = trader.cancel_order_if_open("BTCUSDT", OrderId(42)).await.unwrap();
+            assert!(matches!(outcome, CancelOutcome::AlreadyGone));
+
+            // The plain cancel_order still reports it as an error, with the code intact
+            let err = trader.cancel_order("BTCUSDT", OrderId(42)).await.unwrap_err();
+            assert_eq!(
+                err,
+                crate::error::TradingError::ApiError {
+                    code: -2011,
+                    msg: "Unknown order sent.".to_string(),
+                }
+            );
+        }
+
+        /// Test that other cancel failures are still returned as errors
+        #[tokio::test]
+        async fn test_cancel_order_if_open_other_errors_propagate() {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "code": -1022,
+                    "msg": "Signature for this request is not valid."
+                })))
+                .mount(&mock_server)
+                .await;
+
+            let result = mock_trader(&mock_server).cancel_order_if_open("BTCUSDT", OrderId(42)).await;
+            assert!(result.is_err());
+        }
+
+        /// Test that the nested snapshotVos structure is parsed into daily snapshots
+        #[tokio::test]
+        async fn test_get_account_snapshots() {
+            use crate::trading::SnapshotType;
+
+            let mock_server = MockServer::start().await;
+
+            Mock::given(method("GET"))
+                .and(path("/sapi/v1/accountSnapshot"))
+                .and(query_param("type", "SPOT"))
+                .and(query_param("limit", "7"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "code": 200,
+                    "msg": "",
+                    "snapshotVos": [
+                        {
+                            "data": {
+                                "balances": [
+                                    {"asset": "BTC", "free": "0.09905021", "locked": "0.00000000"},
+                                    {"asset": "USDT", "free": "1.89109409", "locked": "0.00000000"}
+                                ],
+                                "totalAssetOfBtc": "0.09942700"
+                            },
+                            "type": "spot",
+                            "updateTime": 1576281599000u64
+                        }
+                    ]
+                })))
+                .mount(&mock_server)
+                .await;
+
+            let trader = mock_trader(&mock_server);
+            let snapshots = trader
+                .get_account_snapshots(SnapshotType::Spot, None, None, Some(7))
+                .await
+                .unwrap();
+
+            assert_eq!(snapshots.len(), 1);
+            assert_eq!(snapshots[0].update_time, 1576281599000);
+            assert_eq!(snapshots[0].data.total_asset_of_btc, 0.099427);
+            assert_eq!(snapshots[0].data.balances[0].asset, "BTC");
+
+            // HTTP 200 with a failure code in the body is still a typed API error
+            let failing = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/sapi/v1/accountSnapshot"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "code": -3026, "msg": "request param 'type' wrong"
+                })))
+                .mount(&failing)
+                .await;
+            let err = mock_trader(&failing)
+                .get_account_snapshots(SnapshotType::Spot, None, None, None)
+                .await
+                .unwrap_err();
+            assert_eq!(err, crate::error::TradingError::ApiError { code: -3026, msg: "request param 'type' wrong".to_string() });
+        }
+
+        /// Test failover: the first key is rejected, the trader rotates and retries
+        /// with the backup key, and that retry is signed with the backup key's secret
+        #[tokio::test]
+        async fn test_key_rotation_on_auth_error() {
+            use wiremock::matchers::header;
+
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .and(header("X-MBX-APIKEY", "test_api_key"))
+                .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                    "code": -2015,
+                    "msg": "Invalid API-key, IP, or permissions for action."
+                })))
+                .mount(&mock_server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .and(header("X-MBX-APIKEY", "backup_key"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "balances": [],
+                    "canTrade": true,
+                    "canWithdraw": false,
+                    "canDeposit": true
+                })))
+                .mount(&mock_server)
+                .await;
+
+            let trader = mock_trader(&mock_server)
+                .with_additional_credentials("backup_key".to_string(), "backup_secret".to_string());
+
+            let account = trader.get_spot_account().await.unwrap();
+            assert!(account.can_trade);
+            assert_eq!(trader.active_key_index(), 1);
+
+            // Verify the successful request was signed with the backup secret
+            let requests = mock_server.received_requests().await.unwrap();
+            let retried = requests.iter()
+                .find(|r| r.headers.get("X-MBX-APIKEY").unwrap() == "backup_key")
+                .unwrap();
+            let query = retried.url.query().unwrap();
+            let (unsigned, signature) = query.rsplit_once("&signature=").unwrap();
+            let expected = TestnetTrader::new("backup_key".to_string(), "backup_secret".to_string())
+                .sign(unsigned);
+            assert_eq!(signature, expected);
+        }
+
+        /// Test that with a single key, auth errors are returned instead of retried forever
+        #[tokio::test]
+        async fn test_single_key_auth_error_is_returned() {
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                    "code": -2015,
+                    "msg": "Invalid API-key, IP, or permissions for action."
+                })))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let result = mock_trader(&mock_server).get_spot_account().await;
+            assert!(result.is_err());
+        }
+
+        /// Test that spot balances land in the combined view and unsupported
+        /// wallet types are skipped rather than failing the call
+        #[tokio::test]
+        async fn test_get_account_info_spot_and_unsupported_margin() {
+            use crate::trading::AccountType;
+
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "balances": [
+                        {"asset": "BTC", "free": "1.00000000", "locked": "0.50000000"},
+                        {"asset": "USDT", "free": "100.00000000", "locked": "0.00000000"}
+                    ],
+                    "canTrade": true,
+                    "canWithdraw": false,
+                    "canDeposit": true
+                })))
+                .mount(&mock_server)
+                .await;
+
+            let balances = mock_trader(&mock_server)
+                .get_account_info(&[AccountType::Spot, AccountType::Margin])
+                .await
+                .unwrap();
+
+            assert_eq!(balances.wallet(AccountType::Spot).unwrap().len(), 2);
+            assert!(balances.wallet(AccountType::Margin).is_none());
+            assert_eq!(balances.total("BTC"), 1.5);
+            assert_eq!(balances.free("BTC"), 1.0);
+            assert_eq!(balances.total("ETH"), 0.0);
+        }
+
+        /// Test that REST requests carry the default User-Agent, and that it can be overridden
+        #[tokio::test]
+        async fn test_user_agent_header() {
+            use wiremock::matchers::header;
+
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/ticker/price"))
+                .and(header("User-Agent", crate::DEFAULT_USER_AGENT))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"symbol": "BTCUSDT", "price": "1.0"})))
+                .mount(&mock_server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/ticker/price"))
+                .and(header("User-Agent", "my-bot/2.0"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"symbol": "BTCUSDT", "price": "2.0"})))
+                .mount(&mock_server)
+                .await;
+
+            assert!(crate::DEFAULT_USER_AGENT.starts_with("rust-trading-system/"));
+            let trader = mock_trader(&mock_server);
+            assert_eq!(trader.get_current_price("BTCUSDT").await.unwrap(), 1.0);
+
+            let trader = trader.with_user_agent("my-bot/2.0".to_string());
+            assert_eq!(trader.get_current_price("BTCUSDT").await.unwrap(), 2.0);
+        }
+
+        /// Test that orders above the notional cap are refused before anything is sent
+        #[tokio::test]
+        async fn test_max_order_notional_guard() {
+            use crate::error::TradingError;
+            use crate::trading::OrderSide;
+
+            let mock_server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/ticker/price"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"symbol": "BTCUSDT", "price": "50000.00"})))
+                .mount(&mock_server)
+                .await;
+            // The order endpoint must never be hit
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "NEW")))
+                .expect(0)
+                .mount(&mock_server)
+                .await;
+
+            let trader = mock_trader(&mock_server).with_max_order_notional(1000.0);
+
+            // Limit order: 0.1 * 20000 = 2000 > 1000
+            let err = trader.place_limit_order("BTCUSDT", OrderSide::Buy, 0.1, 20000.0).await.unwrap_err();
+            assert_eq!(
+                err,
+                TradingError::OrderTooLarge { notional: 2000.0, limit: 1000.0 }
+            );
+
+            // Market order valued at the current price: 0.1 * 50000 = 5000 > 1000
+            let err = trader.place_market_order("BTCUSDT", OrderSide::Buy, 0.1).await.unwrap_err();
+            assert!(matches!(err, TradingError::OrderTooLarge { .. }));
+        }
+
+        /// Test that orders under the cap go through as usual
+        #[tokio::test]
+        async fn test_max_order_notional_allows_small_orders() {
+            use crate::trading::OrderSide;
+
+            let mock_server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(7, "NEW")))
+                .expect(1)
+                .mount(&mock_server)
+                .await;
+
+            let trader = mock_trader(&mock_server).with_max_order_notional(1000.0);
+            let order = trader.place_limit_order("BTCUSDT", OrderSide::Buy, 0.01, 45000.0).await.unwrap();
+            assert_eq!(order.order_id, OrderId(7));
+        }
+
+        /// Test that a rejected order surfaces Binance's code and message
+        #[tokio::test]
+        async fn test_order_rejection_reason_is_extracted() {
+            use crate::error::TradingError;
+            use crate::trading::OrderSide;
+
+            let mock_server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "code": -2010,
+                    "msg": "Account has insufficient balance for requested action."
+                })))
+                .mount(&mock_server)
+                .await;
+
+            let err = mock_trader(&mock_server)
+                .place_limit_order("BTCUSDT", OrderSide::Buy, 1.0, 100.0)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, TradingError::ApiError { code: -2010, .. }));
+            assert_eq!(
+                err.reject_reason().unwrap(),
+                "Account has insufficient balance for requested action. (code -2010)"
+            );
+        }
+
+        /// Test that check_auth tells a -1022 signature error apart from other failures
+        #[tokio::test]
+        async fn test_check_auth_distinguishes_signature_errors() {
+            use crate::trading::AuthCheck;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(400).set_body_string(
+                    r#"{"code":-1022,"msg":"Signature for this request is not valid."}"#,
+                ))
+                .mount(&server)
+                .await;
+            assert_eq!(
+                mock_trader(&server).check_auth().await,
+                AuthCheck::InvalidSignature("Signature for this request is not valid.".to_string())
+            );
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(400).set_body_string(
+                    r#"{"code":-1021,"msg":"Timestamp for this request is outside of the recvWindow."}"#,
+                ))
+                .mount(&server)
+                .await;
+            assert!(matches!(mock_trader(&server).check_auth().await, AuthCheck::Failed(_)));
+        }
+
+        /// Test that get_prices fetches several symbols in one request
+        #[tokio::test]
+        async fn test_get_prices_batch() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/ticker/price"))
+                .and(query_param("symbols", r#"["BTCUSDT","ETHUSDT"]"#))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                    {"symbol": "BTCUSDT", "price": "45000.10"},
+                    {"symbol": "ETHUSDT", "price": "3000.50"}
+                ])))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let prices = mock_trader(&server)
+                .get_prices(&["BTCUSDT", "ETHUSDT"])
+                .await
+                .unwrap();
+
+            assert_eq!(prices.len(), 2);
+            assert_eq!(prices["BTCUSDT"], 45000.10);
+            assert_eq!(prices["ETHUSDT"], 3000.50);
+        }
+
+        /// Test place_and_manage cancels an order that doesn't fill before the timeout
+        #[tokio::test]
+        async fn test_place_and_manage_cancels_on_timeout() {
+            use crate::orders::place_and_manage;
+            use crate::trading::{NewOrderRequest, OrderStatus, OrderType};
+            use std::time::Duration;
+
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(42, "NEW")))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(42, "NEW")))
+                .mount(&server)
+                .await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(42, "CANCELED")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let order = NewOrderRequest {
                 symbol: "BTCUSDT".to_string(),
-                price: 50000.0,
-                volume: 1000.0,
-                timestamp: 1640995200000,
+                side: OrderSide::Buy,
+                order_type: OrderType::Limit,
+                time_in_force: None,
+                quantity: Some(0.001),
+                quote_order_qty: None,
+                price: Some(45000.0),
+                timestamp: None,
             };
 
-            assert_eq!(ticker.symbol, "BTCUSDT");
-            assert_eq!(ticker.price, 50000.0);
-            assert_eq!(ticker.volume, 1000.0);
+            let mut filled = false;
+            let mut timed_out = false;
+            let result = place_and_manage(
+                &mock_trader(&server),
+                &order,
+                Duration::from_millis(200),
+                |_| filled = true,
+                |_| timed_out = true,
+            ).await.unwrap();
+
+            assert!(!filled);
+            assert!(timed_out);
+            assert_eq!(result.status, OrderStatus::Canceled);
         }
 
-        /// Test that MarketDataEvent enum pattern matching works
-        /// This verifies we can extract data from event streams
-        #[test]
-        fn test_market_data_event_matching() {
-            let ticker = Ticker {
+        /// Test place_and_manage reports a fill seen while polling
+        #[tokio::test]
+        async fn test_place_and_manage_reports_fill() {
+            use crate::orders::place_and_manage;
+            use crate::trading::{NewOrderRequest, OrderStatus, OrderType};
+            use std::time::Duration;
+
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(7, "NEW")))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(7, "FILLED")))
+                .mount(&server)
+                .await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(7, "CANCELED")))
+                .expect(0)
+                .mount(&server)
+                .await;
+
+            let order = NewOrderRequest {
                 symbol: "BTCUSDT".to_string(),
-                price: 50000.0,
-                volume: 1000.0,
-                timestamp: 1640995200000,
+                side: OrderSide::Buy,
+                order_type: OrderType::Limit,
+                time_in_force: None,
+                quantity: Some(0.001),
+                quote_order_qty: None,
+                price: Some(45000.0),
+                timestamp: None,
             };
 
-            let event = MarketDataEvent::Ticker(ticker.clone());
+            let mut filled_id = None;
+            let result = place_and_manage(
+                &mock_trader(&server),
+                &order,
+                Duration::from_secs(5),
+                |o| filled_id = Some(o.order_id),
+                |_| panic!("should not time out"),
+            ).await.unwrap();
 
-            // Test pattern matching to extract ticker data
-            match event {
-                MarketDataEvent::Ticker(t) => {
-                    assert_eq!(t.symbol, "BTCUSDT");
-                    assert_eq!(t.price, 50000.0);
+            assert_eq!(filled_id, Some(OrderId(7)));
+            assert_eq!(result.status, OrderStatus::Filled);
+        }
+
+        /// Test that the microsecond time unit sets the header and a microsecond timestamp
+        #[tokio::test]
+        async fn test_microsecond_time_unit() {
+            use crate::trading::TimeUnit;
+            use wiremock::matchers::header;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .and(header("X-MBX-TIME-UNIT", "MICROSECOND"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server).with_time_unit(TimeUnit::Microsecond);
+            trader.get_open_orders(None).await.unwrap();
+
+            // The signed timestamp must be in micros too (16 digits rather than 13)
+            let requests = server.received_requests().await.unwrap();
+            let query = requests[0].url.query().unwrap().to_string();
+            let timestamp = query.split('&')
+                .find_map(|kv| kv.strip_prefix("timestamp="))
+                .unwrap();
+            assert_eq!(timestamp.len(), 16);
+
+            let dt = TimeUnit::Microsecond.to_datetime(1_640_995_200_123_456).unwrap();
+            assert_eq!(dt.timestamp_micros(), 1_640_995_200_123_456);
+        }
+
+        /// Test flatten_to sells routable assets rounded down to the step and skips the rest
+        #[tokio::test]
+        async fn test_flatten_to() {
+            use crate::trading::FlattenSkipReason;
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "canTrade": true, "canWithdraw": true, "canDeposit": true,
+                    "balances": [
+                        {"asset": "BTC", "free": "0.01234567", "locked": "0.00100000"},
+                        {"asset": "ETH", "free": "0.00001000", "locked": "0.00000000"},
+                        {"asset": "XYZ", "free": "5.00000000", "locked": "0.00000000"},
+                        {"asset": "USDT", "free": "100.00000000", "locked": "0.00000000"}
+                    ]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "symbols": [
+                        {"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+                         "filters": [
+                            {"filterType": "LOT_SIZE", "minQty": "0.00001000", "maxQty": "9000.00000000", "stepSize": "0.00001000"},
+                            {"filterType": "NOTIONAL", "minNotional": "5.00000000", "applyMinToMarket": true},
+                            {"filterType": "ICEBERG_PARTS", "limit": 10}
+                         ]},
+                        {"symbol": "ETHUSDT", "status": "TRADING", "baseAsset": "ETH", "quoteAsset": "USDT",
+                         "filters": [
+                            {"filterType": "LOT_SIZE", "minQty": "0.00010000", "maxQty": "9000.00000000", "stepSize": "0.00010000"}
+                         ]}
+                    ]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/ticker/price"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                    {"symbol": "BTCUSDT", "price": "45000.00"},
+                    {"symbol": "ETHUSDT", "price": "3000.00"}
+                ])))
+                .mount(&server)
+                .await;
+            // Only the free BTC, rounded down to the 0.00001 step, gets sold
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("symbol=BTCUSDT"))
+                .and(body_string_contains("quantity=0.01234"))
+                .and(body_string_contains("side=SELL"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(99, "FILLED")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let summary = mock_trader(&server).flatten_to("USDT").await.unwrap();
+
+            assert_eq!(summary.sold.len(), 1);
+            assert_eq!(summary.sold[0].symbol, "BTCUSDT");
+            assert!((summary.sold[0].quantity - 0.01234).abs() < 1e-12);
+
+            let reason = |asset: &str| summary.skipped.iter().find(|s| s.asset == asset).map(|s| s.reason.clone());
+            assert_eq!(reason("ETH"), Some(FlattenSkipReason::Dust));     // Rounds down to 0
+            assert_eq!(reason("XYZ"), Some(FlattenSkipReason::NoRoute));  // No XYZUSDT pair
+            assert_eq!(reason("USDT"), None);                            // The quote asset itself
+        }
+
+        /// Test that a slow exchange fails with DeadlineExceeded instead of blocking past the budget
+        #[tokio::test]
+        async fn test_request_deadline_exceeded() {
+            use crate::error::TradingError;
+            use std::time::{Duration, Instant};
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200)
+                    .set_body_json(json!([]))
+                    .set_delay(Duration::from_secs(2)))
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server).with_request_deadline(Duration::from_millis(200));
+            let started = Instant::now();
+            let err = trader.get_open_orders(None).await.unwrap_err();
+
+            assert!(started.elapsed() < Duration::from_secs(1));
+            assert_eq!(
+                err,
+                TradingError::DeadlineExceeded { budget: Duration::from_millis(200) }
+            );
+        }
+
+        /// Test that sequential requests reuse one pooled connection instead of reconnecting
+        #[tokio::test]
+        async fn test_sequential_requests_reuse_connection() {
+            use std::sync::atomic::{AtomicUsize, Ordering};
+            use std::sync::Arc;
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+            // Minimal keep-alive HTTP server that counts accepted TCP connections
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let connections = Arc::new(AtomicUsize::new(0));
+            let counter = connections.clone();
+            tokio::spawn(async move {
+                while let Ok((mut socket, _)) = listener.accept().await {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    tokio::spawn(async move {
+                        let mut buf = Vec::new();
+                        let mut chunk = [0u8; 4096];
+                        loop {
+                            let n = match socket.read(&mut chunk).await {
+                                Ok(0) | Err(_) => return,
+                                Ok(n) => n,
+                            };
+                            buf.extend_from_slice(&chunk[..n]);
+                            // GET requests have no body, so each blank line ends a request
+                            while let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                                buf.drain(..end + 4);
+                                let response = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n[]";
+                                if socket.write_all(response.as_bytes()).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                    });
                 }
-                _ => panic!("Expected Ticker event"),
+            });
+
+            let trader = TestnetTrader::new("test_api_key".to_string(), "test_secret_key".to_string())
+                .with_base_url(format!("http://{}", addr));
+            for _ in 0..5 {
+                trader.get_open_orders(None).await.unwrap();
             }
+
+            assert_eq!(connections.load(Ordering::SeqCst), 1);
         }
 
-        /// Test TradeSide enum debug formatting
-        /// Ensures debug output is readable for logging
-        #[test]
-        fn test_trade_side_enum() {
-            assert_eq!(format!("{:?}", TradeSide::Buy), "Buy");
-            assert_eq!(format!("{:?}", TradeSide::Sell), "Sell");
+        /// Test symbols_for_quote filters by quote asset and status, and caches exchange info
+        #[tokio::test]
+        async fn test_symbols_for_quote_cached() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "symbols": [
+                        {"symbol": "ETHUSDT", "status": "TRADING", "baseAsset": "ETH", "quoteAsset": "USDT"},
+                        {"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT"},
+                        {"symbol": "OLDUSDT", "status": "BREAK", "baseAsset": "OLD", "quoteAsset": "USDT"},
+                        {"symbol": "ETHBTC", "status": "TRADING", "baseAsset": "ETH", "quoteAsset": "BTC"}
+                    ]
+                })))
+                .expect(1)  // Second lookup must come from the cache
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            let usdt = trader.symbols_for_quote("USDT").await.unwrap();
+            assert_eq!(usdt, vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]);
+
+            let btc = trader.symbols_for_quote("BTC").await.unwrap();
+            assert_eq!(btc, vec!["ETHBTC".to_string()]);
+        }
+
+        /// Test that the order log writes recorded orders on flush and on drop
+        #[tokio::test]
+        async fn test_order_log_flush_and_drop() {
+            use crate::orders::OrderLog;
+
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("orders.jsonl");
+            let read_lines = || std::fs::read_to_string(&path).unwrap_or_default().lines().count();
+
+            // Dry-run orders go through the trader's log too
+            let trader = TestnetTrader::new("test_api_key".to_string(), "test_secret_key".to_string())
+                .with_dry_run(true)
+                .with_order_log(OrderLog::open(&path).await.unwrap());
+            trader.place_market_order("BTCUSDT", OrderSide::Buy, 0.001).await.unwrap();
+            trader.place_limit_order("BTCUSDT", OrderSide::Sell, 0.001, 50000.0).await.unwrap();
+
+            trader.order_log().unwrap().flush().await.unwrap();
+            assert_eq!(read_lines(), 2);
+            let first: serde_json::Value = serde_json::from_str(
+                std::fs::read_to_string(&path).unwrap().lines().next().unwrap()
+            ).unwrap();
+            assert_eq!(first["event"], "simulated");
+            assert_eq!(first["order"]["symbol"], "BTCUSDT");
+
+            // Dropping without a flush still writes out what was queued
+            trader.place_market_order("BTCUSDT", OrderSide::Buy, 0.001).await.unwrap();
+            drop(trader);
+            for _ in 0..50 {
+                if read_lines() == 3 {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            assert_eq!(read_lines(), 3);
+        }
+
+        /// Test that precision overrides control the quantity and price sent for a symbol
+        #[tokio::test]
+        async fn test_precision_overrides() {
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("quantity=0.123&"))
+                .and(body_string_contains("price=45000.6&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "NEW")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let overrides = HashMap::from([("BTCUSDT".to_string(), (3, 1))]);
+            let trader = mock_trader(&server).with_precision_overrides(overrides);
+            // Quantity rounds down, price to nearest
+            trader.place_limit_order("BTCUSDT", OrderSide::Buy, 0.12399, 45000.55).await.unwrap();
+        }
+
+        /// Test the trailing stop parameters, the signature over them, and delta validation
+        #[tokio::test]
+        async fn test_place_trailing_stop_order() {
+            use crate::error::TradingError;
+
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(5, "NEW")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            trader.place_trailing_stop_order("BTCUSDT", OrderSide::Sell, 0.001, 200, Some(46000.0)).await.unwrap();
+
+            let requests = server.received_requests().await.unwrap();
+            let body = String::from_utf8(requests[0].body.clone()).unwrap();
+            let (query, signature) = body.split_once("&signature=").unwrap();
+            assert!(query.contains("type=STOP_LOSS"));
+            assert!(query.contains("trailingDelta=200"));
+            assert!(query.contains("stopPrice=46000.00"));
+            // The signature covers exactly the parameters that were sent
+            assert_eq!(signature, trader.sign(query));
+
+            // Out-of-range deltas never reach the exchange
+            let err = trader.place_trailing_stop_order("BTCUSDT", OrderSide::Sell, 0.001, 5, None).await.unwrap_err();
+            assert!(matches!(err, TradingError::InvalidParameter(_)));
+        }
+
+        /// Test quantity_for_notional rounds down to the step size and reports the real notional
+        #[tokio::test]
+        async fn test_quantity_for_notional() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/ticker/price"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"symbol": "BTCUSDT", "price": "45000.00"})))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "symbols": [{"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+                                 "filters": [{"filterType": "LOT_SIZE", "minQty": "0.00001000", "maxQty": "9000.00000000", "stepSize": "0.00001000"}]}]
+                })))
+                .mount(&server)
+                .await;
+
+            // $250 / 45000 = 0.0055555... -> 0.00555 BTC
+            let sized = mock_trader(&server).quantity_for_notional("BTCUSDT", 250.0).await.unwrap();
+            assert_eq!(sized.quantity, 0.00555);
+            assert_eq!(sized.price, 45000.0);
+            assert!((sized.notional - 249.75).abs() < 1e-9);
+            assert!(sized.notional <= 250.0);
+
+            // A renamed symbol finds the new name's step size
+            let renamed = mock_trader(&server).with_symbol_alias("XBTUSDT", "BTCUSDT");
+            assert_eq!(renamed.quantity_for_notional("XBTUSDT", 250.0).await.unwrap().quantity, 0.00555);
+            assert_eq!(renamed.symbol_status("XBTUSDT").await.unwrap().as_deref(), Some("TRADING"));
+        }
+
+        /// Test that cached exchange info decides the precision of a tiny-priced order
+        #[tokio::test]
+        async fn test_order_precision_from_cached_step_sizes() {
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "symbols": [{"symbol": "SHIBUSDT", "status": "TRADING", "baseAsset": "SHIB", "quoteAsset": "USDT",
+                                 "filters": [{"filterType": "PRICE_FILTER", "minPrice": "0.00000001", "maxPrice": "1.00000000", "tickSize": "0.00000001"},
+                                             {"filterType": "LOT_SIZE", "minQty": "1.00", "maxQty": "9000000000.00", "stepSize": "1.00"}]}]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("quantity=1000000&"))
+                .and(body_string_contains("price=0.00001235&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(7, "NEW")))
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            trader.cached_exchange_info().await.unwrap();
+            // Parsed from scientific notation, as Binance sometimes sends it
+            let price: f64 = "1.2345E-5".parse().unwrap();
+            let order = trader.place_limit_order("SHIBUSDT", OrderSide::Buy, 1_000_000.4, price).await.unwrap();
+            assert_eq!(order.order_id, OrderId(7));
+        }
+
+        /// Test parsing open OCO lists, including their legs
+        #[tokio::test]
+        async fn test_get_open_oco_orders() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrderList"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([{
+                    "orderListId": 31,
+                    "contingencyType": "OCO",
+                    "listStatusType": "EXEC_STARTED",
+                    "listOrderStatus": "EXECUTING",
+                    "listClientOrderId": "wuB13fmulKj3YjdqWEcsnp",
+                    "transactionTime": 1565246080644u64,
+                    "symbol": "BTCUSDT",
+                    "orders": [
+                        {"symbol": "BTCUSDT", "orderId": 4, "clientOrderId": "r3EH2N76dHfLoSZWIUw1bT"},
+                        {"symbol": "BTCUSDT", "orderId": 5, "clientOrderId": "Cv1SnyPD3qhqpbjpYEHbd2"}
+                    ]
+                }])))
+                .mount(&server)
+                .await;
+
+            let lists = mock_trader(&server).get_open_oco_orders().await.unwrap();
+
+            assert_eq!(lists.len(), 1);
+            assert_eq!(lists[0].order_list_id, 31);
+            assert_eq!(lists[0].list_order_status, "EXECUTING");
+            assert_eq!(lists[0].orders.len(), 2);
+            assert!(lists[0].contains(OrderId(5)));
+            assert!(!lists[0].contains(OrderId(6)));
+        }
+
+        /// Test that rapid-fire signed requests never reuse a timestamp
+        #[tokio::test]
+        async fn test_signed_timestamps_strictly_increase() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            for _ in 0..20 {
+                trader.get_open_orders(None).await.unwrap();
+            }
+
+            let timestamps: Vec<u64> = server.received_requests().await.unwrap().iter()
+                .map(|r| r.url.query_pairs().find(|(k, _)| k == "timestamp").unwrap().1.parse().unwrap())
+                .collect();
+            assert_eq!(timestamps.len(), 20);
+            assert!(timestamps.windows(2).all(|pair| pair[1] > pair[0]), "{:?}", timestamps);
+        }
+
+        /// Test that AccountWatcher starts from REST balances and applies outboundAccountPosition events
+        #[tokio::test]
+        async fn test_account_watcher_applies_stream_updates() {
+            use crate::trading::AccountWatcher;
+            use futures_util::SinkExt;
+            use std::sync::Arc;
+            use std::time::Duration;
+            use tokio_tungstenite::tungstenite::Message;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "balances": [{"asset": "BTC", "free": "1.00000000", "locked": "0.00000000"},
+                                 {"asset": "USDT", "free": "1000.00000000", "locked": "0.00000000"}],
+                    "canTrade": true, "canWithdraw": true, "canDeposit": true
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/userDataStream"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"listenKey": "test-listen-key"})))
+                .mount(&server)
+                .await;
+
+            // User data stream that pushes one balance update after the handshake
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+            tokio::spawn(async move {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                let update = r#"{"e":"outboundAccountPosition","E":1564034571105,"u":1564034571073,
+                                 "B":[{"a":"BTC","f":"0.90000000","l":"0.10000000"}]}"#;
+                ws.send(Message::Text(update.to_string())).await.unwrap();
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            });
+
+            let watcher = AccountWatcher::start_with_url(Arc::new(mock_trader(&server)), format!("ws://{}", address))
+                .await
+                .unwrap();
+            let mut updates = watcher.subscribe();
+            assert_eq!(updates.borrow().free("BTC"), 1.0);
+
+            tokio::time::timeout(Duration::from_secs(5), updates.changed()).await.unwrap().unwrap();
+            let snapshot = updates.borrow().clone();
+            assert_eq!(snapshot.free("BTC"), 0.9);
+            assert_eq!(snapshot.balances["BTC"].locked, 0.1);
+            assert_eq!(snapshot.free("USDT"), 1000.0);
+        }
+
+        /// Test that the sell guard caps or refuses sells larger than the free base balance
+        #[tokio::test]
+        async fn test_sell_guard_caps_and_refuses_oversells() {
+            use crate::error::TradingError;
+            use crate::trading::SellGuard;
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "symbols": [{"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+                                 "filters": [{"filterType": "LOT_SIZE", "minQty": "0.00001000", "maxQty": "9000.00000000", "stepSize": "0.00001000"}]}]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "balances": [{"asset": "BTC", "free": "1.23456789", "locked": "0.50000000"}],
+                    "canTrade": true, "canWithdraw": true, "canDeposit": true
+                })))
+                .mount(&server)
+                .await;
+            // Capped to the free balance (not free + locked), rounded down to the step
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("quantity=1.23456&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(11, "FILLED")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let capped = mock_trader(&server).with_sell_guard(SellGuard::Cap);
+            let order = capped.place_market_order("BTCUSDT", OrderSide::Sell, 2.0).await.unwrap();
+            assert_eq!(order.order_id, OrderId(11));
+
+            let strict = mock_trader(&server).with_sell_guard(SellGuard::Strict);
+            let err = strict.place_market_order("BTCUSDT", OrderSide::Sell, 2.0).await.unwrap_err();
+            assert_eq!(err, TradingError::InsufficientHoldings {
+                asset: "BTC".to_string(),
+                requested: 2.0,
+                available: 1.23456789,
+            });
+        }
+
+        /// Test that sync_time measures the offset, applies it to timestamps and reports large drift
+        #[tokio::test]
+        async fn test_sync_time_reports_clock_drift() {
+            use std::sync::{Arc, Mutex};
+
+            // Server clock 5s ahead of ours
+            let server_time = chrono::Utc::now().timestamp_millis() as u64 + 5_000;
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/time"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"serverTime": server_time})))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+                .mount(&server)
+                .await;
+
+            let alerts = Arc::new(Mutex::new(Vec::new()));
+            let recorded = alerts.clone();
+            let trader = mock_trader(&server)
+                .on_clock_drift(Box::new(move |drift| recorded.lock().unwrap().push(drift)));
+            assert_eq!(trader.clock_drift(), None);
+
+            let offset = trader.sync_time().await.unwrap();
+            assert!((4_000..=6_000).contains(&offset), "offset {}", offset);
+            assert_eq!(trader.clock_drift(), Some(offset));
+            assert!(trader.clock_drift_exceeded());
+            assert_eq!(*alerts.lock().unwrap(), vec![offset]);
+
+            // Signed requests now use server time
+            trader.get_open_orders(None).await.unwrap();
+            let requests = server.received_requests().await.unwrap();
+            let timestamp: u64 = requests.last().unwrap().url.query_pairs()
+                .find(|(k, _)| k == "timestamp").unwrap().1.parse().unwrap();
+            assert!(timestamp >= server_time);
+        }
+
+        /// Test system status parsing, cached symbol status checks and the halt short-circuit
+        #[tokio::test]
+        async fn test_symbol_halt_check() {
+            use crate::error::TradingError;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/sapi/v1/system/status"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"status": 1, "msg": "system_maintenance"})))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "symbols": [
+                        {"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT", "filters": []},
+                        {"symbol": "LUNAUSDT", "status": "HALT", "baseAsset": "LUNA", "quoteAsset": "USDT", "filters": []}
+                    ]
+                })))
+                .expect(1)  // Cached after the first lookup
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "NEW")))
+                .expect(0)
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server).with_halt_check(true);
+            assert!(!trader.get_system_status().await.unwrap().is_normal());
+            assert!(trader.is_symbol_trading("BTCUSDT").await.unwrap());
+            assert!(!trader.is_symbol_trading("LUNAUSDT").await.unwrap());
+            assert!(!trader.is_symbol_trading("NOPEUSDT").await.unwrap());
+
+            let err = trader.place_limit_order("LUNAUSDT", OrderSide::Buy, 1.0, 0.5).await.unwrap_err();
+            assert_eq!(err, TradingError::SymbolHalted {
+                symbol: "LUNAUSDT".to_string(),
+                status: "HALT".to_string(),
+            });
+        }
+
+        /// Test that only orders resting longer than the age limit are canceled
+        #[tokio::test]
+        async fn test_cancel_orders_older_than() {
+            use std::time::Duration;
+            use wiremock::matchers::body_string_contains;
+
+            let now = chrono::Utc::now().timestamp_millis() as u64;
+            let mut fresh = order_json(2, "NEW");
+            fresh.as_object_mut().unwrap().remove("transactTime");
+            fresh["time"] = json!(now - 60_000);  // Listed orders carry `time` instead
+            let mut undated = order_json(3, "NEW");
+            undated.as_object_mut().unwrap().remove("transactTime");
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([order_json(1, "NEW"), fresh, undated])))
+                .mount(&server)
+                .await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("orderId=1&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "CANCELED")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let canceled = mock_trader(&server)
+                .cancel_orders_older_than("BTCUSDT", Duration::from_secs(10 * 60))
+                .await
+                .unwrap();
+
+            assert_eq!(canceled.len(), 1);
+            assert_eq!(canceled[0].order_id, OrderId(1));
+        }
+
+        /// Test that Decimal quantities and prices reach the request without an f64 round trip
+        #[tokio::test]
+        async fn test_decimal_order_inputs() {
+            use rust_decimal::Decimal;
+            use std::str::FromStr;
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            // No step/tick info cached: 8 quantity and 2 price decimals, rounded on the decimal itself
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("quantity=0.12345679&"))
+                .and(body_string_contains("price=45000.10&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "NEW")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let quantity = Decimal::from_str("0.123456789012").unwrap();
+            let price = Decimal::from_str("45000.1").unwrap();
+            mock_trader(&server).place_limit_order("BTCUSDT", OrderSide::Buy, quantity, price).await.unwrap();
+        }
+
+        /// Test that a timed-out placement is resolved by clientOrderId instead of blindly resent
+        #[tokio::test]
+        async fn test_place_order_idempotent() {
+            use crate::trading::{NewOrderRequest, OrderType};
+            use std::time::Duration;
+            use wiremock::matchers::{body_string_contains, query_param};
+
+            let server = MockServer::start().await;
+            let order = NewOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                order_type: OrderType::Limit,
+                time_in_force: None,
+                quantity: Some(0.001),
+                quote_order_qty: None,
+                price: Some(45000.0),
+                timestamp: None,
+            };
+
+            // "landed": the POST times out but the order exists - it must be sent only once
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("newClientOrderId=landed&"))
+                .respond_with(ResponseTemplate::new(200)
+                    .set_body_json(order_json(7, "NEW"))
+                    .set_delay(Duration::from_millis(500)))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/order"))
+                .and(query_param("origClientOrderId", "landed"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(7, "NEW")))
+                .expect(2)
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server).with_request_deadline(Duration::from_millis(200));
+            let placed = trader.place_order_idempotent(&order, "landed").await.unwrap();
+            assert_eq!(placed.order_id, OrderId(7));
+            // Local dedup: asking again only looks the order up
+            let again = trader.place_order_idempotent(&order, "landed").await.unwrap();
+            assert_eq!(again.order_id, OrderId(7));
+
+            // "lost": the first POST times out and Binance never saw it, so it's retried
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("newClientOrderId=lost&"))
+                .respond_with(ResponseTemplate::new(200)
+                    .set_body_json(order_json(8, "NEW"))
+                    .set_delay(Duration::from_millis(500)))
+                .up_to_n_times(1)
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("newClientOrderId=lost&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(8, "NEW")))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/order"))
+                .and(query_param("origClientOrderId", "lost"))
+                .respond_with(ResponseTemplate::new(400)
+                    .set_body_json(json!({"code": -2013, "msg": "Order does not exist."})))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let placed = trader.place_order_idempotent(&order, "lost").await.unwrap();
+            assert_eq!(placed.order_id, OrderId(8));
+        }
+
+        /// Test that batch placement keeps input order and reports each leg on its own
+        #[tokio::test]
+        async fn test_place_orders_batch() {
+            use crate::error::TradingError;
+            use crate::trading::{NewOrderRequest, OrderType};
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("symbol=ETHUSDT"))
+                .respond_with(ResponseTemplate::new(400)
+                    .set_body_json(json!({"code": -2010, "msg": "Account has insufficient balance for requested action."})))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("symbol=BTCUSDT"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "NEW")))
+                .expect(2)
+                .mount(&server)
+                .await;
+
+            let request = |symbol: &str, order_type: OrderType, price: Option<f64>| NewOrderRequest {
+                symbol: symbol.to_string(),
+                side: OrderSide::Buy,
+                order_type,
+                time_in_force: None,
+                quantity: Some(0.01),
+                quote_order_qty: None,
+                price,
+                timestamp: None,
+            };
+            let requests = vec![
+                request("BTCUSDT", OrderType::Limit, Some(45000.0)),
+                request("ETHUSDT", OrderType::Market, None),
+                request("BTCUSDT", OrderType::Limit, None),  // Missing price, never sent
+                request("BTCUSDT", OrderType::Market, None),
+            ];
+
+            let results = mock_trader(&server).place_orders(requests, 2).await;
+            assert_eq!(results.len(), 4);
+            assert!(results[0].is_ok());
+            assert!(matches!(results[1], Err(TradingError::ApiError { code: -2010, .. })));
+            assert!(matches!(&results[2], Err(TradingError::InvalidParameter(msg)) if msg.contains("needs a price")));
+            assert!(results[3].is_ok());
+        }
+
+        /// Test that place_order sends the request's optional fields and enums as parameters
+        #[tokio::test]
+        async fn test_place_order_from_request() {
+            use crate::error::TradingError;
+            use crate::trading::{NewOrderRequest, OrderType, TimeInForce};
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("type=LIMIT&"))
+                .and(body_string_contains("timeInForce=IOC&"))
+                .and(body_string_contains("price=45000.50&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "EXPIRED")))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("quoteOrderQty=100.00000000&"))
+                .and(body_string_contains("type=MARKET&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(2, "FILLED")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            let limit = NewOrderRequest {
+                symbol: "BTCUSDT".to_string(),
+                side: OrderSide::Buy,
+                order_type: OrderType::Limit,
+                time_in_force: Some(TimeInForce::ImmediateOrCancel),
+                quantity: Some(0.001),
+                quote_order_qty: None,
+                price: Some(45000.5),
+                timestamp: None,
+            };
+            assert_eq!(trader.place_order(&limit).await.unwrap().order_id, OrderId(1));
+
+            let by_quote = NewOrderRequest {
+                order_type: OrderType::Market,
+                time_in_force: None,
+                quantity: None,
+                quote_order_qty: Some(100.0),
+                price: None,
+                ..limit.clone()
+            };
+            assert_eq!(trader.place_order(&by_quote).await.unwrap().order_id, OrderId(2));
+
+            // Both sizes at once is rejected before anything is sent
+            let both = NewOrderRequest { quantity: Some(0.001), ..by_quote };
+            assert!(matches!(trader.place_order(&both).await, Err(TradingError::InvalidParameter(_))));
+        }
+
+        /// Test the BNB-burn toggle and that the fee estimate only discounts with BNB to pay
+        #[tokio::test]
+        async fn test_bnb_fee_burn() {
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/sapi/v1/bnbBurn"))
+                .and(body_string_contains("spotBNBBurn=true&"))
+                .respond_with(ResponseTemplate::new(200)
+                    .set_body_json(json!({"spotBNBBurn": true, "interestBNBBurn": false})))
+                .expect(1)
+                .mount(&server)
+                .await;
+            let account = |bnb: &str| json!({
+                "balances": [{"asset": "BNB", "free": bnb, "locked": "0.00000000"}],
+                "canTrade": true, "canWithdraw": true, "canDeposit": true,
+                "commissionRates": {"maker": "0.00100000", "taker": "0.00100000", "buyer": "0.00000000", "seller": "0.00000000"}
+            });
+            // First with no BNB (warns on enable, no discount), then with some
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(account("0.00000000")))
+                .up_to_n_times(2)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(account("1.50000000")))
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            assert!(!trader.bnb_fee_burn_enabled());
+            let status = trader.set_bnb_fee_burn(true).await.unwrap();
+            assert!(status.spot_bnb_burn);
+            assert!(trader.bnb_fee_burn_enabled());
+
+            let empty = trader.fee_estimator().await.unwrap().unwrap();
+            assert!((empty.effective_fee(OrderSide::Buy, false) - 0.001).abs() < 1e-12);
+            let funded = trader.fee_estimator().await.unwrap().unwrap();
+            assert!((funded.effective_fee(OrderSide::Buy, false) - 0.00075).abs() < 1e-12);
+        }
+
+        /// Test that exchangeInfo rateLimits configure the limiter and orders are counted against them
+        #[tokio::test]
+        async fn test_rate_limits_from_exchange_info() {
+            use crate::trading::{RateLimitInterval, RateLimitType};
+            use std::time::Duration;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "rateLimits": [
+                        {"rateLimitType": "REQUEST_WEIGHT", "interval": "MINUTE", "intervalNum": 1, "limit": 6000},
+                        {"rateLimitType": "ORDERS", "interval": "SECOND", "intervalNum": 10, "limit": 2},
+                        {"rateLimitType": "ORDERS", "interval": "DAY", "intervalNum": 1, "limit": 160000},
+                        {"rateLimitType": "RAW_REQUESTS", "interval": "MINUTE", "intervalNum": 5, "limit": 61000}
+                    ],
+                    "symbols": []
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "FILLED")))
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            assert!(trader.rate_limits().is_none());
+            trader.get_exchange_info().await.unwrap();
+
+            let limits = trader.rate_limits().unwrap();
+            assert_eq!(limits.request_weight[0].limit, 6000);
+            assert_eq!(limits.orders.len(), 2);
+            assert_eq!(limits.orders[0].interval, RateLimitInterval::Second);
+            assert_eq!(limits.orders[0].window(), Duration::from_secs(10));
+            assert_eq!(limits.raw_requests[0].to_string(), "61000 requests per 300s");
+
+            trader.place_market_order("BTCUSDT", OrderSide::Buy, 0.001).await.unwrap();
+            trader.place_market_order("BTCUSDT", OrderSide::Buy, 0.001).await.unwrap();
+
+            let limiter = trader.rate_limiter().unwrap();
+            let usage = limiter.usage();
+            let used = |kind: RateLimitType| usage.iter().find(|(l, _)| l.rate_limit_type == kind).unwrap().1;
+            assert_eq!(used(RateLimitType::Orders), 2);
+            assert_eq!(used(RateLimitType::RequestWeight), 2);  // exchangeInfo came before the limits were known
+
+            // A third order in the same 10s has to wait; other requests don't
+            let wait = limiter.try_acquire(1, true).unwrap_err();
+            assert!(wait > Duration::from_secs(9) && wait <= Duration::from_secs(10));
+            assert!(limiter.try_acquire(1, false).is_ok());
+        }
+
+        /// Test that OrderManager keeps local tags with orders, across status updates
+        #[test]
+        fn test_order_manager_tags() {
+            use crate::orders::{OrderManager, OrderTags};
+            use crate::trading::{AccountTrade, OrderResponse};
+
+            let order = |id: u64, status: &str| -> OrderResponse { serde_json::from_value(order_json(id, status)).unwrap() };
+            let tags = |strategy: &str| -> OrderTags {
+                [("strategy".to_string(), strategy.to_string()), ("purpose".to_string(), "entry".to_string())].into()
+            };
+
+            let mut manager = OrderManager::new();
+            manager.record(order(1, "NEW"), Some(tags("momentum")));
+            manager.record(order(2, "NEW"), Some(tags("mean_reversion")));
+            manager.record(order(3, "NEW"), None);
+
+            // A status update without tags keeps the old ones; new tags are merged in
+            manager.record(order(1, "FILLED"), None);
+            manager.record(order(2, "NEW"), Some([("purpose".to_string(), "exit".to_string())].into()));
+
+            assert_eq!(manager.get(OrderId(1)).unwrap().status, crate::trading::OrderStatus::Filled);
+            assert_eq!(manager.tag(OrderId(1), "strategy"), Some("momentum"));
+            assert_eq!(manager.tag(OrderId(2), "purpose"), Some("exit"));
+            assert_eq!(manager.tag(OrderId(2), "strategy"), Some("mean_reversion"));
+            assert!(manager.tags(OrderId(3)).unwrap().is_empty());
+            assert!(manager.tags(OrderId(4)).is_none());
+
+            let momentum: Vec<OrderId> = manager.orders_tagged("strategy", "momentum").iter().map(|o| o.order_id).collect();
+            assert_eq!(momentum, vec![OrderId(1)]);
+
+            // A fill is attributed through its orderId
+            let trade: AccountTrade = serde_json::from_value(json!({
+                "symbol": "BTCUSDT", "id": 9, "orderId": 1, "price": "45000.00", "qty": "0.001",
+                "quoteQty": "45.00", "commission": "0.00000100", "commissionAsset": "BTC",
+                "time": 1640995200000u64, "isBuyer": true, "isMaker": false
+            })).unwrap();
+            assert_eq!(manager.tags_for_trade(&trade).unwrap()["strategy"], "momentum");
+
+            assert!(manager.remove(OrderId(3)).is_some());
+            assert_eq!(manager.len(), 2);
+        }
+
+        /// Test that 5xx responses are retried for GETs and surface as ServerError, not a rejection, for orders
+        #[tokio::test]
+        async fn test_server_errors_are_transient() {
+            use crate::error::TradingError;
+            use std::time::Duration;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(503).set_body_string("<html>Service Unavailable</html>"))
+                .up_to_n_times(2)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(5, "NEW")))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .respond_with(ResponseTemplate::new(503))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/time"))
+                .respond_with(ResponseTemplate::new(502))
+                .expect(3)
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server).with_server_error_retries(2, Duration::from_millis(1));
+
+            // Two 503s, then the order comes back
+            let order = trader.get_order("BTCUSDT", OrderId(5)).await.unwrap();
+            assert_eq!(order.order_id, OrderId(5));
+
+            // Placement is not repeated, and the error says it wasn't a rejection
+            let err = trader.place_market_order("BTCUSDT", OrderSide::Buy, 0.001).await.unwrap_err();
+            assert_eq!(err, TradingError::ServerError { status: 503 });
+            assert!(err.is_transient());
+            assert!(err.reject_reason().is_none());
+
+            // Public GETs retry too, then give up with the status
+            let err = trader.get_server_time().await.unwrap_err();
+            assert_eq!(err, TradingError::ServerError { status: 502 });
+        }
+
+        /// Test that get_klines parses all 12 positional fields of the REST kline rows
+        #[tokio::test]
+        async fn test_get_klines_full_rows() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/klines"))
+                .and(query_param("symbol", "BTCUSDT"))
+                .and(query_param("interval", "1m"))
+                .and(query_param("limit", "2"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                    [1499040000000u64, "0.01634790", "0.80000000", "0.01575800", "0.01577100", "148976.11427815",
+                     1499644799999u64, "2434.19055334", 308, "1756.87402397", "28.46694368", "17928899.62484339"],
+                    [4102444800000u64, "0.01577100", "0.01600000", "0.01570000", "0.01590000", "10.00000000",
+                     4102444859999u64, "0.15800000", 3, "4.00000000", "0.06300000", "0"]
+                ])))
+                .mount(&server)
+                .await;
+
+            let klines = mock_trader(&server).get_klines("BTCUSDT", "1m", Some(2)).await.unwrap();
+            assert_eq!(klines.len(), 2);
+
+            let k = &klines[0];
+            assert_eq!((k.symbol.as_str(), k.interval.as_str()), ("BTCUSDT", "1m"));
+            assert_eq!((k.open_time, k.close_time), (1499040000000, 1499644799999));
+            assert_eq!((k.open, k.high, k.low, k.close), (0.0163479, 0.8, 0.015758, 0.015771));
+            assert_eq!(k.volume, 148976.11427815);
+            assert_eq!(k.quote_volume, 2434.19055334);
+            assert_eq!(k.trade_count, 308);
+            assert_eq!(k.taker_buy_base, 1756.87402397);
+            assert_eq!(k.taker_buy_quote, 28.46694368);
+            assert!(k.is_closed);
+            // Closes in 2100: still forming
+            assert!(!klines[1].is_closed);
+        }
+
+        /// Test that malformed kline rows are rejected with a clear error instead of panicking
+        #[test]
+        fn test_kline_row_validation() {
+            use crate::market_data::Kline;
+
+            let short = json!([1499040000000u64, "1", "1", "1", "1", "1"]);
+            let err = Kline::from_rest_row("BTCUSDT", "1m", short.as_array().unwrap(), 0).unwrap_err();
+            assert!(err.contains("6 fields"), "{}", err);
+
+            let garbled = json!([1499040000000u64, "1", "x", "1", "1", "1", 1499040059999u64, "1", 3, "1", "1", "0"]);
+            let err = Kline::from_rest_row("BTCUSDT", "1m", garbled.as_array().unwrap(), 0).unwrap_err();
+            assert!(err.contains("high"), "{}", err);
+
+            // Numbers instead of strings, and no trailing placeholder, are fine
+            let numeric = json!([0, 1.0, 2.0, 0.5, 1.5, 10.0, 59999, 15.0, 4, 5.0, 7.5]);
+            let kline = Kline::from_rest_row("BTCUSDT", "1m", numeric.as_array().unwrap(), 60_000).unwrap();
+            assert_eq!((kline.high, kline.trade_count, kline.is_closed), (2.0, 4, true));
+        }
+
+        /// Test that precision lookups for a renamed symbol use the new name's settings
+        #[tokio::test]
+        async fn test_symbol_alias_precision() {
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("quantity=0.123&"))
+                .and(body_string_contains("price=45000.6&"))
+                .and(body_string_contains("symbol=OLDUSDT&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "NEW")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let overrides = HashMap::from([("NEWUSDT".to_string(), (3, 1))]);
+            let trader = mock_trader(&server)
+                .with_precision_overrides(overrides)
+                .with_symbol_alias("OLDUSDT", "NEWUSDT");
+            trader.place_limit_order("OLDUSDT", OrderSide::Buy, 0.12399, 45000.55).await.unwrap();
+        }
+
+        #[tokio::test]
+        async fn test_dead_mans_switch_cancels_only_after_heartbeats_stop() {
+            use crate::orders::DeadMansSwitch;
+            use std::sync::Arc;
+            use wiremock::matchers::body_string_contains;
+            use std::time::Duration;
+
+            let server = MockServer::start().await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/openOrders"))
+                .and(body_string_contains("symbol=BTCUSDT"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([order_json(7, "CANCELED")])))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let switch = DeadMansSwitch::start(
+                Arc::new(mock_trader(&server)),
+                vec!["BTCUSDT".to_string()],
+                Duration::from_millis(150),
+            );
+            switch.track_symbol("BTCUSDT");
+            assert_eq!(switch.symbols(), vec!["BTCUSDT".to_string()]);
+
+            // Kicked well within the timeout: nothing is canceled
+            for _ in 0..6 {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                switch.heartbeat();
+            }
+            assert_eq!(switch.trigger_count(), 0);
+
+            // Silence: fires once, then waits for a heartbeat before re-arming
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            assert_eq!(switch.trigger_count(), 1);
+        }
+
+        #[tokio::test]
+        async fn test_cancel_all_orders_with_nothing_open() {
+            let server = MockServer::start().await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "code": -2011, "msg": "Unknown order sent."
+                })))
+                .mount(&server)
+                .await;
+
+            let canceled = mock_trader(&server).cancel_all_orders("BTCUSDT").await.unwrap();
+            assert!(canceled.is_empty());
+        }
+
+        #[tokio::test]
+        async fn test_get_agg_trades_by_time_and_by_id() {
+            use crate::market_data::TradeSide;
+
+            let server = MockServer::start().await;
+            let rows = json!([
+                {"a": 26129, "p": "0.01633102", "q": "4.70443515", "f": 27781, "l": 27781, "T": 1498793709153u64, "m": true, "M": true},
+                {"a": 26130, "p": "0.01633200", "q": "1.00000000", "f": 27782, "l": 27784, "T": 1498793709160u64, "m": false, "M": true}
+            ]);
+            Mock::given(method("GET"))
+                .and(path("/api/v3/aggTrades"))
+                .and(query_param("startTime", "1498793709000"))
+                .and(query_param("endTime", "1498793710000"))
+                .and(query_param("limit", "2"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(rows.clone()))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/aggTrades"))
+                .and(query_param("fromId", "26129"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(rows))
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            let trades = trader.get_agg_trades("BNBBTC", Some(1498793709000), Some(1498793710000), Some(2)).await.unwrap();
+            assert_eq!(trades.len(), 2);
+            assert_eq!(trades[0].symbol, "BNBBTC");
+            assert_eq!(trades[0].side, TradeSide::Sell);  // Buyer was the maker
+            assert_eq!(trades[1].side, TradeSide::Buy);
+            assert_eq!(trades[1].timestamp, 1498793709160);
+            assert!((trades[0].quantity - 4.70443515).abs() < 1e-12);
+
+            let page = trader.get_agg_trades_from_id("BNBBTC", 26129, None).await.unwrap();
+            assert_eq!(page.iter().map(|t| t.id).collect::<Vec<_>>(), vec![26129, 26130]);
+            assert_eq!(page[1].last_trade_id, 27784);
+        }
+
+        #[tokio::test]
+        async fn test_get_klines_range_pages_and_dedups_the_boundary() {
+            let server = MockServer::start().await;
+            let minute = 60_000u64;
+            let rows = |from: u64, to: u64| -> serde_json::Value {
+                (from..to).map(|i| json!([
+                    i * minute, "1.0", "2.0", "0.5", "1.5", "10.0", (i + 1) * minute - 1,
+                    "15.0", 3, "4.0", "6.0", "0"
+                ])).collect()
+            };
+            Mock::given(method("GET"))
+                .and(path("/api/v3/klines"))
+                .and(query_param("startTime", "0"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(rows(0, 1000)))
+                .expect(1)
+                .mount(&server)
+                .await;
+            // Second page starts at the last candle already received
+            Mock::given(method("GET"))
+                .and(path("/api/v3/klines"))
+                .and(query_param("startTime", (999 * minute).to_string()))
+                .and(query_param("endTime", (1400 * minute).to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(rows(999, 1401)))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let klines = mock_trader(&server)
+                .get_klines_range("BTCUSDT", "1m", 0, 1400 * minute)
+                .await
+                .unwrap();
+            assert_eq!(klines.len(), 1401);
+            assert!(klines.windows(2).all(|pair| pair[1].open_time == pair[0].open_time + minute));
+            assert_eq!(klines.last().unwrap().open_time, 1400 * minute);
+        }
+
+        #[tokio::test]
+        async fn test_get_agg_trades_range_continues_by_id_and_stops_at_end_time() {
+            let server = MockServer::start().await;
+            let rows = |from: u64, to: u64| -> serde_json::Value {
+                (from..to).map(|id| json!({
+                    "a": id, "p": "100.0", "q": "1.0", "f": id, "l": id, "T": 1_000 + id, "m": false
+                })).collect()
+            };
+            Mock::given(method("GET"))
+                .and(path("/api/v3/aggTrades"))
+                .and(query_param("startTime", "1000"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(rows(0, 1000)))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/aggTrades"))
+                .and(query_param("fromId", "999"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(rows(999, 1999)))
+                .mount(&server)
+                .await;
+
+            let trades = mock_trader(&server)
+                .get_agg_trades_range("BTCUSDT", 1_000, 2_500)
+                .await
+                .unwrap();
+            // Trade N happens at 1000 + N ms, so the range ends with trade 1500
+            assert_eq!(trades.len(), 1501);
+            assert_eq!(trades.last().unwrap().timestamp, 2_500);
+            assert!(trades.windows(2).all(|pair| pair[1].timestamp > pair[0].timestamp));
+        }
+
+        /// Test that each kind of failure comes back as its own TradingError variant
+        #[tokio::test]
+        async fn test_errors_are_typed() {
+            use crate::error::TradingError;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/account"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "code": -1022,
+                    "msg": "Signature for this request is not valid."
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_string("<html>maintenance</html>"))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "timezone": "UTC", "serverTime": 0, "symbols": []
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/ticker/price"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"symbol": "NOPEUSDT", "price": "1.0"})))
+                .mount(&server)
+                .await;
+            let trader = mock_trader(&server);
+
+            let err = trader.get_spot_account().await.unwrap_err();
+            assert_eq!(err, TradingError::Signature("Signature for this request is not valid.".to_string()));
+
+            let err = trader.get_open_orders(Some("BTCUSDT")).await.unwrap_err();
+            assert!(matches!(err, TradingError::Deserialize(_)), "{:?}", err);
+
+            let err = trader.quantity_for_notional("NOPEUSDT", 100.0).await.unwrap_err();
+            assert_eq!(err, TradingError::InvalidSymbol("NOPEUSDT".to_string()));
+
+            // An error body from exchangeInfo is an API error, not a parse failure
+            let banned = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(429).set_body_json(json!({"code": -1003, "msg": "Too many requests."})))
+                .mount(&banned)
+                .await;
+            let err = mock_trader(&banned).get_exchange_info().await.unwrap_err();
+            assert!(matches!(err, TradingError::ApiError { code: -1003, .. }), "{:?}", err);
+
+            // Same for the other public endpoints
+            let rejecting = MockServer::start().await;
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({"code": -1121, "msg": "Invalid symbol."})))
+                .mount(&rejecting)
+                .await;
+            let trader = mock_trader(&rejecting);
+            let invalid = TradingError::ApiError { code: -1121, msg: "Invalid symbol.".to_string() };
+            assert_eq!(trader.get_current_price("NOPEUSDT").await.unwrap_err(), invalid);
+            assert_eq!(trader.get_system_status().await.unwrap_err(), invalid);
+            assert_eq!(trader.get_server_time().await.unwrap_err(), invalid);
+
+            // Nothing listens on port 1
+            let unreachable = TestnetTrader::new("key".to_string(), "secret".to_string())
+                .with_base_url("http://127.0.0.1:1".to_string());
+            let err = unreachable.get_server_time().await.unwrap_err();
+            assert!(matches!(err, TradingError::Network(_)), "{:?}", err);
+        }
+
+        /// Test that a quote-sized market order sends quoteOrderQty, rounded down to the quote precision
+        #[tokio::test]
+        async fn test_place_market_order_by_quote() {
+            use wiremock::matchers::body_string_contains;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "symbols": [
+                        {"symbol": "BTCUSDT", "status": "TRADING", "baseAsset": "BTC", "quoteAsset": "USDT",
+                         "quoteAssetPrecision": 2, "filters": []}
+                    ]
+                })))
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("type=MARKET&"))
+                .and(body_string_contains("quoteOrderQty=100.12&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(3, "FILLED")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let order = mock_trader(&server)
+                .place_market_order_by_quote("BTCUSDT", OrderSide::Buy, 100.129)
+                .await
+                .unwrap();
+            assert_eq!(order.order_id, OrderId(3));
+
+            let requests = server.received_requests().await.unwrap();
+            let body = String::from_utf8_lossy(&requests.last().unwrap().body).to_string();
+            assert!(!body.contains("quantity="), "{}", body);
+
+            // Without exchange info the amount is still rounded down, to 8 decimals
+            let no_info = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/exchangeInfo"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({"code": -1000, "msg": "unavailable"})))
+                .mount(&no_info)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("quoteOrderQty=100.12345678&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(4, "FILLED")))
+                .expect(1)
+                .mount(&no_info)
+                .await;
+            mock_trader(&no_info)
+                .place_market_order_by_quote("BTCUSDT", OrderSide::Buy, 100.123456789)
+                .await
+                .unwrap();
+        }
+
+        /// Test that signed requests carry recvWindow inside the signed query, clamped to 60000
+        #[tokio::test]
+        async fn test_recv_window_is_sent_and_clamped() {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+                .mount(&server)
+                .await;
+
+            mock_trader(&server).get_open_orders(None).await.unwrap();
+            let trader = mock_trader(&server).with_recv_window(120_000);
+            assert_eq!(trader.recv_window(), 60_000);
+            trader.get_open_orders(None).await.unwrap();
+
+            let requests = server.received_requests().await.unwrap();
+            let windows: Vec<String> = requests.iter()
+                .map(|r| r.url.query_pairs().find(|(k, _)| k == "recvWindow").unwrap().1.to_string())
+                .collect();
+            assert_eq!(windows, vec!["5000", "60000"]);
+
+            // Signed along with everything else
+            let query = requests[1].url.query().unwrap();
+            let (unsigned, signature) = query.rsplit_once("&signature=").unwrap();
+            assert!(unsigned.contains("recvWindow=60000"));
+            assert_eq!(signature, trader.sign(unsigned));
+        }
+
+        /// Test that a -1021 rejection re-syncs the clock and retries once with a corrected timestamp
+        #[tokio::test]
+        async fn test_timestamp_rejection_resyncs_and_retries() {
+            let server = MockServer::start().await;
+            let server_time = chrono::Utc::now().timestamp_millis() as u64 + 600_000;  // Local clock 10 minutes behind
+            Mock::given(method("GET"))
+                .and(path("/api/v3/time"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({"serverTime": server_time})))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                    "code": -1021,
+                    "msg": "Timestamp for this request is outside of the recvWindow."
+                })))
+                .up_to_n_times(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server);
+            assert!(trader.get_open_orders(None).await.unwrap().is_empty());
+            assert!(trader.clock_drift().unwrap() > 590_000);
+
+            let requests = server.received_requests().await.unwrap();
+            let timestamp = |r: &wiremock::Request| -> u64 {
+                r.url.query_pairs().find(|(k, _)| k == "timestamp").unwrap().1.parse().unwrap()
+            };
+            let signed: Vec<u64> = requests.iter().filter(|r| r.url.path() == "/api/v3/openOrders").map(timestamp).collect();
+            assert_eq!(signed.len(), 2);
+            assert!(signed[1] >= server_time && signed[1] - signed[0] > 590_000);
+        }
+
+        /// Test that a time sync interval syncs before the first signed call and then only when due
+        #[tokio::test]
+        async fn test_periodic_time_sync() {
+            use std::time::Duration;
+
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/time"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "serverTime": chrono::Utc::now().timestamp_millis()
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+                .expect(2)
+                .mount(&server)
+                .await;
+
+            let trader = mock_trader(&server).with_time_sync_interval(Duration::from_secs(3600));
+            assert!(trader.clock_drift().is_none());
+            trader.get_open_orders(None).await.unwrap();
+            trader.get_open_orders(None).await.unwrap();
+            assert!(trader.clock_drift().is_some());
+        }
+
+        /// Test that myTrades over several days sends 24h windows, moves past a quiet day,
+        /// and continues a busy day by trade id
+        #[tokio::test]
+        async fn test_get_my_trades_range_windows_and_ids() {
+            use crate::trading::MAX_TIME_WINDOW as DAY;
+
+            let server = MockServer::start().await;
+            let trades = |ids: std::ops::Range<u64>, start: u64| -> serde_json::Value {
+                ids.map(|id| json!({
+                    "symbol": "BTCUSDT", "id": id, "orderId": id, "price": "100.0", "qty": "1.0",
+                    "quoteQty": "100.0", "commission": "0.1", "commissionAsset": "USDT",
+                    "time": start + id, "isBuyer": true, "isMaker": false
+                })).collect()
+            };
+            // Day one is quiet: a short page, so the next day is asked for next
+            Mock::given(method("GET"))
+                .and(path("/api/v3/myTrades"))
+                .and(query_param("startTime", "0"))
+                .and(query_param("endTime", DAY.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(trades(1..4, 0)))
+                .expect(1)
+                .mount(&server)
+                .await;
+            // Day two fills a page, so it continues by id
+            Mock::given(method("GET"))
+                .and(path("/api/v3/myTrades"))
+                .and(query_param("startTime", (DAY + 1).to_string()))
+                .and(query_param("endTime", (2 * DAY + 1).to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(trades(100..1100, DAY)))
+                .expect(1)
+                .mount(&server)
+                .await;
+            // A short id page means there's nothing newer, so day three is never asked for
+            Mock::given(method("GET"))
+                .and(path("/api/v3/myTrades"))
+                .and(query_param("fromId", "1099"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(trades(1099..1600, DAY)))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let fills = mock_trader(&server)
+                .get_my_trades_range("BTCUSDT", 0, 3 * DAY)
+                .await
+                .unwrap();
+            assert_eq!(fills.len(), 3 + 1000 + 500);
+            assert!(fills.windows(2).all(|pair| pair[1].id > pair[0].id));
+            assert_eq!(server.received_requests().await.unwrap().len(), 3);
+        }
+
+        /// Test that allOrders walks every 24h window of a longer range and drops orders past the end
+        #[tokio::test]
+        async fn test_get_all_orders_range_advances_windows() {
+            use crate::trading::MAX_TIME_WINDOW as DAY;
+
+            let server = MockServer::start().await;
+            let order = |id: u64, time: u64| -> serde_json::Value {
+                let mut order = order_json(id, "FILLED");
+                order["transactTime"] = json!(time);
+                order
+            };
+            let windows = [
+                (0, DAY, vec![order(1, 5), order(2, DAY)]),
+                (DAY + 1, 2 * DAY + 1, vec![]),
+                (2 * DAY + 2, 2 * DAY + 10, vec![order(3, 2 * DAY + 3)]),
+            ];
+            for (start, end, orders) in windows {
+                Mock::given(method("GET"))
+                    .and(path("/api/v3/allOrders"))
+                    .and(query_param("startTime", start.to_string()))
+                    .and(query_param("endTime", end.to_string()))
+                    .respond_with(ResponseTemplate::new(200).set_body_json(orders))
+                    .expect(1)
+                    .mount(&server)
+                    .await;
+            }
+
+            let orders = mock_trader(&server)
+                .get_all_orders_range("BTCUSDT", 0, 2 * DAY + 10)
+                .await
+                .unwrap();
+            let ids: Vec<OrderId> = orders.iter().map(|o| o.order_id).collect();
+            assert_eq!(ids, vec![OrderId(1), OrderId(2), OrderId(3)]);
+        }
+
+        /// Test that refresh_quotes cancels only stale orders, places only missing quotes,
+        /// and replaces a resting order that is larger than its quote
+        #[tokio::test]
+        async fn test_refresh_quotes() {
+            use crate::orders::{refresh_quotes, Quote};
+            use wiremock::matchers::body_string_contains;
+
+            let resting = |id: u64, side: &str, price: &str, qty: &str| {
+                let mut order = order_json(id, "NEW");
+                order["side"] = json!(side);
+                order["price"] = json!(price);
+                order["origQty"] = json!(qty);
+                order
+            };
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                    resting(1, "BUY", "99.00", "1.00000000"),   // Still wanted
+                    resting(2, "SELL", "105.00", "1.00000000"),  // Price moved away
+                ])))
+                .mount(&server)
+                .await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("orderId=2&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(2, "CANCELED")))
+                .expect(1)
+                .mount(&server)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("side=SELL&"))
+                .and(body_string_contains("price=101.00&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(3, "NEW")))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            let desired = [
+                Quote { side: OrderSide::Buy, price: 99.0, quantity: 1.0 },
+                Quote { side: OrderSide::Sell, price: 101.0, quantity: 1.0 },
+            ];
+            let placed = refresh_quotes(&mock_trader(&server), "BTCUSDT", &desired, 0.01).await.unwrap();
+            assert_eq!(placed.iter().map(|o| o.order_id).collect::<Vec<_>>(), vec![OrderId(3)]);
+            // Nothing else was canceled or placed: order 1 kept its place in the queue
+            let requests = server.received_requests().await.unwrap();
+            assert_eq!(requests.iter().filter(|r| r.method.as_str() == "DELETE").count(), 1);
+            assert_eq!(requests.iter().filter(|r| r.method.as_str() == "POST").count(), 1);
+
+            // A resting buy bigger than the capped quote is replaced, not kept
+            let oversized = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/openOrders"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!([resting(1, "BUY", "99.00", "1.00000000")])))
+                .mount(&oversized)
+                .await;
+            Mock::given(method("DELETE"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("orderId=1&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(1, "CANCELED")))
+                .expect(1)
+                .mount(&oversized)
+                .await;
+            Mock::given(method("POST"))
+                .and(path("/api/v3/order"))
+                .and(body_string_contains("quantity=0.25000000&"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(order_json(4, "NEW")))
+                .expect(1)
+                .mount(&oversized)
+                .await;
+            let capped = [Quote { side: OrderSide::Buy, price: 99.0, quantity: 0.25 }];
+            refresh_quotes(&mock_trader(&oversized), "BTCUSDT", &capped, 0.01).await.unwrap();
+        }
+    }
+
+    /// Tests for market data functionality
+    /// These verify our real-time data structures work correctly
+    mod market_data_tests {
+        use crate::market_data::{Ticker, MarketDataEvent, TradeSide};
+
+        /// Test basic Ticker struct creation and field access
+        #[test]
+        fn test_ticker_creation() {
+            let ticker = Ticker {
+                symbol: "BTCUSDT".to_string(),
+                price: 50000.0,
+                volume: 1000.0,
+                quote_volume: 50_000_000.0,
+                timestamp: 1640995200000,
+            };
+
+            assert_eq!(ticker.symbol, "BTCUSDT");
+            assert_eq!(ticker.price, 50000.0);
+            assert_eq!(ticker.volume, 1000.0);
+        }
+
+        /// Test that MarketDataEvent enum pattern matching works
+        /// This verifies we can extract data from event streams
+        #[test]
+        fn test_market_data_event_matching() {
+            let ticker = Ticker {
+                symbol: "BTCUSDT".to_string(),
+                price: 50000.0,
+                volume: 1000.0,
+                quote_volume: 50_000_000.0,
+                timestamp: 1640995200000,
+            };
+
+            let event = MarketDataEvent::Ticker(ticker.clone());
+
+            // Test pattern matching to extract ticker data
+            match event {
+                MarketDataEvent::Ticker(t) => {
+                    assert_eq!(t.symbol, "BTCUSDT");
+                    assert_eq!(t.price, 50000.0);
+                }
+                _ => panic!("Expected Ticker event"),
+            }
+        }
+
+        /// Test that a raw Binance ticker frame is parsed into a Ticker event
+        /// Covers both the single-stream and the combined-stream message formats
+        #[test]
+        fn test_ticker_frame_parsing() {
+            use crate::market_data::binance::BinanceClient;
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender);
+
+            // Single stream format: the frame is the ticker itself
+            let frame = r#"{"e":"24hrTicker","E":1640995200000,"s":"BTCUSDT","c":"50000.50","v":"1234.5","q":"61725000.0","p":"10.0"}"#;
+            client.handle_message(frame).unwrap();
+
+            // Combined stream format: the ticker is wrapped in {"stream", "data"}
+            let wrapped = format!(r#"{{"stream":"btcusdt@ticker","data":{}}}"#, frame);
+            client.handle_message(&wrapped).unwrap();
+
+            for _ in 0..2 {
+                match receiver.try_recv().unwrap() {
+                    MarketDataEvent::Ticker(t) => {
+                        assert_eq!(t.symbol, "BTCUSDT");
+                        assert_eq!(t.price, 50000.5);
+                        assert_eq!(t.volume, 1234.5);
+                        assert_eq!(t.quote_volume, 61725000.0);
+                        assert_eq!(t.timestamp, 1640995200000);
+                    }
+                    _ => panic!("Expected Ticker event"),
+                }
+            }
+        }
+
+        /// Test that a ticker frame missing a required field is rejected
+        /// instead of silently producing a zero price
+        #[test]
+        fn test_ticker_frame_missing_field_is_error() {
+            use crate::market_data::binance::BinanceClient;
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender);
+
+            let frame = r#"{"e":"24hrTicker","E":1640995200000,"s":"BTCUSDT","v":"1234.5"}"#;
+            assert!(client.handle_message(frame).is_err());
+            assert!(receiver.try_recv().is_err(), "No event should be emitted");
+        }
+
+        /// Test TradeSide enum debug formatting
+        /// Ensures debug output is readable for logging
+        #[test]
+        fn test_trade_side_enum() {
+            assert_eq!(format!("{:?}", TradeSide::Buy), "Buy");
+            assert_eq!(format!("{:?}", TradeSide::Sell), "Sell");
+        }
+
+        /// Test that a failed connection goes Connecting -> Disconnected and
+        /// reports each transition both as an event and through the watch channel
+        #[tokio::test]
+        async fn test_connection_state_transitions_on_failure() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::ConnectionState;
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url("ws://127.0.0.1:1".to_string())
+                .with_reconnect_config(crate::market_data::ReconnectConfig::disabled());
+            let state = client.connection_state();
+
+            assert!(client.start().await.is_err());
+            assert_eq!(*state.borrow(), ConnectionState::Disconnected);
+
+            let mut transitions = Vec::new();
+            while let Ok(MarketDataEvent::ConnectionStateChanged(s)) = receiver.try_recv() {
+                transitions.push(s);
+            }
+            assert_eq!(transitions, vec![ConnectionState::Connecting, ConnectionState::Disconnected]);
+        }
+
+        /// Test that the WebSocket handshake request carries the configured User-Agent
+        #[test]
+        fn test_websocket_handshake_user_agent() {
+            use crate::market_data::binance::BinanceClient;
+
+            let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender);
+            let request = client.build_request("wss://stream.testnet.binance.vision/ws/btcusdt@ticker").unwrap();
+            assert_eq!(request.headers()["user-agent"], crate::DEFAULT_USER_AGENT);
+
+            let client = client.with_user_agent("my-bot/2.0".to_string());
+            let request = client.build_request("wss://stream.testnet.binance.vision/ws/btcusdt@ticker").unwrap();
+            assert_eq!(request.headers()["user-agent"], "my-bot/2.0");
+        }
+
+        /// Helper to build a small order book for fill estimation tests
+        fn sample_book() -> crate::market_data::OrderBook {
+            use crate::market_data::{OrderBook, OrderBookLevel};
+
+            OrderBook {
+                symbol: "BTCUSDT".to_string(),
+                bids: vec![
+                    OrderBookLevel { price: 99.0, quantity: 1.0 },
+                    OrderBookLevel { price: 98.0, quantity: 2.0 },
+                ],
+                asks: vec![
+                    OrderBookLevel { price: 101.0, quantity: 1.0 },
+                    OrderBookLevel { price: 102.0, quantity: 1.0 },
+                    OrderBookLevel { price: 105.0, quantity: 5.0 },
+                ],
+                timestamp: 1640995200000,
+            }
+        }
+
+        /// Test that a buy walks the asks and averages across levels
+        #[test]
+        fn test_estimate_fill_buy_across_levels() {
+            use crate::trading::OrderSide;
+
+            let estimate = sample_book().estimate_fill(OrderSide::Buy, 2.5).unwrap();
+            // 1 @ 101 + 1 @ 102 + 0.5 @ 105 = 255.5 for 2.5 units
+            assert!((estimate.average_price - 102.2).abs() < 1e-9);
+            assert_eq!(estimate.worst_price, 105.0);
+            assert_eq!(estimate.filled_quantity, 2.5);
+            assert!(estimate.fully_filled);
+        }
+
+        /// Test that a sell walks the bids from the top down
+        #[test]
+        fn test_estimate_fill_sell_within_top_level() {
+            use crate::trading::OrderSide;
+
+            let estimate = sample_book().estimate_fill(OrderSide::Sell, 0.5).unwrap();
+            assert_eq!(estimate.average_price, 99.0);
+            assert_eq!(estimate.worst_price, 99.0);
+            assert!(estimate.fully_filled);
+        }
+
+        /// Test that insufficient depth is flagged and empty sides return None
+        #[test]
+        fn test_estimate_fill_insufficient_depth() {
+            use crate::trading::OrderSide;
+
+            let book = sample_book();
+            let estimate = book.estimate_fill(OrderSide::Sell, 10.0).unwrap();
+            assert!(!estimate.fully_filled);
+            assert_eq!(estimate.filled_quantity, 3.0);
+            assert_eq!(estimate.worst_price, 98.0);
+
+            let mut empty = book.clone();
+            empty.asks.clear();
+            assert!(empty.estimate_fill(OrderSide::Buy, 1.0).is_none());
+            assert!(book.estimate_fill(OrderSide::Buy, 0.0).is_none());
+        }
+
+        /// Spawn a local WebSocket server for reconnection tests
+        /// The first `drop_first` connections are closed right after the handshake,
+        /// later ones receive `frame` and are then held open
+        async fn spawn_flaky_ws_server(drop_first: usize, frame: &'static str) -> String {
+            use futures_util::{SinkExt, StreamExt};
+            use tokio_tungstenite::tungstenite::Message;
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+
+            tokio::spawn(async move {
+                let mut connections = 0;
+                while let Ok((tcp, _)) = listener.accept().await {
+                    connections += 1;
+                    let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                    if connections <= drop_first {
+                        let _ = ws.close(None).await;
+                        continue;
+                    }
+                    tokio::spawn(async move {
+                        let _ = ws.send(Message::Text(frame.to_string())).await;
+                        // Keep the connection open until the test ends
+                        while ws.next().await.is_some() {}
+                    });
+                }
+            });
+
+            format!("ws://{}", address)
+        }
+
+        /// Test that the client reconnects after the server drops the connection,
+        /// and that the reconnect hook fires with the attempt number
+        #[tokio::test]
+        async fn test_reconnect_invokes_hook() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::ReconnectConfig;
+            use std::sync::{Arc, Mutex};
+            use std::time::Duration;
+
+            let frame = r#"{"e":"24hrTicker","E":1640995200000,"s":"BTCUSDT","c":"50000.50","v":"1234.5"}"#;
+            let url = spawn_flaky_ws_server(1, frame).await;
+
+            let attempts = Arc::new(Mutex::new(Vec::new()));
+            let recorded = attempts.clone();
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url(url)
+                .with_reconnect_config(ReconnectConfig {
+                    initial_backoff: Duration::from_millis(10),
+                    ..ReconnectConfig::default()
+                })
+                .on_reconnect(Box::new(move |attempt| recorded.lock().unwrap().push(attempt)));
+            tokio::spawn(async move { client.start().await.is_ok() });
+
+            // The ticker only arrives on the second connection
+            let ticker = tokio::time::timeout(Duration::from_secs(5), async {
+                loop {
+                    if let Some(MarketDataEvent::Ticker(t)) = receiver.recv().await {
+                        return t;
+                    }
+                }
+            }).await.unwrap();
+
+            assert_eq!(ticker.symbol, "BTCUSDT");
+            assert_eq!(*attempts.lock().unwrap(), vec![1]);
+        }
+
+        /// Test that one connection carrying ticker, trade, depth and kline frames
+        /// (in the combined-stream wrapper) routes each to the right event
+        #[test]
+        fn test_dispatcher_routes_mixed_frames() {
+            use crate::market_data::binance::BinanceClient;
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender);
+
+            let frames = [
+                r#"{"stream":"btcusdt@ticker","data":{"e":"24hrTicker","E":1,"s":"BTCUSDT","c":"50000.0","v":"10.0"}}"#,
+                r#"{"stream":"btcusdt@aggTrade","data":{"e":"aggTrade","E":2,"s":"BTCUSDT","a":5,"p":"50001.0","q":"0.5","f":1,"l":2,"T":2,"m":true,"M":true}}"#,
+                r#"{"stream":"btcusdt@depth","data":{"e":"depthUpdate","E":3,"s":"BTCUSDT","U":1,"u":2,"b":[["49999.0","1.5"]],"a":[["50002.0","2.0"]]}}"#,
+                r#"{"stream":"btcusdt@kline_1m","data":{"e":"kline","E":4,"s":"BTCUSDT","k":{"t":0,"T":59999,"s":"BTCUSDT","i":"1m","o":"1.0","c":"2.0","h":"3.0","l":"0.5","v":"100.0","x":true}}}"#,
+                r#"{"result":null,"id":1}"#,  // Subscription ack - ignored
+                r#"{"e":"someNewEvent","E":5}"#,  // Unknown type - ignored
+            ];
+            for frame in frames {
+                client.handle_message(frame).unwrap();
+            }
+
+            assert!(matches!(receiver.try_recv().unwrap(), MarketDataEvent::Ticker(t) if t.price == 50000.0));
+            match receiver.try_recv().unwrap() {
+                MarketDataEvent::Trade(t) => {
+                    assert_eq!(t.price, 50001.0);
+                    assert_eq!(t.side, TradeSide::Sell);  // Buyer was maker, so the seller was the aggressor
+                }
+                other => panic!("Expected Trade, got {:?}", other),
+            }
+            match receiver.try_recv().unwrap() {
+                MarketDataEvent::DepthUpdate(update) => {
+                    assert_eq!(update.bids[0].price, 49999.0);
+                    assert_eq!(update.asks[0].quantity, 2.0);
+                }
+                other => panic!("Expected DepthUpdate, got {:?}", other),
+            }
+            match receiver.try_recv().unwrap() {
+                MarketDataEvent::Kline(k) => {
+                    assert_eq!((k.open, k.high, k.low, k.close), (1.0, 3.0, 0.5, 2.0));
+                    assert!(k.is_closed);
+                }
+                other => panic!("Expected Kline, got {:?}", other),
+            }
+            assert!(receiver.try_recv().is_err(), "Ack and unknown frames produce no events");
+        }
+
+        /// Test that min_interval is enforced even when the backoff is zero
+        #[tokio::test]
+        async fn test_reconnect_min_interval() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::ReconnectConfig;
+            use std::time::{Duration, Instant};
+
+            let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url("ws://127.0.0.1:1".to_string())
+                .with_reconnect_config(ReconnectConfig {
+                    initial_backoff: Duration::ZERO,
+                    max_backoff: Duration::ZERO,
+                    max_retries: Some(3),
+                    min_interval: Duration::from_millis(50),
+                    max_total_duration: None,
+                    reset_after: Duration::from_secs(30),
+                });
+
+            let started = Instant::now();
+            assert!(client.start().await.is_err());
+            // 3 retries, each waiting at least 50ms
+            assert!(started.elapsed() >= Duration::from_millis(150));
+        }
+
+        /// Test that reconnection gives up after max_total_duration with a terminal error event
+        #[tokio::test]
+        async fn test_reconnect_gives_up_after_max_total_duration() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::{ConnectionState, ReconnectConfig};
+            use std::time::{Duration, Instant};
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url("ws://127.0.0.1:1".to_string())
+                .with_reconnect_config(ReconnectConfig {
+                    min_interval: Duration::from_millis(20),
+                    initial_backoff: Duration::from_millis(20),
+                    max_total_duration: Some(Duration::from_millis(200)),
+                    ..ReconnectConfig::default()
+                });
+            let state = client.connection_state();
+
+            let started = Instant::now();
+            let err = client.start().await.unwrap_err();
+            assert!(err.to_string().contains("Giving up"));
+            assert!(started.elapsed() < Duration::from_secs(2));
+            assert_eq!(*state.borrow(), ConnectionState::Disconnected);
+
+            let mut saw_terminal_error = false;
+            while let Ok(event) = receiver.try_recv() {
+                if let MarketDataEvent::Error(msg) = event {
+                    saw_terminal_error |= msg.contains("Giving up");
+                }
+            }
+            assert!(saw_terminal_error);
+        }
+
+        /// Test that 15 one-minute candles resample into one correct 15-minute candle
+        #[test]
+        fn test_resample_one_minute_to_fifteen() {
+            use crate::market_data::{resample, Kline};
+
+            let start = 1_640_995_200_000u64;
+            let klines: Vec<Kline> = (0..15)
+                .map(|i| Kline {
+                    symbol: "BTCUSDT".to_string(),
+                    interval: "1m".to_string(),
+                    open_time: start + i * 60_000,
+                    close_time: start + (i + 1) * 60_000 - 1,
+                    open: 100.0 + i as f64,
+                    high: 101.0 + i as f64,
+                    low: 99.0 + i as f64,
+                    close: 100.5 + i as f64,
+                    volume: 1.0,
+                    quote_volume: 100.0,
+                    trade_count: 10,
+                    taker_buy_base: 0.5,
+                    taker_buy_quote: 50.0,
+                    is_closed: true,
+                })
+                .collect();
+
+            let bars = resample(&klines, 15);
+            assert_eq!(bars.len(), 1);
+            let bar = &bars[0];
+            assert_eq!(bar.interval, "15m");
+            assert_eq!(bar.open_time, start);
+            assert_eq!(bar.close_time, start + 15 * 60_000 - 1);
+            assert_eq!(bar.open, 100.0);   // First open
+            assert_eq!(bar.high, 115.0);   // Max high (101 + 14)
+            assert_eq!(bar.low, 99.0);     // Min low
+            assert_eq!(bar.close, 114.5);  // Last close
+            assert_eq!(bar.volume, 15.0);
+            assert!(bar.is_closed);
+
+            // 20 candles at factor 15: one full bar plus a partial trailing one
+            let bars = resample(&[klines.clone(), klines[..5].to_vec()].concat(), 15);
+            assert_eq!(bars.len(), 2);
+            assert!(!bars[1].is_closed);
+        }
+
+        /// Test that datetime() converts millisecond timestamps without an off-by-1000
+        #[test]
+        fn test_ticker_datetime() {
+            let ticker = Ticker {
+                symbol: "BTCUSDT".to_string(),
+                price: 50000.0,
+                volume: 1000.0,
+                quote_volume: 50_000_000.0,
+                timestamp: 1640995200123,
+            };
+
+            let dt = ticker.datetime();
+            assert_eq!(dt.to_rfc3339(), "2022-01-01T00:00:00.123+00:00");
+            assert_eq!(dt.timestamp_millis(), 1640995200123);
+        }
+
+        /// Test that a closed-only aggregator suppresses in-progress kline updates
+        #[test]
+        fn test_candle_aggregator_closed_only() {
+            use crate::market_data::{CandleAggregator, Kline};
+
+            let update = |close: f64, is_closed: bool| Kline {
+                symbol: "BTCUSDT".to_string(),
+                interval: "1m".to_string(),
+                open_time: 1_640_995_200_000,
+                close_time: 1_640_995_259_999,
+                open: 100.0,
+                high: 102.0,
+                low: 99.0,
+                close,
+                volume: 1.0,
+                quote_volume: 100.0,
+                trade_count: 10,
+                taker_buy_base: 0.5,
+                taker_buy_quote: 50.0,
+                is_closed,
+            };
+
+            let mut closed_only = CandleAggregator::new(true);
+            assert!(closed_only.on_kline(update(100.5, false)).is_none());
+            assert!(closed_only.on_kline(update(101.0, false)).is_none());
+            assert_eq!(closed_only.forming("BTCUSDT").unwrap().close, 101.0);
+            let emitted = closed_only.on_kline(update(101.5, true)).unwrap();
+            assert_eq!(emitted.close, 101.5);
+            assert!(closed_only.forming("BTCUSDT").is_none());
+            assert_eq!(closed_only.closed_candles("BTCUSDT").count(), 1);
+
+            // Without the flag every update comes through
+            let mut all_updates = CandleAggregator::new(false);
+            assert!(all_updates.on_kline(update(100.5, false)).is_some());
+            assert!(all_updates.on_kline(update(101.5, true)).is_some());
+        }
+
+        /// Spawn a WebSocket server that answers every SUBSCRIBE with `reply`
+        /// (`{id}` is replaced by the request id); None means never answer
+        async fn spawn_subscribe_server(reply: Option<&'static str>) -> String {
+            use futures_util::{SinkExt, StreamExt};
+            use tokio_tungstenite::tungstenite::Message;
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap();
+
+            tokio::spawn(async move {
+                while let Ok((tcp, _)) = listener.accept().await {
+                    let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                    tokio::spawn(async move {
+                        while let Some(Ok(Message::Text(text))) = ws.next().await {
+                            let request: serde_json::Value = serde_json::from_str(&text).unwrap();
+                            assert_eq!(request["method"], "SUBSCRIBE");
+                            if let Some(reply) = reply {
+                                let id = request["id"].to_string();
+                                let _ = ws.send(Message::Text(reply.replace("{id}", &id))).await;
+                            }
+                        }
+                    });
+                }
+            });
+
+            format!("ws://{}", address)
+        }
+
+        /// Test that subscribe waits for the matching confirmation, and surfaces rejections and timeouts
+        #[tokio::test]
+        async fn test_subscribe_awaits_confirmation() {
+            use crate::market_data::MarketDataStream;
+            use std::time::Duration;
+
+            let connect = |url: String| MarketDataStream::new_with_client(vec!["BTCUSDT".to_string()], move |c| {
+                c.with_stream_base_url(url)
+            });
+
+            let confirmed = connect(spawn_subscribe_server(Some(r#"{"result":null,"id":{id}}"#)).await).await.unwrap();
+            confirmed.subscribe_with_timeout("ETHUSDT", Duration::from_secs(5)).await.unwrap();
+
+            let rejected = connect(spawn_subscribe_server(Some(r#"{"error":{"code":2,"msg":"Invalid request"},"id":{id}}"#)).await).await.unwrap();
+            let err = rejected.subscribe_with_timeout("ETHUSDT", Duration::from_secs(5)).await.unwrap_err();
+            assert!(err.to_string().contains("Invalid request"));
+
+            let silent = connect(spawn_subscribe_server(None).await).await.unwrap();
+            let err = silent.subscribe_with_timeout("ETHUSDT", Duration::from_millis(300)).await.unwrap_err();
+            assert!(err.to_string().contains("No confirmation"));
+        }
+
+        /// Test that an enabled heartbeat fills in for missing events on a quiet stream
+        #[tokio::test]
+        async fn test_heartbeat_on_quiet_stream() {
+            use crate::market_data::MarketDataStream;
+            use std::time::Duration;
+
+            let url = spawn_subscribe_server(None).await;
+            let mut stream = MarketDataStream::new_with_client(vec!["BTCUSDT".to_string()], move |c| {
+                c.with_stream_base_url(url)
+            }).await.unwrap().with_heartbeat(Duration::from_millis(100));
+
+            // The server never sends market data, so after the connection state changes we get heartbeats
+            let mut heartbeat = None;
+            for _ in 0..10 {
+                match stream.next_event().await {
+                    Some(MarketDataEvent::Heartbeat { timestamp }) => { heartbeat = Some(timestamp); break; }
+                    Some(_) => continue,
+                    None => break,
+                }
+            }
+            assert!(heartbeat.expect("no heartbeat received") > 1_600_000_000_000);
+        }
+
+        /// Test that shutdown closes a live connection and stops the client without reconnecting
+        #[tokio::test]
+        async fn test_stream_shutdown() {
+            use crate::market_data::{ConnectionState, MarketDataStream};
+            use std::time::Duration;
+
+            let url = spawn_subscribe_server(None).await;
+            let mut stream = MarketDataStream::new_with_client(vec!["BTCUSDT".to_string()], move |c| {
+                c.with_stream_base_url(url)
+            }).await.unwrap();
+
+            // Wait until the connection is up
+            while let Some(event) = stream.next_event().await {
+                if matches!(event, MarketDataEvent::ConnectionStateChanged(ConnectionState::Connected)) {
+                    break;
+                }
+            }
+
+            let state = stream.watch_connection_state();
+            tokio::time::timeout(Duration::from_secs(5), stream.shutdown())
+                .await
+                .expect("shutdown should finish promptly");
+            assert_eq!(*state.borrow(), ConnectionState::Disconnected);
+        }
+
+        /// Test that the configured message size limit applies: an oversized frame is
+        /// reported as an error, while the generous defaults accept the same frame
+        #[tokio::test]
+        async fn test_websocket_message_size_limit() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::ReconnectConfig;
+            use std::time::Duration;
+
+            let frame = r#"{"e":"24hrTicker","E":1640995200000,"s":"BTCUSDT","c":"50000.50","v":"1234.5"}"#;
+
+            // A 32-byte limit can't fit the ticker frame
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url(spawn_flaky_ws_server(0, frame).await)
+                .with_reconnect_config(ReconnectConfig::disabled())
+                .with_size_limits(Some(32), Some(32));
+            tokio::spawn(async move { client.start().await.is_ok() });
+
+            let error = tokio::time::timeout(Duration::from_secs(5), async {
+                loop {
+                    match receiver.recv().await {
+                        Some(MarketDataEvent::Error(e)) => return e,
+                        Some(MarketDataEvent::Ticker(_)) => panic!("oversized frame got through"),
+                        Some(_) => continue,
+                        None => panic!("stream ended without an error"),
+                    }
+                }
+            }).await.unwrap();
+            assert!(error.to_lowercase().contains("too"), "unexpected error: {}", error);
+
+            // The defaults let it through
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url(spawn_flaky_ws_server(0, frame).await);
+            tokio::spawn(async move { client.start().await.is_ok() });
+
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while !matches!(receiver.recv().await, Some(MarketDataEvent::Ticker(_))) {}
+            }).await.unwrap();
+        }
+
+        /// Test that a recording saved to disk replays the same events, with the gaps scaled by speed
+        #[tokio::test]
+        async fn test_event_recorder_round_trip_and_replay() {
+            use crate::market_data::{EventRecorder, FileReplaySource, MarketDataSource};
+            use std::time::{Duration, Instant};
+
+            let ticker = |price: f64| MarketDataEvent::Ticker(Ticker {
+                symbol: "BTCUSDT".to_string(),
+                price,
+                volume: 1.0,
+                quote_volume: price,
+                timestamp: 1_640_995_200_000,
+            });
+
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("session.jsonl");
+            let mut recorder = EventRecorder::to_file(&path).unwrap();
+            recorder.record(&ticker(50000.0));
+            recorder.record(&ticker(50001.0));
+            let mut recorded = recorder.events().to_vec();
+            drop(recorder);  // Flushes the file
+
+            let mut from_file = FileReplaySource::open(&path).unwrap().with_speed(f64::INFINITY);
+            assert_eq!(from_file.next_event().await, Some(ticker(50000.0)));
+            assert_eq!(from_file.next_event().await, Some(ticker(50001.0)));
+            assert_eq!(from_file.next_event().await, None);
+
+            // With a 1s gap between receive times, 10x speed waits ~100ms
+            recorded[0].received_at = 1_000;
+            recorded[1].received_at = 2_000;
+            let mut replay = FileReplaySource::from_events(recorded).with_speed(10.0);
+            let start = Instant::now();
+            replay.next_event().await.unwrap();
+            replay.next_event().await.unwrap();
+            let elapsed = start.elapsed();
+            assert!(elapsed >= Duration::from_millis(100) && elapsed < Duration::from_millis(900), "{:?}", elapsed);
+        }
+
+        /// Test rolling spread aggregation over synthetic order book updates, plus the CSV output
+        #[test]
+        fn test_spread_tracker_rolling_windows() {
+            use crate::market_data::{OrderBook, OrderBookLevel, SpreadTracker};
+            use std::time::Duration;
+
+            let book = |timestamp: u64, bid: f64, ask: f64| OrderBook {
+                symbol: "BTCUSDT".to_string(),
+                // Unsorted levels on purpose: the best prices are picked, not the first ones
+                bids: vec![OrderBookLevel { price: bid - 1.0, quantity: 1.0 }, OrderBookLevel { price: bid, quantity: 1.0 }],
+                asks: vec![OrderBookLevel { price: ask + 1.0, quantity: 1.0 }, OrderBookLevel { price: ask, quantity: 1.0 }],
+                timestamp,
+            };
+
+            let dir = tempfile::tempdir().unwrap();
+            let csv_path = dir.path().join("spreads.csv");
+            let mut tracker = SpreadTracker::new("BTCUSDT")
+                .with_retention(Duration::from_secs(10))
+                .with_csv(&csv_path)
+                .unwrap();
+            assert_eq!(tracker.current(), None);
+
+            // Spreads of 4, 2, 1, 3 at t = 0s, 5s, 8s, 12s
+            tracker.on_event(&MarketDataEvent::OrderBook(book(0, 100.0, 104.0)));
+            tracker.on_book(&book(5_000, 100.0, 102.0));
+            tracker.on_book(&book(8_000, 100.0, 101.0));
+            tracker.on_book(&book(12_000, 100.0, 103.0));
+            // Other symbols are ignored
+            assert!(tracker.on_book(&OrderBook { symbol: "ETHUSDT".to_string(), ..book(12_500, 1.0, 50.0) }).is_none());
+
+            assert_eq!(tracker.current(), Some(3.0));
+            // Last 5s: samples at 8s and 12s
+            assert_eq!(tracker.average(Duration::from_secs(5)), Some(2.0));
+            assert_eq!(tracker.max(Duration::from_secs(5)), Some(3.0));
+            // The t=0 sample fell out of the 10s retention
+            assert_eq!(tracker.samples().count(), 3);
+            assert_eq!(tracker.max(Duration::from_secs(60)), Some(3.0));
+
+            drop(tracker);
+            let csv = std::fs::read_to_string(&csv_path).unwrap();
+            let lines: Vec<&str> = csv.lines().collect();
+            assert_eq!(lines[0], "timestamp,bid,ask,spread");
+            assert_eq!(lines[1], "0,100,104,4");
+            assert_eq!(lines.len(), 5);
+        }
+
+        /// Test bookTicker support: stream names in the URL and parsing the event-type-less frames
+        #[test]
+        fn test_book_ticker_stream() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::StreamType;
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()], sender)
+                .with_stream_base_url("wss://example.test".to_string())
+                .with_stream_types(vec![StreamType::BookTicker]);
+            assert_eq!(client.build_stream_url().unwrap(), "wss://example.test/stream?streams=btcusdt@bookTicker/ethusdt@bookTicker");
+
+            // Both the raw and the combined-stream form
+            client.handle_message(r#"{"u":400900217,"s":"BTCUSDT","b":"50000.10","B":"1.5","a":"50000.20","A":"2.25"}"#).unwrap();
+            client.handle_message(r#"{"stream":"ethusdt@bookTicker","data":{"u":1,"s":"ETHUSDT","b":"3000.00","B":"4","a":"3000.50","A":"5"}}"#).unwrap();
+
+            assert_eq!(receiver.try_recv().unwrap(), MarketDataEvent::BookTicker {
+                symbol: "BTCUSDT".to_string(), bid: 50000.10, bid_qty: 1.5, ask: 50000.20, ask_qty: 2.25,
+            });
+            assert!(matches!(receiver.try_recv().unwrap(), MarketDataEvent::BookTicker { symbol, ask, .. } if symbol == "ETHUSDT" && ask == 3000.5));
+        }
+
+        /// Test that mid is the plain midpoint and the microprice leans toward the thinner side
+        #[test]
+        fn test_mid_price_and_microprice() {
+            use crate::market_data::{microprice, mid_price};
+
+            assert_eq!(mid_price(100.0, 102.0), 101.0);
+
+            // Balanced sizes: same as the mid
+            assert_eq!(microprice(100.0, 5.0, 102.0, 5.0), 101.0);
+            // Thin ask (heavy bid): pulled up toward the ask
+            let toward_ask = microprice(100.0, 9.0, 102.0, 1.0);
+            assert!((toward_ask - 101.8).abs() < 1e-9);
+            // Thin bid (heavy ask): pulled down toward the bid
+            let toward_bid = microprice(100.0, 1.0, 102.0, 9.0);
+            assert!((toward_bid - 100.2).abs() < 1e-9);
+            // No size on either side falls back to the mid
+            assert_eq!(microprice(100.0, 0.0, 102.0, 0.0), 101.0);
+        }
+
+        /// Test rolling buy/sell aggressor volume, delta and ratio over a mixed trade sequence
+        #[test]
+        fn test_trade_flow_delta_and_ratio() {
+            use crate::market_data::{Trade, TradeFlow};
+            use std::time::Duration;
+
+            let trade = |timestamp: u64, side: TradeSide, quantity: f64| Trade {
+                symbol: "BTCUSDT".to_string(),
+                price: 50000.0,
+                quantity,
+                side,
+                timestamp,
+            };
+
+            let mut flow = TradeFlow::new("BTCUSDT", Duration::from_secs(10));
+            assert_eq!(flow.ratio(), None);
+
+            flow.on_trade(&trade(0, TradeSide::Buy, 2.0));
+            flow.on_trade(&trade(1_000, TradeSide::Sell, 0.5));
+            flow.on_event(&MarketDataEvent::Trade(trade(2_000, TradeSide::Buy, 1.0)));
+            flow.on_trade(&trade(3_000, TradeSide::Sell, 1.5));
+            assert_eq!(flow.buy_volume(), 3.0);
+            assert_eq!(flow.sell_volume(), 2.0);
+            assert_eq!(flow.delta(), 1.0);
+            assert_eq!(flow.ratio(), Some(0.6));
+
+            // At t=10.5s the first trade (2.0 bought at t=0) leaves the 10s window
+            flow.on_trade(&trade(10_500, TradeSide::Sell, 1.0));
+            assert_eq!(flow.trade_count(), 4);
+            assert_eq!(flow.buy_volume(), 1.0);
+            assert_eq!(flow.sell_volume(), 3.0);
+            assert_eq!(flow.delta(), -2.0);
+
+            // Other symbols don't count
+            flow.on_trade(&Trade { symbol: "ETHUSDT".to_string(), ..trade(11_000, TradeSide::Buy, 100.0) });
+            assert_eq!(flow.delta(), -2.0);
+
+            // Late trades are evicted by their own timestamp, not by when they arrived
+            flow.on_trade(&trade(100, TradeSide::Buy, 50.0));  // Already outside the window
+            flow.on_trade(&trade(1_500, TradeSide::Buy, 4.0));
+            assert_eq!((flow.trade_count(), flow.buy_volume()), (5, 5.0));
+            flow.on_trade(&trade(12_000, TradeSide::Sell, 0.5));
+            assert_eq!(flow.trade_count(), 4);
+            assert_eq!(flow.buy_volume(), 1.0);
+            assert_eq!(flow.sell_volume(), 3.0);
+        }
+
+        /// Injected latency delays every event; jitter stays within its bound
+        #[tokio::test]
+        async fn test_simulated_latency_delays_events() {
+            use crate::market_data::{MarketDataSource, MockMarketDataSource, SimulatedLatency};
+            use std::time::{Duration, Instant};
+
+            let mut source = MockMarketDataSource::from_prices("BTCUSDT", &[1.0, 2.0, 3.0])
+                .with_latency(Duration::from_millis(20), Duration::ZERO);
+            let started = Instant::now();
+            let mut count = 0;
+            while MarketDataSource::next_event(&mut source).await.is_some() {
+                count += 1;
+            }
+            assert_eq!(count, 3);
+            assert!(started.elapsed() >= Duration::from_millis(60));
+
+            // Jittered delays land in latency..=latency+jitter and repeat for the same seed
+            let mut a = SimulatedLatency::with_seed(Duration::from_millis(10), Duration::from_millis(5), 42);
+            let mut b = SimulatedLatency::with_seed(Duration::from_millis(10), Duration::from_millis(5), 42);
+            for _ in 0..100 {
+                let delay = a.next_delay();
+                assert!(delay >= Duration::from_millis(10) && delay <= Duration::from_millis(15));
+                assert_eq!(delay, b.next_delay());
+            }
+        }
+
+        /// Test splitting a large symbol list across connections under the stream limit
+        #[test]
+        fn test_partition_symbols() {
+            use crate::market_data::{partition_symbols, MAX_STREAMS_PER_CONNECTION};
+
+            let symbols: Vec<String> = (0..300).map(|i| format!("SYM{}USDT", i)).collect();
+
+            // 300 ticker streams fit on one connection
+            let groups = partition_symbols(&symbols, 1, MAX_STREAMS_PER_CONNECTION);
+            assert_eq!(groups.len(), 1);
+            assert_eq!(groups[0].len(), 300);
+
+            // 600 streams (ticker + bookTicker) at 200 per connection need 3, evenly filled
+            let groups = partition_symbols(&symbols, 2, 200);
+            assert_eq!(groups.len(), 3);
+            assert!(groups.iter().all(|g| g.len() == 100));
+
+            // Uneven splits stay within one symbol of each other, and nothing is lost or doubled
+            let groups = partition_symbols(&symbols, 1, 64);
+            assert_eq!(groups.len(), 5);
+            let sizes: Vec<usize> = groups.iter().map(|g| g.len()).collect();
+            assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+            assert!(sizes.iter().all(|&n| n <= 64));
+            let mut all: Vec<&String> = groups.iter().flatten().collect();
+            all.sort();
+            all.dedup();
+            assert_eq!(all.len(), 300);
+
+            let doubled = vec!["BTCUSDT".to_string(), "BTCUSDT".to_string()];
+            assert_eq!(partition_symbols(&doubled, 1, 10), vec![vec!["BTCUSDT".to_string()]]);
+            assert!(partition_symbols(&[], 1, 10).is_empty());
+        }
+
+        /// Test that added symbols go to the least-loaded connection, or a new one when all are full
+        #[tokio::test]
+        async fn test_supervisor_add_symbol() {
+            use crate::market_data::{StreamSupervisor, StreamType};
+
+            let url = spawn_subscribe_server(Some(r#"{"result":null,"id":{id}}"#)).await;
+            let symbols: Vec<String> = ["BTCUSDT", "ETHUSDT", "BNBUSDT"].iter().map(|s| s.to_string()).collect();
+            let mut supervisor = StreamSupervisor::with_client(
+                symbols,
+                vec![StreamType::Ticker],
+                2,
+                Box::new(move |c| c.with_stream_base_url(url.clone())),
+            );
+            assert_eq!(supervisor.connection_loads(), vec![2, 1]);
+
+            // Fits next to BNBUSDT, confirmed over the existing connection
+            supervisor.add_symbol("SOLUSDT").await.unwrap();
+            assert_eq!(supervisor.connection_loads(), vec![2, 2]);
+
+            // Both connections are full now
+            supervisor.add_symbol("XRPUSDT").await.unwrap();
+            assert_eq!(supervisor.connection_loads(), vec![2, 2, 1]);
+
+            // Already streaming - nothing changes
+            supervisor.add_symbol("ETHUSDT").await.unwrap();
+            assert_eq!(supervisor.symbol_count(), 5);
+            supervisor.shutdown().await;
+        }
+
+        /// Test that the leaderboard re-sorts as 24h quote volumes change
+        #[test]
+        fn test_volume_leaderboard() {
+            use crate::market_data::VolumeLeaderboard;
+
+            let ticker = |symbol: &str, quote_volume: f64| MarketDataEvent::Ticker(Ticker {
+                symbol: symbol.to_string(),
+                price: 1.0,
+                volume: 0.0,
+                quote_volume,
+                timestamp: 1_640_995_200_000,
+            });
+
+            let mut board = VolumeLeaderboard::new();
+            board.on_event(&ticker("BTCUSDT", 900.0));
+            board.on_event(&ticker("ETHUSDT", 500.0));
+            board.on_event(&ticker("SOLUSDT", 700.0));
+            assert_eq!(board.top(2), vec![("BTCUSDT".to_string(), 900.0), ("SOLUSDT".to_string(), 700.0)]);
+            assert_eq!(board.rank("ETHUSDT"), Some(3));
+
+            // ETH's volume jumps past everyone; BTC falls - each symbol appears once
+            board.on_event(&ticker("ETHUSDT", 1000.0));
+            board.on_event(&ticker("BTCUSDT", 100.0));
+            let order: Vec<String> = board.top(10).into_iter().map(|(s, _)| s).collect();
+            assert_eq!(order, vec!["ETHUSDT", "SOLUSDT", "BTCUSDT"]);
+            assert_eq!(board.len(), 3);
+
+            // Equal volumes sort by symbol
+            board.on_event(&ticker("ADAUSDT", 700.0));
+            assert_eq!(board.rank("ADAUSDT"), Some(2));
+            assert_eq!(board.rank("SOLUSDT"), Some(3));
+            assert_eq!(board.rank("XRPUSDT"), None);
+        }
+
+        /// Test that set_filter drops non-matching events before next_event returns them
+        #[tokio::test]
+        async fn test_stream_filter() {
+            use crate::market_data::MarketDataStream;
+            use futures_util::{SinkExt, StreamExt};
+            use std::time::Duration;
+            use tokio_tungstenite::tungstenite::Message;
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("ws://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                for symbol in ["BTCUSDT", "ETHUSDT", "BTCUSDT", "SOLUSDT"] {
+                    let frame = format!(r#"{{"e":"24hrTicker","E":1,"s":"{}","c":"1.0","v":"1.0"}}"#, symbol);
+                    ws.send(Message::Text(frame)).await.unwrap();
+                }
+                while ws.next().await.is_some() {}
+            });
+
+            let mut stream = MarketDataStream::new_with_client(vec!["BTCUSDT".to_string()], move |c| {
+                c.with_stream_base_url(url)
+            }).await.unwrap();
+            stream.set_filter(Box::new(|event| event.symbol().is_some_and(|s| s != "BTCUSDT")));
+
+            // Connection state changes and BTC tickers never come out
+            let first = tokio::time::timeout(Duration::from_secs(5), stream.next_event()).await.unwrap().unwrap();
+            assert_eq!(first.symbol(), Some("ETHUSDT"));
+
+            // Swapping the filter takes effect right away
+            stream.clear_filter();
+            let second = tokio::time::timeout(Duration::from_secs(5), stream.next_event()).await.unwrap().unwrap();
+            assert_eq!(second.symbol(), Some("BTCUSDT"));
+            stream.shutdown().await;
+        }
+
+        /// Test the time-to-fill heuristic at, behind and far from the best price
+        #[test]
+        fn test_trade_flow_time_to_fill() {
+            use crate::market_data::{Trade, TradeFlow};
+            use crate::trading::OrderSide;
+            use std::time::Duration;
+
+            let mut flow = TradeFlow::new("BTCUSDT", Duration::from_secs(10));
+            // 5 BTC sold by aggressors over the 10s window = 0.5 BTC/s hitting bids
+            for i in 0..5 {
+                flow.on_trade(&Trade {
+                    symbol: "BTCUSDT".to_string(),
+                    price: 50000.0,
+                    quantity: 1.0,
+                    side: TradeSide::Sell,
+                    timestamp: i * 2_000,
+                });
+            }
+
+            // A 1 BTC buy at the best bid needs 2s of selling
+            assert_eq!(flow.time_to_fill(OrderSide::Buy, 50000.0, 1.0, 50000.0), Some(Duration::from_secs(2)));
+            // 0.1% below the bid needs twice the volume
+            let behind = flow.time_to_fill(OrderSide::Buy, 49950.0, 1.0, 50000.0).unwrap();
+            assert!((behind.as_secs_f64() - 4.0).abs() < 1e-6);
+            // Too far away, or nobody buying to fill a sell
+            assert_eq!(flow.time_to_fill(OrderSide::Buy, 49000.0, 1.0, 50000.0), None);
+            assert_eq!(flow.time_to_fill(OrderSide::Sell, 50000.0, 1.0, 50000.0), None);
+        }
+
+        /// Test building a TLS connector from custom roots, and that a client using one still connects
+        #[tokio::test]
+        async fn test_tls_connector_with_roots() {
+            use crate::market_data::{tls_connector_with_roots, MarketDataStream};
+            use std::time::Duration;
+
+            // Self-signed stand-in for a corporate root CA
+            const ROOT_PEM: &str = "\
+                -----BEGIN CERTIFICATE-----\n\
+                MIIDCzCCAfOgAwIBAgIULCxuXGnkkuK6h4Sg2HNjWwCUXfswDQYJKoZIhvcNAQEL\n\
+                BQAwFDESMBAGA1UEAwwJdGVzdC1yb290MCAXDTI2MTAxNjExMjAxN1oYDzIxMjYw\n\
+                OTIyMTEyMDE3WjAUMRIwEAYDVQQDDAl0ZXN0LXJvb3QwggEiMA0GCSqGSIb3DQEB\n\
+                AQUAA4IBDwAwggEKAoIBAQDlkzhOSmXAXB3r0EU/L3V4gXz+iu2ox+OYKfDRKZQo\n\
+                HGFng0xyYsXcGjMMPuUpLSHHyRnF9KSO84vzTZKgcmpUCiahBezziifgJWld3Pns\n\
+                xwcKrGFTovrZmmWJMqGFmfnB38HaEwVAoAU9aIaPM42TLi84ctImmom32K49Pbuu\n\
+                78yO7kQd30zwgHcmWKCKa5PKSSIB3wv2smphgn05YZa42Xvy+iD9qUeUO761yiGj\n\
+                68GTlan4Ol0gnE4/NqYYi5zK3owywAC4QY3hHbRsbGBWln5ED6O5v2YXGwHB9Jsa\n\
+                kq4M2OTGYFXfsntNLVVsOzMlHatigO33D8XonGrt0xgjAgMBAAGjUzBRMB0GA1Ud\n\
+                DgQWBBSZnId00cOIa8BmIcJ9m5q3197ipDAfBgNVHSMEGDAWgBSZnId00cOIa8Bm\n\
+                IcJ9m5q3197ipDAPBgNVHRMBAf8EBTADAQH/MA0GCSqGSIb3DQEBCwUAA4IBAQCC\n\
+                PlSkg/AgZX03HwKMIHSnvtMrIltQcwu13OuGm2ayxUXBsAKwZvUQDCkBf1u9p5Id\n\
+                33/SXYSPKxKnUPC8D2UBdMyYbyGON0Ou/OnIWLMph/2yX1w+AsAK+AWSipS62YiV\n\
+                +voTXCFLMYMNAYTwpuHHICMRnLBQ8jZ6tbxg4DYGWcbrOUapQbnkRw/KPhxtL6Kk\n\
+                O2p5VA0b7sqNzZoy9vJrKLYm2rVd8D9ew2kUPZjvl9J1fL82dx/uGpV6DoURrmUc\n\
+                v9hjnGzW+eEJNbaMbQY4YVudFjNyILG5m/qMxBAAP+UjBdxU4c+21kDkeVMvwfx0\n\
+                z+m4ZE9obVFwV+lKAXn4\n\
+                -----END CERTIFICATE-----\n\
+                ";
+
+            assert!(tls_connector_with_roots(&[ROOT_PEM.as_bytes()]).is_ok());
+            assert!(tls_connector_with_roots(&[b"not a certificate".as_slice()]).is_err());
+
+            // The connector rides along on every connect (plain ws:// just doesn't use it)
+            let url = spawn_flaky_ws_server(0, r#"{"e":"24hrTicker","E":1,"s":"BTCUSDT","c":"1.0","v":"1.0"}"#).await;
+            let mut stream = MarketDataStream::new_with_client(vec!["BTCUSDT".to_string()], move |c| {
+                c.with_stream_base_url(url)
+                    .with_tls_connector(tls_connector_with_roots(&[ROOT_PEM.as_bytes()]).unwrap())
+            }).await.unwrap();
+            let ticker = loop {
+                match tokio::time::timeout(Duration::from_secs(5), stream.next_event()).await.unwrap() {
+                    Some(MarketDataEvent::Ticker(ticker)) => break ticker,
+                    Some(_) => continue,
+                    None => panic!("stream ended"),
+                }
+            };
+            assert_eq!(ticker.symbol, "BTCUSDT");
+            stream.shutdown().await;
+        }
+
+        /// Test that every subscribe_events receiver gets each event, and a lagging one is told what it missed
+        #[tokio::test]
+        async fn test_stream_broadcast() {
+            use crate::market_data::MarketDataStream;
+            use futures_util::{SinkExt, StreamExt};
+            use std::time::Duration;
+            use tokio::sync::broadcast::error::RecvError;
+            use tokio_tungstenite::tungstenite::Message;
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("ws://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                let (tcp, _) = listener.accept().await.unwrap();
+                let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+                for symbol in ["BTCUSDT", "ETHUSDT", "SOLUSDT"] {
+                    let frame = format!(r#"{{"e":"24hrTicker","E":1,"s":"{}","c":"1.0","v":"1.0"}}"#, symbol);
+                    ws.send(Message::Text(frame)).await.unwrap();
+                }
+                while ws.next().await.is_some() {}
+            });
+
+            let mut stream = MarketDataStream::new_with_client(vec!["BTCUSDT".to_string()], move |c| {
+                c.with_stream_base_url(url)
+            }).await.unwrap().with_broadcast(2);
+            stream.set_filter(Box::new(|event| event.symbol().is_some()));
+            let mut strategy = stream.subscribe_events();
+            let mut logger = stream.subscribe_events();
+
+            let mut delivered = Vec::new();
+            for _ in 0..3 {
+                let event = tokio::time::timeout(Duration::from_secs(5), stream.next_event()).await.unwrap().unwrap();
+                delivered.push(event.symbol().unwrap().to_string());
+                // The strategy keeps up; the logger doesn't read until the end
+                assert_eq!(strategy.recv().await.unwrap().symbol(), event.symbol());
+            }
+            assert_eq!(delivered, vec!["BTCUSDT", "ETHUSDT", "SOLUSDT"]);
+
+            // Capacity 2: the logger lost the oldest event and resumes after it
+            assert!(matches!(logger.recv().await, Err(RecvError::Lagged(1))));
+            assert_eq!(logger.recv().await.unwrap().symbol(), Some("ETHUSDT"));
+            assert_eq!(logger.recv().await.unwrap().symbol(), Some("SOLUSDT"));
+            stream.shutdown().await;
+        }
+
+        /// Test that differently-cased and repeated symbols produce each stream exactly once
+        #[tokio::test]
+        async fn test_stream_url_dedups_symbols() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::{normalize_symbols, MarketDataStream, StreamType};
+
+            let symbols: Vec<String> = ["BTCUSDT", "btcusdt", " BTCUSDT", "ethusdt"].iter().map(|s| s.to_string()).collect();
+            assert_eq!(normalize_symbols(&symbols), vec!["BTCUSDT", "ETHUSDT"]);
+
+            let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(symbols, sender)
+                .with_stream_base_url("wss://example.test".to_string())
+                .with_stream_types(vec![StreamType::Ticker, StreamType::BookTicker, StreamType::Ticker]);
+            assert_eq!(
+                client.build_stream_url().unwrap(),
+                "wss://example.test/stream?streams=btcusdt@ticker/btcusdt@bookTicker/ethusdt@ticker/ethusdt@bookTicker"
+            );
+
+            let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+            let single = BinanceClient::new(vec!["btcusdt".to_string(), "BTCUSDT".to_string()], sender)
+                .with_stream_base_url("wss://example.test".to_string());
+            assert_eq!(single.build_stream_url().unwrap(), "wss://example.test/ws/btcusdt@ticker");
+
+            let (sender, _receiver) = tokio::sync::mpsc::unbounded_channel();
+            assert!(BinanceClient::new(Vec::new(), sender).build_stream_url().is_err());
+            assert!(MarketDataStream::new(vec![" ".to_string()]).await.is_err());
+        }
+
+        /// Test that Ticker's Display picks enough decimals for cheap symbols
+        #[test]
+        fn test_ticker_display_precision() {
+            use crate::market_data::display_decimals;
+
+            let ticker = |symbol: &str, price: f64, volume: f64| Ticker {
+                symbol: symbol.to_string(),
+                price,
+                volume,
+                quote_volume: 0.0,
+                timestamp: 1_640_995_200_000,
+            };
+
+            assert_eq!(ticker("BTCUSDT", 45000.123, 1234.5678).to_string(), "BTCUSDT | Price: 45000.12 | Volume: 1234.57");
+            assert_eq!(ticker("SHIBUSDT", 0.00001234, 5.0e9).to_string(), "SHIBUSDT | Price: 0.00001234 | Volume: 5000000000.00");
+            assert_eq!(ticker("XRPUSDT", 0.61234, 0.0).to_string(), "XRPUSDT | Price: 0.61234 | Volume: 0.00");
+            assert_eq!(ticker("BTCUSDT", 45000.123, 1.5).format(0, 3), "BTCUSDT | Price: 45000 | Volume: 1.500");
+
+            assert_eq!(display_decimals(1.23456), 4);
+            assert_eq!(display_decimals(1e-12), 8);
+            assert_eq!(display_decimals(f64::NAN), 2);
+        }
+
+        /// Test that a healthy REST ping skips the first backoff, and a failing one is reported as down
+        #[tokio::test]
+        async fn test_reconnect_rest_probe() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::{ReconnectConfig, RestProbe};
+            use crate::trading::TestnetTrader;
+            use std::time::Duration;
+            use wiremock::matchers::{method, path};
+            use wiremock::{Mock, MockServer, ResponseTemplate};
+
+            let rest = MockServer::start().await;
+            Mock::given(method("GET"))
+                .and(path("/api/v3/ping"))
+                .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+                .mount(&rest)
+                .await;
+            let trader = TestnetTrader::new("k".to_string(), "s".to_string()).with_base_url(rest.uri());
+            let probe = RestProbe::new(trader.http_client(), trader.base_url());
+            assert!(probe.is_reachable().await);
+
+            let frame = r#"{"e":"24hrTicker","E":1640995200000,"s":"BTCUSDT","c":"50000.50","v":"1234.5"}"#;
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url(spawn_flaky_ws_server(1, frame).await)
+                .with_reconnect_config(ReconnectConfig {
+                    initial_backoff: Duration::from_secs(60),  // Would time the test out without the probe
+                    min_interval: Duration::from_millis(10),
+                    ..ReconnectConfig::default()
+                })
+                .with_rest_probe(probe);
+            tokio::spawn(async move { client.start().await.is_ok() });
+
+            let ticker = tokio::time::timeout(Duration::from_secs(5), async {
+                loop {
+                    if let Some(MarketDataEvent::Ticker(t)) = receiver.recv().await {
+                        return t;
+                    }
+                }
+            }).await.unwrap();
+            assert_eq!(ticker.symbol, "BTCUSDT");
+
+            // Handshakes that keep failing while REST answers: only the first retry skips the backoff
+            let refused = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url(format!("ws://{}", refused))
+                .with_reconnect_config(ReconnectConfig {
+                    initial_backoff: Duration::from_millis(50),
+                    min_interval: Duration::from_millis(10),
+                    max_retries: Some(3),
+                    ..ReconnectConfig::default()
+                })
+                .with_rest_probe(RestProbe::new(trader.http_client(), trader.base_url()));
+            assert!(client.start().await.is_err());
+            let mut delays = Vec::new();
+            while let Ok(event) = receiver.try_recv() {
+                if let MarketDataEvent::Reconnecting { delay, .. } = event {
+                    delays.push(delay);
+                }
+            }
+            assert_eq!(delays, vec![Duration::from_millis(10), Duration::from_millis(100), Duration::from_millis(200)]);
+
+            // 5xx or nothing listening: the exchange counts as down
+            let down = MockServer::start().await;
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(503))
+                .mount(&down)
+                .await;
+            assert!(!RestProbe::new(reqwest::Client::new(), &down.uri()).is_reachable().await);
+            let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+            let unreachable = RestProbe::new(reqwest::Client::new(), &format!("http://{}", closed))
+                .with_timeout(Duration::from_secs(1));
+            assert!(!unreachable.is_reachable().await);
+        }
+
+        /// Test the windowed frame-rate counter, including dropping to zero when the feed goes quiet
+        #[tokio::test(start_paused = true)]
+        async fn test_throughput_counter() {
+            use crate::market_data::ThroughputCounter;
+            use std::time::Duration;
+
+            let counter = ThroughputCounter::new(Duration::from_secs(2));
+            tokio::time::advance(Duration::from_millis(500)).await;
+            for _ in 0..10 {
+                counter.record();
+            }
+            // First window still running: 10 frames in 0.5s so far
+            assert_eq!(counter.rate(), 20.0);
+
+            tokio::time::advance(Duration::from_millis(1500)).await;
+            counter.record();
+            // That frame closed the 2s window (10 frames) and opened a new one
+            assert_eq!(counter.rate(), 5.0);
+
+            for _ in 0..7 {
+                counter.record();
+            }
+            tokio::time::advance(Duration::from_secs(2)).await;
+            assert_eq!(counter.rate(), 4.0);
+
+            // Two whole windows with nothing
+            tokio::time::advance(Duration::from_secs(5)).await;
+            assert_eq!(counter.rate(), 0.0);
+        }
+
+        /// Test that MarketDataStream::throughput counts the frames coming in
+        #[tokio::test]
+        async fn test_stream_throughput() {
+            use crate::market_data::MarketDataStream;
+            use std::time::Duration;
+
+            let frame = r#"{"e":"24hrTicker","E":1640995200000,"s":"BTCUSDT","c":"50000.50","v":"1234.5"}"#;
+            let url = spawn_flaky_ws_server(0, frame).await;
+            let mut stream = MarketDataStream::new_with_client(vec!["BTCUSDT".to_string()], move |c| {
+                c.with_stream_base_url(url)
+            }).await.unwrap();
+
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while !matches!(stream.next_event().await, Some(MarketDataEvent::Ticker(_))) {}
+            }).await.unwrap();
+            assert!(stream.throughput() > 0.0);
+            stream.shutdown().await;
+        }
+
+        /// Test that a handshake that never completes times out and counts as a failed attempt
+        #[tokio::test]
+        async fn test_connect_timeout() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::ReconnectConfig;
+            use std::time::Duration;
+
+            // Accepts TCP connections but never answers the WebSocket handshake
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("ws://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                let mut held = Vec::new();
+                while let Ok((tcp, _)) = listener.accept().await {
+                    held.push(tcp);
+                }
+            });
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url(url)
+                .with_connect_timeout(Duration::from_millis(200))
+                .with_reconnect_config(ReconnectConfig {
+                    initial_backoff: Duration::from_millis(10),
+                    min_interval: Duration::from_millis(10),
+                    max_retries: Some(1),
+                    ..ReconnectConfig::default()
+                });
+
+            let result = tokio::time::timeout(Duration::from_secs(5), client.start()).await.unwrap();
+            let err = result.unwrap_err().to_string();
+            assert!(err.contains("Timed out connecting"), "{}", err);
+
+            // Both attempts were reported
+            let mut timeouts = 0;
+            while let Ok(event) = receiver.try_recv() {
+                if matches!(event, MarketDataEvent::Error(ref e) if e.contains("Timed out")) {
+                    timeouts += 1;
+                }
+            }
+            assert_eq!(timeouts, 2);
+        }
+
+        /// Test that every reconnect attempt is reported, and that connections which drop
+        /// right after coming up keep backing off instead of starting over
+        #[tokio::test]
+        async fn test_reconnect_events_and_backoff_reset() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::{MarketDataEvent, ReconnectConfig};
+            use std::time::Duration;
+
+            let frame = r#"{"e":"24hrTicker","E":1640995200000,"s":"BTCUSDT","c":"50000.50","v":"1234.5"}"#;
+            let delays_until_ticker = |reset_after: Duration| async move {
+                // The first three connections are closed right after the handshake
+                let url = spawn_flaky_ws_server(3, frame).await;
+                let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+                let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                    .with_stream_base_url(url)
+                    .with_reconnect_config(ReconnectConfig {
+                        initial_backoff: Duration::from_millis(10),
+                        min_interval: Duration::ZERO,
+                        reset_after,
+                        ..ReconnectConfig::default()
+                    });
+                tokio::spawn(async move { client.start().await.is_ok() });
+
+                let mut delays = Vec::new();
+                tokio::time::timeout(Duration::from_secs(5), async {
+                    loop {
+                        match receiver.recv().await.unwrap() {
+                            MarketDataEvent::Reconnecting { attempt, delay } => {
+                                assert_eq!(attempt, 1);  // Each drop follows a successful connect
+                                delays.push(delay.as_millis());
+                            }
+                            MarketDataEvent::Ticker(_) => break,
+                            _ => {}
+                        }
+                    }
+                }).await.unwrap();
+                delays
+            };
+
+            assert_eq!(delays_until_ticker(Duration::from_secs(30)).await, vec![10, 20, 40]);
+            assert_eq!(delays_until_ticker(Duration::ZERO).await, vec![10, 10, 10]);
+        }
+
+        /// Test per-symbol depth streams: stream names in the URL, parsed and sorted levels
+        #[test]
+        fn test_depth_stream() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::{DepthLevels, OrderBookLevel, StreamType};
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()], sender)
+                .with_stream_base_url("wss://example.test".to_string())
+                .with_symbol_stream_types("btcusdt", vec![StreamType::PartialDepth(DepthLevels::Twenty)])
+                .with_symbol_stream_types("ETHUSDT", vec![StreamType::Depth, StreamType::Ticker]);
+            assert_eq!(
+                client.build_stream_url().unwrap(),
+                "wss://example.test/stream?streams=btcusdt@depth20@100ms/ethusdt@depth/ethusdt@ticker"
+            );
+
+            // Partial depth: no symbol in the payload, so it comes from the stream name
+            client.handle_message(r#"{"stream":"btcusdt@depth20@100ms","data":{"lastUpdateId":160,
+                "bids":[["49999.00","2.0"],["50000.00","1.5"]],"asks":[["50002.00","3"],["50001.00","0.25"]]}}"#).unwrap();
+            let MarketDataEvent::OrderBook(book) = receiver.try_recv().unwrap() else { panic!("expected a book") };
+            assert_eq!(book.symbol, "BTCUSDT");
+            assert_eq!(book.bids, vec![
+                OrderBookLevel { price: 50000.0, quantity: 1.5 },
+                OrderBookLevel { price: 49999.0, quantity: 2.0 },
+            ]);
+            assert_eq!(book.asks, vec![
+                OrderBookLevel { price: 50001.0, quantity: 0.25 },
+                OrderBookLevel { price: 50002.0, quantity: 3.0 },
+            ]);
+
+            // Diff depth is a separate event with its update ids; quantity 0 removes a level
+            client.handle_message(r#"{"stream":"ethusdt@depth","data":{"e":"depthUpdate","E":1700000000000,"s":"ETHUSDT",
+                "U":157,"u":160,"b":[["2999.5","1"],["3000.0","0.00000000"]],"a":[["3001.0","1"]]}}"#).unwrap();
+            let MarketDataEvent::DepthUpdate(update) = receiver.try_recv().unwrap() else { panic!("expected a depth update") };
+            assert_eq!((update.symbol.as_str(), update.timestamp), ("ETHUSDT", 1700000000000));
+            assert_eq!((update.first_update_id, update.final_update_id), (157, 160));
+            assert_eq!(update.bids[0], OrderBookLevel { price: 3000.0, quantity: 0.0 });
+
+            // A malformed price is an error, not a zero
+            assert!(client.handle_message(r#"{"stream":"btcusdt@depth20@100ms","data":{"lastUpdateId":1,"bids":[["abc","1"]],"asks":[]}}"#).is_err());
+        }
+
+        /// Test trade streams: subscribed by name, `m` mapped to the aggressor side
+        #[test]
+        fn test_trade_stream() {
+            use crate::market_data::binance::BinanceClient;
+            use crate::market_data::{StreamType, Trade};
+
+            let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            let client = BinanceClient::new(vec!["BTCUSDT".to_string()], sender)
+                .with_stream_base_url("wss://example.test".to_string())
+                .with_stream_types(vec![StreamType::AggTrade, StreamType::Trade]);
+            assert_eq!(client.build_stream_url().unwrap(), "wss://example.test/stream?streams=btcusdt@aggTrade/btcusdt@trade");
+
+            // Buyer was maker: a seller hit the bid
+            client.handle_message(r#"{"e":"aggTrade","E":1700000000001,"s":"BTCUSDT","a":26129,"p":"50000.10","q":"0.125",
+                "f":100,"l":105,"T":1700000000000,"m":true,"M":true}"#).unwrap();
+            // Buyer was taker, on the raw trade stream
+            client.handle_message(r#"{"stream":"btcusdt@trade","data":{"e":"trade","E":1700000000003,"s":"BTCUSDT","t":12345,
+                "p":"50000.20","q":"2","T":1700000000002,"m":false,"M":true}}"#).unwrap();
+
+            assert_eq!(receiver.try_recv().unwrap(), MarketDataEvent::Trade(Trade {
+                symbol: "BTCUSDT".to_string(),
+                price: 50000.10,
+                quantity: 0.125,
+                side: TradeSide::Sell,
+                timestamp: 1700000000000,
+            }));
+            assert!(matches!(receiver.try_recv().unwrap(), MarketDataEvent::Trade(t) if t.side == TradeSide::Buy && t.quantity == 2.0));
+
+            // Missing price is an error
+            assert!(client.handle_message(r#"{"e":"aggTrade","s":"BTCUSDT","q":"1","T":1,"m":true}"#).is_err());
+        }
+    }
+
+    /// Tests for strategies and the strategy runner
+    /// These use the mock data source and a dry-run trader, so nothing touches the network
+    mod strategy_tests {
+        use crate::market_data::{MockMarketDataSource, Ticker};
+        use crate::strategies::{Signal, Strategy, StrategyRunner};
+        use crate::trading::TestnetTrader;
+
+        /// Simple test strategy: buy above `upper` when flat, sell below `lower` when long
+        struct ThresholdStrategy {
+            upper: f64,
+            lower: f64,
+            long: bool,
+        }
+
+        impl Strategy for ThresholdStrategy {
+            fn name(&self) -> &str {
+                "threshold"
+            }
+
+            fn on_ticker(&mut self, ticker: &Ticker) -> Signal {
+                if !self.long && ticker.price > self.upper {
+                    self.long = true;
+                    Signal::Buy
+                } else if self.long && ticker.price < self.lower {
+                    self.long = false;
+                    Signal::Sell
+                } else {
+                    Signal::Hold
+                }
+            }
+        }
+
+        fn threshold_strategy() -> Box<dyn Strategy> {
+            Box::new(ThresholdStrategy { upper: 110.0, lower: 100.0, long: false })
+        }
+
+        /// Test that the runner feeds every ticker to the strategy and turns
+        /// Buy/Sell signals into orders on a dry-run trader
+        #[tokio::test]
+        async fn test_runner_executes_signals_dry_run() {
+            let source = MockMarketDataSource::from_prices("BTCUSDT", &[100.0, 105.0, 111.0, 108.0, 95.0]);
+            let trader = TestnetTrader::new("key".to_string(), "secret".to_string()).with_dry_run(true);
+
+            let mut runner = StrategyRunner::new(source, threshold_strategy(), trader)
+                .with_position_size(0.01);
+            let stats = runner.run().await;
+
+            assert_eq!(stats.tickers_processed, 5);
+            assert_eq!(stats.signals, 2);        // Buy at 111, Sell at 95
+            assert_eq!(stats.orders_placed, 2);
+            assert_eq!(stats.order_errors, 0);
+        }
+
+        /// Test that failing orders are counted but don't stop the loop
+        #[tokio::test]
+        async fn test_runner_survives_order_errors() {
+            let source = MockMarketDataSource::from_prices("BTCUSDT", &[111.0, 95.0]);
+            // Nothing listens on port 1, so every order fails with a connection error
+            let trader = TestnetTrader::new("key".to_string(), "secret".to_string())
+                .with_base_url("http://127.0.0.1:1".to_string());
+
+            let mut runner = StrategyRunner::new(source, threshold_strategy(), trader);
+            let stats = runner.run().await;
+
+            assert_eq!(stats.tickers_processed, 2);
+            assert_eq!(stats.orders_placed, 0);
+            assert_eq!(stats.order_errors, 2);
+        }
+
+        /// Test a series that makes a new high and then a new low
+        #[test]
+        fn test_breakout_signals_new_high_then_new_low() {
+            use crate::strategies::BreakoutStrategy;
+
+            let mut strategy = BreakoutStrategy::new(3);
+            let closes = [100.0, 102.0, 101.0, 103.0, 102.5, 101.0, 99.0];
+            let signals: Vec<Signal> = closes.iter().map(|&c| strategy.on_close(c)).collect();
+
+            assert_eq!(signals, vec![
+                Signal::Hold,  // Window filling up
+                Signal::Hold,
+                Signal::Hold,
+                Signal::Buy,   // 103 > highest of [100, 102, 101]
+                Signal::Hold,
+                Signal::Hold,
+                Signal::Sell,  // 99 < lowest of [103, 102.5, 101]
+            ]);
+        }
+
+        /// Test that the confirmation margin filters out marginal breakouts
+        #[test]
+        fn test_breakout_confirmation_margin() {
+            use crate::strategies::BreakoutStrategy;
+
+            let mut strategy = BreakoutStrategy::new(2).with_confirmation_pct(1.0);
+            strategy.on_close(100.0);
+            strategy.on_close(100.0);
+
+            // 100.5 beats the high by only 0.5%, not enough
+            assert_eq!(strategy.on_close(100.5), Signal::Hold);
+            // 102 beats the new high (100.5) by more than 1%
+            assert_eq!(strategy.on_close(102.0), Signal::Buy);
+        }
+
+        /// Test the market maker's inventory skew at flat, long, short and limit positions
+        #[test]
+        fn test_market_maker_inventory_skew() {
+            use crate::strategies::MarketMaker;
+            use crate::trading::OrderSide;
+
+            // Book 99/101 -> mid 100, quotes 1.0 either side, max inventory 10
+            let mm = MarketMaker::new(1.0, 2.0, 10.0);
+            let price_of = |quotes: &[crate::orders::Quote], side: OrderSide| {
+                quotes.iter().find(|q| q.side == side).map(|q| q.price)
+            };
+
+            // Flat: symmetric around mid
+            let flat = mm.quotes(99.0, 101.0, 0.0);
+            assert_eq!(price_of(&flat, OrderSide::Buy), Some(99.0));
+            assert_eq!(price_of(&flat, OrderSide::Sell), Some(101.0));
+
+            // Half long: both quotes shift down by half the half-spread
+            let long = mm.quotes(99.0, 101.0, 5.0);
+            assert_eq!(price_of(&long, OrderSide::Buy), Some(98.5));
+            assert_eq!(price_of(&long, OrderSide::Sell), Some(100.5));
+
+            // Half short: both shift up
+            let short = mm.quotes(99.0, 101.0, -5.0);
+            assert_eq!(price_of(&short, OrderSide::Buy), Some(99.5));
+            assert_eq!(price_of(&short, OrderSide::Sell), Some(101.5));
+
+            // Near the limit the buy size shrinks, at the limit it disappears
+            let near = mm.quotes(99.0, 101.0, 9.0);
+            assert_eq!(near.iter().find(|q| q.side == OrderSide::Buy).unwrap().quantity, 1.0);
+            let full = mm.quotes(99.0, 101.0, 10.0);
+            assert_eq!(price_of(&full, OrderSide::Buy), None);
+            assert_eq!(price_of(&full, OrderSide::Sell), Some(100.0));  // Max skew: a full half-spread
+
+            // Inventory beyond the limit doesn't skew any further
+            assert_eq!(mm.skew(25.0), mm.skew(10.0));
+        }
+
+        /// Test that the position mode turns repeated same-direction signals into one order
+        #[tokio::test]
+        async fn test_runner_position_mode_dedups_signals() {
+            use crate::strategies::PositionMode;
+
+            /// Replays a fixed list of signals, one per ticker
+            struct Scripted(Vec<Signal>);
+
+            impl Strategy for Scripted {
+                fn name(&self) -> &str {
+                    "scripted"
+                }
+
+                fn on_ticker(&mut self, _ticker: &Ticker) -> Signal {
+                    self.0.remove(0)
+                }
+            }
+
+            let run = |signals: Vec<Signal>, mode: PositionMode| async move {
+                let prices = vec![100.0; signals.len()];
+                let source = MockMarketDataSource::from_prices("BTCUSDT", &prices);
+                let trader = TestnetTrader::new("key".to_string(), "secret".to_string()).with_dry_run(true);
+                let mut runner = StrategyRunner::new(source, Box::new(Scripted(signals)), trader)
+                    .with_position_size(0.01)
+                    .with_position_mode(mode);
+                let stats = runner.run().await;
+                (stats, runner.position("BTCUSDT"))
+            };
+
+            // MA-crossover style: Buy keeps firing while we're long
+            let (stats, position) = run(vec![Signal::Buy; 4], PositionMode::LongOnly).await;
+            assert_eq!((stats.signals, stats.orders_placed, stats.signals_suppressed), (4, 1, 3));
+            assert_eq!(position, 0.01);
+
+            // Long-only can't sell what it doesn't hold
+            let (stats, _) = run(vec![Signal::Sell, Signal::Buy, Signal::Sell, Signal::Sell], PositionMode::LongOnly).await;
+            assert_eq!(stats.orders_placed, 2);
+
+            // Long/short: short once, cover, go long once
+            let signals = vec![Signal::Sell, Signal::Sell, Signal::Buy, Signal::Buy, Signal::Buy];
+            let (stats, position) = run(signals, PositionMode::LongShort).await;
+            assert_eq!(stats.orders_placed, 3);
+            assert_eq!(position, 0.01);
+
+            // Reduce-only never opens anything
+            let (stats, position) = run(vec![Signal::Buy, Signal::Sell], PositionMode::Flat).await;
+            assert_eq!((stats.orders_placed, position), (0, 0.0));
+        }
+
+        /// Test that rapid alternating signals inside the debounce window trade at most once
+        #[tokio::test]
+        async fn test_runner_signal_debounce_prevents_whipsaw() {
+            use crate::strategies::SignalDebounce;
+            use std::time::Duration;
+
+            struct Scripted(Vec<Signal>);
+
+            impl Strategy for Scripted {
+                fn name(&self) -> &str {
+                    "scripted"
+                }
+
+                fn on_ticker(&mut self, _ticker: &Ticker) -> Signal {
+                    self.0.remove(0)
+                }
+            }
+
+            let run = |signals: Vec<Signal>, prices: Vec<f64>, debounce: SignalDebounce| async move {
+                // Tickers are one second apart
+                let source = MockMarketDataSource::from_prices("BTCUSDT", &prices);
+                let trader = TestnetTrader::new("key".to_string(), "secret".to_string()).with_dry_run(true);
+                let mut runner = StrategyRunner::new(source, Box::new(Scripted(signals)), trader)
+                    .with_signal_debounce(debounce);
+                runner.run().await
+            };
+
+            // Hovering at the crossover for 6 seconds with a 10 second debounce
+            let flips = vec![Signal::Buy, Signal::Sell, Signal::Buy, Signal::Sell, Signal::Buy, Signal::Sell];
+            let stats = run(flips.clone(), vec![100.0; 6], SignalDebounce::new().with_min_interval(Duration::from_secs(10))).await;
+            assert_eq!((stats.signals, stats.orders_placed, stats.signals_debounced), (6, 1, 5));
+
+            // No thresholds configured: every flip trades
+            let stats = run(flips, vec![100.0; 6], SignalDebounce::new()).await;
+            assert_eq!((stats.orders_placed, stats.signals_debounced), (6, 0));
+
+            // A 1% move lets the reversal through; smaller wiggles don't
+            let signals = vec![Signal::Buy, Signal::Sell, Signal::Sell, Signal::Sell, Signal::Buy];
+            let prices = vec![100.0, 100.2, 99.9, 102.0, 101.5];
+            let stats = run(signals, prices, SignalDebounce::new().with_min_price_move(0.01)).await;
+            assert_eq!((stats.orders_placed, stats.signals_debounced), (2, 3));
+        }
+    }
+
+    /// Tests for step-size rounding helpers
+    /// Binance rejects quantities that aren't on the LOT_SIZE grid, so these must be exact
+    mod precision_tests {
+        use crate::trading::precision::{format_quantity, round_to_step, step_decimals};
+        use crate::trading::RoundingMode;
+
+        /// Test decimal counting for common step sizes
+        #[test]
+        fn test_step_decimals() {
+            assert_eq!(step_decimals(0.00001), 5);
+            assert_eq!(step_decimals(0.001), 3);
+            assert_eq!(step_decimals(0.5), 1);
+            assert_eq!(step_decimals(1.0), 0);
+            assert_eq!(step_decimals(10.0), 0);
+        }
+
+        /// Test each rounding mode against a tiny step size
+        #[test]
+        fn test_rounding_modes_small_step() {
+            let step = 0.00001;
+            assert_eq!(round_to_step(0.123456789, step, RoundingMode::Down), 0.12345);
+            assert_eq!(round_to_step(0.123456789, step, RoundingMode::Up), 0.12346);
+            assert_eq!(round_to_step(0.123456789, step, RoundingMode::Nearest), 0.12346);
+            assert_eq!(round_to_step(0.123451, step, RoundingMode::Nearest), 0.12345);
+        }
+
+        /// Test that values already on the grid are left alone by every mode
+        /// (0.3 / 0.1 is 2.9999999999999996 in f64, a naive floor would give 0.2)
+        #[test]
+        fn test_on_grid_values_are_stable() {
+            for mode in [RoundingMode::Down, RoundingMode::Up, RoundingMode::Nearest] {
+                assert_eq!(round_to_step(0.3, 0.1, mode), 0.3);
+                assert_eq!(round_to_step(0.00003, 0.00001, mode), 0.00003);
+                assert_eq!(round_to_step(1.5, 0.5, mode), 1.5);
+            }
+        }
+
+        /// Test that Down never exceeds and Up never falls below the input
+        #[test]
+        fn test_down_and_up_bounds() {
+            let values = [0.123456, 1.000019, 0.000011, 42.424242];
+            for v in values {
+                assert!(round_to_step(v, 0.00001, RoundingMode::Down) <= v);
+                assert!(round_to_step(v, 0.00001, RoundingMode::Up) >= v);
+            }
+        }
+
+        /// Test that formatted quantities use exactly the step's decimals
+        #[test]
+        fn test_format_quantity() {
+            assert_eq!(format_quantity(0.123456789, 0.00001, RoundingMode::Down), "0.12345");
+            assert_eq!(format_quantity(2.0, 0.001, RoundingMode::Nearest), "2.000");
+            assert_eq!(format_quantity(7.7, 1.0, RoundingMode::Down), "7");
+        }
+
+        /// Test that SHIB-sized values parsed from scientific notation survive formatting
+        #[test]
+        fn test_format_decimal_tiny_values() {
+            use crate::trading::precision::format_decimal;
+            use crate::trading::Balance;
+
+            // Binance sometimes sends tiny values like this
+            let balance: Balance = serde_json::from_str(r#"{"asset": "SHIB", "free": "1.5E-7", "locked": "1.234E-7"}"#).unwrap();
+            assert_eq!(format_decimal(balance.free, 8), "0.00000015");
+            // Never more than the 8 decimals Binance accepts
+            assert_eq!(format_decimal(balance.locked, 8), "0.00000012");
+            assert_eq!(format_decimal(1.5e-9, 2), "0.00");
+            assert_eq!(format_decimal(0.5, 12), "0.50000000");
+
+            // Ordinary values keep their usual fixed width
+            assert_eq!(format_decimal(0.001, 8), "0.00100000");
+            assert_eq!(format_decimal(45000.5, 2), "45000.50");
+            assert_eq!(format_decimal(0.0, 2), "0.00");
+        }
+
+        /// Test step snapping on exact decimals, where f64 would drift
+        #[test]
+        fn test_format_decimal_to_step() {
+            use crate::trading::precision::{format_decimal_to_step, format_exact_decimal, Amount};
+            use rust_decimal::Decimal;
+            use std::str::FromStr;
+
+            let d = |s: &str| Decimal::from_str(s).unwrap();
+            // 0.3 / 0.1 is exactly 3 steps here
+            assert_eq!(format_decimal_to_step(d("0.3"), d("0.1"), RoundingMode::Down), "0.3");
+            assert_eq!(format_decimal_to_step(d("0.123456"), d("0.00001"), RoundingMode::Down), "0.12345");
+            assert_eq!(format_decimal_to_step(d("0.123451"), d("0.00001"), RoundingMode::Up), "0.12346");
+            assert_eq!(format_decimal_to_step(d("45000.55"), d("0.1"), RoundingMode::Nearest), "45000.6");
+            assert_eq!(format_decimal_to_step(d("12"), d("1.00000000"), RoundingMode::Down), "12");
+
+            assert_eq!(format_exact_decimal(d("45000.1"), 2), "45000.10");
+            assert_eq!(format_exact_decimal(d("0.000000001500"), 8), "0.0000000015");
+
+            // f64 inputs convert to their shortest decimal form
+            assert_eq!(Amount::from(0.1).decimal(), d("0.1"));
+        }
+
+        /// Test snapping prices to the tick grid, including a non-power-of-ten tick
+        #[test]
+        fn test_format_price_to_tick() {
+            use crate::trading::precision::format_price_to_tick;
+
+            assert_eq!(format_price_to_tick(45123.456, 0.01), "45123.46");
+            assert_eq!(format_price_to_tick(45123.4, 0.01), "45123.40");
+            assert_eq!(format_price_to_tick(0.123456, 0.0001), "0.1235");
+            assert_eq!(format_price_to_tick(0.12344, 0.0001), "0.1234");
+            // 0.5 ticks: snap to the nearest half
+            assert_eq!(format_price_to_tick(100.3, 0.5), "100.5");
+            assert_eq!(format_price_to_tick(100.2, 0.5), "100.0");
+            assert_eq!(format_price_to_tick(100.75, 0.5), "101.0");
+            // Whole-number ticks have no decimals
+            assert_eq!(format_price_to_tick(1234.6, 1.0), "1235");
+        }
+    }
+
+    /// Tests for the Portfolio position tracking
+    mod portfolio_tests {
+        use crate::portfolio::Portfolio;
+        use crate::trading::{OrderId, OrderResponse, OrderStatus};
+
+        /// Helper to build an order with the given fill state
+        fn order(order_id: u64, side: &str, status: OrderStatus, executed: f64, quote: f64) -> OrderResponse {
+            OrderResponse {
+                symbol: "BTCUSDT".to_string(),
+                order_id: OrderId(order_id),
+                order_list_id: -1,
+                client_order_id: format!("test_{}", order_id),
+                transact_time: Some(1640995200000),
+                price: "0.00000000".to_string(),
+                orig_qty: "1.00000000".to_string(),
+                executed_qty: format!("{:.8}", executed),
+                cummulative_quote_qty: format!("{:.8}", quote),
+                status,
+                time_in_force: "GTC".to_string(),
+                order_type: "LIMIT".to_string(),
+                side: side.to_string(),
+                time: None,
+                rejection: None,
+            }
+        }
+
+        /// Test an order that fills in two chunks, polled three times:
+        /// the position must reflect the total once, not double-count
+        #[test]
+        fn test_apply_fill_partial_then_full() {
+            let mut portfolio = Portfolio::new();
+
+            // First chunk: 0.4 @ 100
+            let applied = portfolio.apply_fill(&order(1, "BUY", OrderStatus::PartiallyFilled, 0.4, 40.0)).unwrap();
+            assert_eq!(applied, 0.4);
+
+            // Same state polled again - nothing new
+            let applied = portfolio.apply_fill(&order(1, "BUY", OrderStatus::PartiallyFilled, 0.4, 40.0)).unwrap();
+            assert_eq!(applied, 0.0);
+
+            // Second chunk: 0.6 @ 110 (cumulative quote 40 + 66)
+            let applied = portfolio.apply_fill(&order(1, "BUY", OrderStatus::Filled, 1.0, 106.0)).unwrap();
+            assert!((applied - 0.6).abs() < 1e-9);
+
+            let position = portfolio.position("BTCUSDT").unwrap();
+            assert!((position.quantity - 1.0).abs() < 1e-9);
+            assert!((position.average_price - 106.0).abs() < 1e-9);
+        }
+
+        /// Test that a partial sell realizes P&L against the average cost
+        #[test]
+        fn test_apply_fill_sell_realizes_pnl() {
+            let mut portfolio = Portfolio::new();
+            portfolio.apply_fill(&order(1, "BUY", OrderStatus::Filled, 2.0, 200.0)).unwrap();
+            portfolio.apply_fill(&order(2, "SELL", OrderStatus::PartiallyFilled, 0.5, 60.0)).unwrap();
+
+            let position = portfolio.position("BTCUSDT").unwrap();
+            assert!((position.quantity - 1.5).abs() < 1e-9);
+            assert!((position.realized_pnl - 10.0).abs() < 1e-9);  // 0.5 * (120 - 100)
+        }
+
+        /// Helper to build a myTrades entry for BTCUSDT
+        fn account_trade(id: u64, is_buyer: bool, price: f64, qty: f64, commission: f64, asset: &str) -> crate::trading::AccountTrade {
+            crate::trading::AccountTrade {
+                symbol: "BTCUSDT".to_string(),
+                id,
+                order_id: OrderId(id),
+                price,
+                qty,
+                quote_qty: price * qty,
+                commission,
+                commission_asset: asset.to_string(),
+                time: 1640995200000,
+                is_buyer,
+                is_maker: false,
+            }
+        }
+
+        /// Test the fee-inclusive P&L report with fees paid in BNB and in the quote asset
+        #[test]
+        fn test_realized_pnl_report_with_mixed_fee_assets() {
+            let mut portfolio = Portfolio::new();
+
+            // Fee in BNB can't be converted until we know BNB's price
+            let buy = account_trade(1, true, 100.0, 1.0, 0.01, "BNB");
+            assert!(portfolio.apply_account_trade(&buy).is_err());
+            assert!(portfolio.position("BTCUSDT").is_none());
+
+            portfolio.set_conversion_rate("BNB", 300.0);
+            assert!((portfolio.apply_account_trade(&buy).unwrap() - 3.0).abs() < 1e-9);
+
+            // Sell fee charged directly in USDT
+            let sell = account_trade(2, false, 110.0, 1.0, 0.11, "USDT");
+            assert!((portfolio.apply_account_trade(&sell).unwrap() - 0.11).abs() < 1e-9);
+            // Re-applying the same trade id changes nothing
+            assert_eq!(portfolio.apply_account_trade(&sell).unwrap(), 0.0);
+
+            let report = portfolio.realized_pnl_report();
+            let row = report["BTCUSDT"];
+            assert!((row.gross_pnl - 10.0).abs() < 1e-9);
+            assert!((row.fees - 3.11).abs() < 1e-9);
+            assert!((row.net_pnl - 6.89).abs() < 1e-9);
+        }
+
+        /// Helper to build an executionReport for a BTCUSDT buy order 5
+        fn execution_report(trade_id: i64, status: &str, last_qty: &str, last_price: &str, cumulative: &str) -> crate::trading::ExecutionReport {
+            serde_json::from_value(serde_json::json!({
+                "e": "executionReport", "E": 1640995200000u64, "s": "BTCUSDT", "c": "client_5",
+                "S": "BUY", "o": "LIMIT", "f": "GTC", "q": "1.00000000", "p": "100.00000000",
+                "x": "TRADE", "X": status, "r": "NONE", "i": 5,
+                "l": last_qty, "z": cumulative, "L": last_price,
+                "n": "0.10000000", "N": "USDT", "T": 1640995200000u64, "t": trade_id
+            })).unwrap()
+        }
+
+        /// Test that a sequence of partial-fill execution reports adds up to the full fill
+        #[test]
+        fn test_apply_execution_reports_partial_fills() {
+            let mut portfolio = Portfolio::new();
+
+            let reports = [
+                execution_report(1, "PARTIALLY_FILLED", "0.25000000", "100.00000000", "0.25000000"),
+                execution_report(2, "PARTIALLY_FILLED", "0.25000000", "102.00000000", "0.50000000"),
+                execution_report(3, "FILLED", "0.50000000", "104.00000000", "1.00000000"),
+            ];
+            for report in &reports {
+                portfolio.apply_execution_report(report);
+            }
+            // A redelivered report must not count twice
+            assert_eq!(portfolio.apply_execution_report(&reports[1]), 0.0);
+
+            let position = portfolio.position("BTCUSDT").unwrap();
+            assert!((position.quantity - 1.0).abs() < 1e-9);
+            // (0.25*100 + 0.25*102 + 0.5*104) / 1.0
+            assert!((position.average_price - 102.5).abs() < 1e-9);
+            assert!((position.fees_paid - 0.3).abs() < 1e-9);
+        }
+
+        /// Test that rounded outputs use the tick size precision, or the default without one
+        #[test]
+        fn test_rounded_pnl_report() {
+            let mut portfolio = Portfolio::new();
+            // Buys at 100.1 and 100.2, then a sell at 100.3 - classic float noise territory
+            portfolio.apply_fill(&order(1, "BUY", OrderStatus::Filled, 1.0, 100.1)).unwrap();
+            portfolio.apply_fill(&order(2, "BUY", OrderStatus::Filled, 1.0, 100.2)).unwrap();
+            portfolio.apply_fill(&order(3, "SELL", OrderStatus::Filled, 2.0, 200.6)).unwrap();
+
+            let raw = portfolio.realized_pnl_report()["BTCUSDT"];
+            assert!((raw.gross_pnl - 0.3).abs() < 1e-9);
+
+            // Default: 2 decimals, exact value rather than float noise
+            let rounded = portfolio.rounded_pnl_report()["BTCUSDT"];
+            assert_eq!(rounded.gross_pnl, 0.3);
+            assert_eq!(rounded.net_pnl, 0.3);
+
+            // A 0.1 tick size means 1 decimal
+            portfolio.set_tick_size("BTCUSDT", 0.1);
+            assert_eq!(portfolio.display_decimals("BTCUSDT"), 1);
+            assert_eq!(portfolio.rounded_position("BTCUSDT").unwrap().realized_pnl, 0.3);
+        }
+
+        /// Drawdown on a known curve: 100 -> 120 -> 90 -> 130 -> 104
+        #[test]
+        fn test_drawdown() {
+            use crate::portfolio::{max_drawdown, DrawdownTracker};
+
+            let curve = [100.0, 120.0, 90.0, 130.0, 104.0];
+            // Worst drop is 120 -> 90 = 25%; the later 130 -> 104 is only 20%
+            assert!((max_drawdown(&curve) - 0.25).abs() < 1e-12);
+            assert_eq!(max_drawdown(&[]), 0.0);
+            assert_eq!(max_drawdown(&[1.0, 2.0, 3.0]), 0.0);
+
+            let mut tracker = DrawdownTracker::new();
+            for value in curve {
+                tracker.update(value);
+            }
+            assert_eq!(tracker.peak(), Some(130.0));
+            assert!((tracker.current_drawdown() - 0.20).abs() < 1e-12);
+            assert!((tracker.max_drawdown() - 0.25).abs() < 1e-12);
+
+            // A new high clears the current drawdown but not the max
+            assert_eq!(tracker.update(140.0), 0.0);
+            assert!((tracker.max_drawdown() - 0.25).abs() < 1e-12);
+        }
+
+        /// Test that a position stored under a symbol's old name is found, merged and updated via its new name
+        #[test]
+        fn test_symbol_alias_migrates_positions() {
+            let renamed = |mut o: OrderResponse, symbol: &str| { o.symbol = symbol.to_string(); o };
+
+            let mut portfolio = Portfolio::new();
+            portfolio.set_tick_size("OLDUSDT", 0.001);
+            portfolio.apply_fill(&renamed(order(1, "BUY", OrderStatus::Filled, 2.0, 200.0), "OLDUSDT")).unwrap();
+            portfolio.apply_fill(&renamed(order(2, "BUY", OrderStatus::Filled, 2.0, 240.0), "NEWUSDT")).unwrap();
+
+            portfolio.add_symbol_alias("OLDUSDT", "NEWUSDT");
+
+            // One merged position under the new name, reachable through either name
+            let position = portfolio.position("NEWUSDT").unwrap();
+            assert_eq!(position.symbol, "NEWUSDT");
+            assert_eq!(position.quantity, 4.0);
+            assert_eq!(position.average_price, 110.0);
+            assert_eq!(portfolio.position("OLDUSDT"), Some(position));
+            assert_eq!(portfolio.positions().count(), 1);
+            assert_eq!(portfolio.display_decimals("NEWUSDT"), 3);
+
+            // Fills still reported under the old name land on the new position
+            portfolio.apply_fill(&renamed(order(3, "SELL", OrderStatus::Filled, 1.0, 120.0), "OLDUSDT")).unwrap();
+            let position = portfolio.position("NEWUSDT").unwrap();
+            assert_eq!(position.quantity, 3.0);
+            assert_eq!(position.realized_pnl, 10.0);
+        }
+
+        /// Test buy-buy-sell where FIFO and average cost disagree, from the same fills
+        #[test]
+        fn test_pnl_by_method_fifo_vs_average() {
+            use crate::portfolio::{CostBasisMethod, Lot};
+
+            let mut portfolio = Portfolio::new();
+            portfolio.apply_fill(&order(1, "BUY", OrderStatus::Filled, 1.0, 100.0)).unwrap();
+            portfolio.apply_fill(&order(2, "BUY", OrderStatus::Filled, 1.0, 200.0)).unwrap();
+            portfolio.apply_fill(&order(3, "SELL", OrderStatus::Filled, 1.0, 250.0)).unwrap();
+
+            // Average cost 150 -> +100; FIFO sells the 100 lot -> +150
+            let pnl = portfolio.pnl_by_method();
+            assert_eq!(pnl[&CostBasisMethod::AverageCost], 100.0);
+            assert_eq!(pnl[&CostBasisMethod::Fifo], 150.0);
+            let remaining: Vec<&Lot> = portfolio.open_lots("BTCUSDT").collect();
+            assert_eq!(remaining, vec![&Lot { quantity: 1.0, price: 200.0 }]);
+
+            // Selling the rest brings both to the same total
+            portfolio.apply_fill(&order(4, "SELL", OrderStatus::Filled, 1.0, 180.0)).unwrap();
+            let pnl = portfolio.symbol_pnl_by_method("BTCUSDT");
+            assert_eq!(pnl[&CostBasisMethod::AverageCost], 130.0);
+            assert_eq!(pnl[&CostBasisMethod::Fifo], 130.0);
+            assert_eq!(portfolio.open_lots("BTCUSDT").count(), 0);
+        }
+    }
+
+    mod output_tests {
+        use crate::output::json_line;
+        use crate::trading::Balance;
+
+        /// Test that JSON output lines carry a type tag and the serialized data
+        #[test]
+        fn test_json_line_format() {
+            let balances = vec![Balance { asset: "BTC".to_string(), free: 1.5, locked: 0.0 }];
+            let line = json_line("balances", &balances);
+
+            assert!(!line.contains('\n'));
+            let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(parsed["type"], "balances");
+            assert_eq!(parsed["data"][0]["asset"], "BTC");
+            assert_eq!(parsed["data"][0]["free"], 1.5);
         }
     }
-}
\ No newline at end of file
+}
@@ -1,26 +1,37 @@
-use rust_trading_system::market_data::MarketDataStream;
-use tracing_subscriber;
+use rust_trading_system::market_data::{MarketDataStream, StreamType};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialize logging
     tracing_subscriber::fmt::init();
 
-    // Create market data stream for BTC/USDT
-    let mut stream = MarketDataStream::new(vec!["BTCUSDT".to_string()]).await?;
+    // Create market data stream for BTC/USDT: 24hr ticker plus the aggregated trades
+    let mut stream = MarketDataStream::new_with_streams(vec![
+        ("BTCUSDT".to_string(), StreamType::Ticker),
+        ("BTCUSDT".to_string(), StreamType::AggTrade),
+    ]).await?;
 
     println!("🚀 Starting Rust Trading System");
-    println!("📊 Listening for BTC/USDT price updates...");
+    println!("📊 Listening for BTC/USDT price updates and trades...");
     println!("Press Ctrl+C to stop\n");
 
-    // Process market data events
-    while let Some(event) = stream.next_event().await {
+    // Process market data events until Ctrl+C (or the stream ends on its own)
+    loop {
+        let event = tokio::select! {
+            event = stream.next_event() => event,
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n🛑 Ctrl+C received, closing the connection...");
+                break;
+            }
+        };
+        let Some(event) = event else { break };
+
         match event {
             rust_trading_system::market_data::MarketDataEvent::Ticker(ticker) => {
-                println!(
-                    "💰 {} | Price: ${:.2} | Volume: {:.2} | Time: {}",
-                    ticker.symbol, ticker.price, ticker.volume, ticker.timestamp
-                );
+                println!("💰 {} | Time: {}", ticker, ticker.datetime().format("%H:%M:%S%.3f"));
+            }
+            rust_trading_system::market_data::MarketDataEvent::Trade(trade) => {
+                println!("🔁 {} {:?} {} @ {}", trade.symbol, trade.side, trade.quantity, trade.price);
             }
             rust_trading_system::market_data::MarketDataEvent::Error(err) => {
                 eprintln!("❌ Error: {}", err);
@@ -29,5 +40,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    stream.shutdown().await;
+    println!("👋 Goodbye!");
+
     Ok(())
-}
\ No newline at end of file
+}
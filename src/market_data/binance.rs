@@ -1,12 +1,236 @@
+use super::frames::*;
 use super::types::*;
-use serde_json::Value;
-use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-use tracing::{error, info, warn};
+use super::throughput::ThroughputCounter;
+use crate::error::TradingError;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, watch, Notify};
+use tokio_tungstenite::{connect_async_tls_with_config, Connector};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+use tokio_tungstenite::tungstenite::http::header::{HeaderValue, USER_AGENT};
+use tokio_tungstenite::tungstenite::protocol::{Message, WebSocketConfig};
+use tracing::{debug, error, info, warn};
+
+/// Controls how `BinanceClient` reconnects after the WebSocket drops
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectConfig {
+    pub initial_backoff: Duration,  // Wait before the first reconnect attempt
+    pub max_backoff: Duration,      // Backoff doubles after each failure up to this cap
+    pub max_retries: Option<u32>,   // Give up after this many consecutive failures (None = never)
+    pub min_interval: Duration,     // Absolute floor on the wait before any attempt, even if backoff is 0
+    pub max_total_duration: Option<Duration>,  // Give up if still not reconnected after this long (None = never)
+    pub reset_after: Duration,      // A connection up at least this long resets the backoff to initial_backoff
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_retries: None,
+            min_interval: Duration::from_millis(500),
+            max_total_duration: None,
+            reset_after: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// Don't reconnect at all - the client stops when the first connection ends
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: Some(0),
+            ..Self::default()
+        }
+    }
+}
+
+/// Wait before reconnecting while the REST probe can't reach Binance either
+pub const DEFAULT_EXCHANGE_DOWN_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How long the REST probe waits for `/api/v3/ping`
+pub const DEFAULT_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pings the REST API before each reconnect, to tell a dropped socket from an exchange outage
+///
+/// If the ping answers, Binance is up and only our socket broke, so the first reconnect
+/// after a drop happens right away (after `min_interval`); later ones keep the usual
+/// backoff. If it doesn't, the wait is at least `down_backoff`, since hammering a down
+/// exchange is pointless. Share the trader's
+/// HTTP client (`TestnetTrader::http_client`) to reuse its connection pool.
+#[derive(Debug, Clone)]
+pub struct RestProbe {
+    client: reqwest::Client,
+    ping_url: String,
+    timeout: Duration,
+    down_backoff: Duration,
+}
+
+impl RestProbe {
+    /// Probe `{rest_base_url}/api/v3/ping`, e.g. "https://testnet.binance.vision"
+    pub fn new(client: reqwest::Client, rest_base_url: &str) -> Self {
+        Self {
+            client,
+            ping_url: format!("{}/api/v3/ping", rest_base_url.trim_end_matches('/')),
+            timeout: DEFAULT_PROBE_TIMEOUT,
+            down_backoff: DEFAULT_EXCHANGE_DOWN_BACKOFF,
+        }
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Minimum wait before reconnecting while the ping fails
+    pub fn with_down_backoff(mut self, down_backoff: Duration) -> Self {
+        self.down_backoff = down_backoff;
+        self
+    }
+
+    /// Whether the ping came back with a success status within the timeout
+    pub async fn is_reachable(&self) -> bool {
+        match self.client.get(&self.ping_url).timeout(self.timeout).send().await {
+            Ok(response) => response.status().is_success(),
+            Err(e) => {
+                debug!("REST ping failed: {}", e);
+                false
+            }
+        }
+    }
+}
+
+/// How long the TCP/TLS/WebSocket handshake may take before the attempt counts as failed
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest message we accept by default - `!ticker@arr` frames carry every symbol
+/// and run to several hundred KB, so leave plenty of headroom
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 128 << 20;
+
+/// Largest single frame we accept by default
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 64 << 20;
+
+/// WebSocket limits used unless `with_websocket_config` overrides them
+pub fn default_websocket_config() -> WebSocketConfig {
+    WebSocketConfig {
+        max_message_size: Some(DEFAULT_MAX_MESSAGE_SIZE),
+        max_frame_size: Some(DEFAULT_MAX_FRAME_SIZE),
+        ..WebSocketConfig::default()
+    }
+}
+
+/// TLS connector trusting the given PEM-encoded root certificates on top of the system ones
+/// For networks where the WebSocket goes through a TLS-inspecting proxy with a corporate CA.
+/// Uses native-tls, the same backend reqwest uses for REST
+pub fn tls_connector_with_roots(pem_certs: &[&[u8]]) -> Result<Connector, TradingError> {
+    let mut builder = native_tls::TlsConnector::builder();
+    for pem in pem_certs {
+        builder.add_root_certificate(native_tls::Certificate::from_pem(pem)
+            .map_err(|e| TradingError::WebSocket(format!("Invalid root certificate: {}", e)))?);
+    }
+    Ok(Connector::NativeTls(builder.build()
+        .map_err(|e| TradingError::WebSocket(format!("Failed to build TLS connector: {}", e)))?))
+}
+
+/// Which per-symbol streams `BinanceClient` subscribes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamType {
+    Ticker,      // `<symbol>@ticker`: 24hr rolling stats, about once a second
+    BookTicker,  // `<symbol>@bookTicker`: best bid/ask, on every top-of-book change
+    Trade,       // `<symbol>@trade`: every individual trade
+    AggTrade,    // `<symbol>@aggTrade`: trades aggregated per taker order and price
+    Depth,       // `<symbol>@depth`: order book diffs (`DepthUpdate`), once a second
+    PartialDepth(DepthLevels),  // `<symbol>@depth<levels>@100ms`: the top levels as a full `OrderBook`, every 100ms
+}
+
+/// How many levels per side a partial depth stream sends - Binance only serves these three
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthLevels {
+    Five,
+    Ten,
+    Twenty,
+}
+
+impl DepthLevels {
+    pub fn count(&self) -> usize {
+        match self {
+            DepthLevels::Five => 5,
+            DepthLevels::Ten => 10,
+            DepthLevels::Twenty => 20,
+        }
+    }
+}
+
+impl StreamType {
+    /// Stream name for a symbol, e.g. "btcusdt@bookTicker"
+    pub fn stream_name(&self, symbol: &str) -> String {
+        let suffix = match self {
+            StreamType::Ticker => "ticker".to_string(),
+            StreamType::BookTicker => "bookTicker".to_string(),
+            StreamType::Trade => "trade".to_string(),
+            StreamType::AggTrade => "aggTrade".to_string(),
+            StreamType::Depth => "depth".to_string(),
+            StreamType::PartialDepth(levels) => format!("depth{}@100ms", levels.count()),
+        };
+        format!("{}@{}", symbol.to_lowercase(), suffix)
+    }
+}
+
+/// Canonical form of a symbol list: trimmed, uppercase, each symbol once in first-seen order
+/// `["BTCUSDT", "btcusdt", " BTCUSDT"]` becomes `["BTCUSDT"]`; blank entries are dropped
+pub fn normalize_symbols(symbols: &[String]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let symbol = symbol.trim().to_uppercase();
+        if !symbol.is_empty() && !normalized.contains(&symbol) {
+            normalized.push(symbol);
+        }
+    }
+    normalized
+}
+
+/// Callback invoked after a successful reconnect, with the attempt number that succeeded
+pub type ReconnectHook = Box<dyn Fn(u32) + Send + Sync>;
+
+/// Control requests from `MarketDataStream` to the live connection
+pub(crate) enum StreamCommand {
+    /// Send SUBSCRIBE for these stream names; `reply` gets Binance's answer
+    Subscribe {
+        streams: Vec<String>,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
+}
+
+/// A SUBSCRIBE sent on the socket, waiting for the reply with its id
+type PendingSubscribe = (Vec<String>, oneshot::Sender<Result<(), String>>);
 
 pub struct BinanceClient {
     symbols: Vec<String>,
+    stream_types: Vec<StreamType>,  // Streams opened for every symbol (default: ticker)
+    symbol_stream_types: HashMap<String, Vec<StreamType>>,  // Per-symbol overrides of `stream_types`
     event_sender: mpsc::UnboundedSender<MarketDataEvent>,
+    state: watch::Sender<ConnectionState>,  // Current connection state, readable from other tasks
+    stream_base_url: String,  // WebSocket host (can be changed for testing)
+    user_agent: String,       // Sent with the WebSocket handshake
+    reconnect: ReconnectConfig,
+    websocket_config: WebSocketConfig,  // Frame/message size limits and buffer sizes
+    tls_connector: Option<Connector>,   // None = default TLS settings for wss:// URLs
+    rest_probe: Option<RestProbe>,      // Checked before each reconnect to pick the wait
+    connect_timeout: Duration,          // Upper bound on each connection handshake
+    throughput: Arc<ThroughputCounter>, // Frames received per second, shared with the stream
+    on_reconnect: Option<ReconnectHook>,
+    connected_at: Mutex<Option<Instant>>,  // When the current/last connection came up
+    subscriptions: Mutex<Vec<String>>,  // Streams added with SUBSCRIBE, included again after a reconnect
+    command_sender: mpsc::UnboundedSender<StreamCommand>,
+    command_receiver: tokio::sync::Mutex<mpsc::UnboundedReceiver<StreamCommand>>,
+    next_request_id: AtomicU64,  // Ids for control requests, to match up the replies
+    shutdown: Arc<Notify>,       // Signalled by `MarketDataStream::shutdown`
+    stopped: AtomicBool,         // Set once shutdown was requested, so we don't reconnect
 }
 
 impl BinanceClient {
@@ -14,89 +238,457 @@ impl BinanceClient {
         symbols: Vec<String>,
         event_sender: mpsc::UnboundedSender<MarketDataEvent>,
     ) -> Self {
+        let (state, _) = watch::channel(ConnectionState::Disconnected);
+        let (command_sender, command_receiver) = mpsc::unbounded_channel();
         Self {
-            symbols,
+            symbols: normalize_symbols(&symbols),
+            stream_types: vec![StreamType::Ticker],
+            symbol_stream_types: HashMap::new(),
             event_sender,
+            state,
+            stream_base_url: "wss://stream.testnet.binance.vision".to_string(),
+            user_agent: crate::DEFAULT_USER_AGENT.to_string(),
+            reconnect: ReconnectConfig::default(),
+            websocket_config: default_websocket_config(),
+            tls_connector: None,
+            rest_probe: None,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            throughput: Arc::new(ThroughputCounter::default()),
+            on_reconnect: None,
+            connected_at: Mutex::new(None),
+            subscriptions: Mutex::new(Vec::new()),
+            command_sender,
+            command_receiver: tokio::sync::Mutex::new(command_receiver),
+            next_request_id: AtomicU64::new(1),
+            shutdown: Arc::new(Notify::new()),
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    /// Choose which streams to open for each symbol, e.g. `[StreamType::BookTicker]`
+    /// for just top-of-book across many symbols
+    pub fn with_stream_types(mut self, stream_types: Vec<StreamType>) -> Self {
+        self.stream_types = stream_types;
+        self
+    }
+
+    /// Open these streams for `symbol` instead of the ones set with `with_stream_types`,
+    /// e.g. `[StreamType::PartialDepth(DepthLevels::Twenty)]` for the one symbol that needs a book
+    pub fn with_symbol_stream_types(mut self, symbol: &str, stream_types: Vec<StreamType>) -> Self {
+        self.symbol_stream_types.insert(symbol.trim().to_uppercase(), stream_types);
+        self
+    }
+
+    /// Streams opened for `symbol`
+    pub fn stream_types_for(&self, symbol: &str) -> &[StreamType] {
+        self.symbol_stream_types.get(symbol).unwrap_or(&self.stream_types)
+    }
+
+    pub fn with_reconnect_config(mut self, reconnect: ReconnectConfig) -> Self {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Override the WebSocket limits (max frame/message size, write buffer sizes)
+    /// Messages over `max_message_size` drop the connection with "message too large".
+    /// tungstenite 0.20 has no read buffer setting, so only these limits are configurable
+    pub fn with_websocket_config(mut self, websocket_config: WebSocketConfig) -> Self {
+        self.websocket_config = websocket_config;
+        self
+    }
+
+    /// Set just the max message and frame sizes (None = unlimited)
+    pub fn with_size_limits(mut self, max_message_size: Option<usize>, max_frame_size: Option<usize>) -> Self {
+        self.websocket_config.max_message_size = max_message_size;
+        self.websocket_config.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// Use a custom TLS setup for the handshake, e.g. from `tls_connector_with_roots`
+    /// Applies to every (re)connect; ignored for plain ws:// URLs
+    pub fn with_tls_connector(mut self, connector: Connector) -> Self {
+        self.tls_connector = Some(connector);
+        self
+    }
+
+    /// Give up on a handshake that hasn't completed after `timeout` (default 10s)
+    /// A timed-out attempt is a failed attempt: it's reported as an `Error` event and
+    /// the reconnect policy decides what happens next
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
+
+    /// Ping the REST API before reconnecting and wait according to the answer (see `RestProbe`)
+    pub fn with_rest_probe(mut self, probe: RestProbe) -> Self {
+        self.rest_probe = Some(probe);
+        self
+    }
+
+    /// Register a callback to run after each successful reconnect (e.g. to
+    /// re-sync local state or send an alert). It receives the attempt number.
+    pub fn on_reconnect(mut self, hook: ReconnectHook) -> Self {
+        self.on_reconnect = Some(hook);
+        self
+    }
+
+    /// Override the default `rust-trading-system/<version>` User-Agent
+    pub fn with_user_agent(mut self, user_agent: String) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    pub fn with_stream_base_url(mut self, stream_base_url: String) -> Self {
+        self.stream_base_url = stream_base_url;
+        self
+    }
+
+    /// Channel for sending control requests (SUBSCRIBE) to the running connection
+    pub(crate) fn command_sender(&self) -> mpsc::UnboundedSender<StreamCommand> {
+        self.command_sender.clone()
+    }
+
+    /// The frame-rate counter, readable while the client runs in another task
+    pub fn throughput_counter(&self) -> Arc<ThroughputCounter> {
+        self.throughput.clone()
+    }
+
+    /// Handle for asking the running client to close the socket and stop
+    /// `notify_one` keeps the request even if the client is mid-handshake
+    pub(crate) fn shutdown_handle(&self) -> Arc<Notify> {
+        self.shutdown.clone()
+    }
+
+    /// Get a receiver that always holds the latest connection state
+    pub fn connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.state.subscribe()
+    }
+
+    /// Record a state transition and emit it as an event (no-op if unchanged)
+    fn set_state(&self, new_state: ConnectionState) {
+        let previous = self.state.send_replace(new_state);
+        if previous != new_state {
+            info!("Connection state: {:?} -> {:?}", previous, new_state);
+            let _ = self.event_sender.send(MarketDataEvent::ConnectionStateChanged(new_state));
         }
     }
 
-    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let url = self.build_stream_url();
+    /// Connect and keep the stream alive, reconnecting with exponential backoff
+    /// whenever the socket closes or fails. Returns Ok once the consumer has gone away
+    /// or asked to shut down, or the last connection error when `max_retries` is exhausted.
+    pub async fn start(&self) -> Result<(), TradingError> {
+        let mut attempt: u32 = 0;
+        let mut backoff = self.reconnect.initial_backoff;
+        let mut reconnecting_since: Option<Instant> = None;  // When the current outage started
+
+        loop {
+            let last_error = match self.run_connection(attempt).await {
+                Ok(()) => None,
+                Err(e) => {
+                    error!("WebSocket connection failed: {}", e);
+                    Some(e.to_string())
+                }
+            };
+
+            // Nobody is listening anymore (stream dropped or shut down), so stop quietly
+            if self.event_sender.is_closed() || self.stopped.load(Ordering::SeqCst) {
+                self.set_state(ConnectionState::Disconnected);
+                return Ok(());
+            }
+
+            // A connection that actually came up ends the outage; the backoff only starts
+            // over if it also stayed up, so a socket that drops right after connecting
+            // (e.g. rejected streams) keeps backing off
+            if *self.state.borrow() == ConnectionState::Connected {
+                attempt = 0;
+                reconnecting_since = None;
+                let uptime = self.connected_at.lock().unwrap().map(|at| at.elapsed());
+                if uptime.is_some_and(|uptime| uptime >= self.reconnect.reset_after) {
+                    backoff = self.reconnect.initial_backoff;
+                }
+            }
+
+            if self.reconnect.max_retries.is_some_and(|max| attempt >= max) {
+                self.set_state(ConnectionState::Disconnected);
+                return match last_error {
+                    Some(e) => Err(e.into()),
+                    None => Ok(()),
+                };
+            }
+
+            // Never retry faster than min_interval, so an outage can't turn into a hot loop
+            let mut delay = backoff.max(self.reconnect.min_interval);
+            if let Some(probe) = &self.rest_probe {
+                if probe.is_reachable().await {
+                    // Only the first attempt skips the backoff: if the handshake keeps failing
+                    // while REST answers (403/429, WS connection limits), keep backing off
+                    if attempt == 0 {
+                        info!("Binance REST is reachable, reconnecting right away");
+                        delay = self.reconnect.min_interval;
+                    }
+                } else {
+                    warn!("Binance REST is unreachable too, the exchange looks down");
+                    delay = delay.max(probe.down_backoff);
+                }
+            }
+
+            // Stop once the next attempt would land past the total reconnection budget
+            let outage_start = *reconnecting_since.get_or_insert_with(Instant::now);
+            if let Some(max_total) = self.reconnect.max_total_duration {
+                if outage_start.elapsed() + delay > max_total {
+                    let message = format!(
+                        "Giving up reconnecting after {:?} ({} attempts)",
+                        outage_start.elapsed(), attempt
+                    );
+                    error!("{}", message);
+                    let _ = self.event_sender.send(MarketDataEvent::Error(message.clone()));
+                    self.set_state(ConnectionState::Disconnected);
+                    return Err(message.into());
+                }
+            }
+
+            attempt += 1;
+            self.set_state(ConnectionState::Reconnecting);
+            warn!("Reconnecting in {:?} (attempt {})", delay, attempt);
+            let _ = self.event_sender.send(MarketDataEvent::Reconnecting { attempt, delay });
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = self.shutdown.notified() => {
+                    info!("Shutdown requested while reconnecting");
+                    self.set_state(ConnectionState::Disconnected);
+                    return Ok(());
+                }
+            }
+            backoff = (backoff * 2).min(self.reconnect.max_backoff);
+        }
+    }
+
+    /// Run a single connection until it closes. `attempt` is 0 for the initial
+    /// connection and counts up while reconnecting.
+    async fn run_connection(&self, attempt: u32) -> Result<(), TradingError> {
+        let url = self.build_stream_url()?;
         info!("Connecting to Binance testnet: {}", url);
+        if attempt == 0 {
+            self.set_state(ConnectionState::Connecting);
+        }
 
-        let (ws_stream, _) = connect_async(&url).await?;
-        let (_, mut read) = ws_stream.split();
+        let request = self.build_request(&url)?;
+        let connect = connect_async_tls_with_config(
+            request,
+            Some(self.websocket_config),
+            false,
+            self.tls_connector.clone(),
+        );
+        let (ws_stream, _) = match tokio::time::timeout(self.connect_timeout, connect).await {
+            Ok(result) => result?,
+            Err(_) => {
+                let message = format!("Timed out connecting to {} after {:?}", url, self.connect_timeout);
+                let _ = self.event_sender.send(MarketDataEvent::Error(message.clone()));
+                return Err(message.into());
+            }
+        };
+        *self.connected_at.lock().unwrap() = Some(Instant::now());
+        self.set_state(ConnectionState::Connected);
+        if attempt > 0 {
+            info!("Reconnected after {} attempt(s)", attempt);
+            if let Some(hook) = &self.on_reconnect {
+                hook(attempt);
+            }
+        }
+        let (mut write, mut read) = ws_stream.split();
+        let mut commands = self.command_receiver.lock().await;
+        // Dropped with the connection, so anyone still waiting gets an error
+        let mut pending: HashMap<u64, PendingSubscribe> = HashMap::new();
 
-        while let Some(msg) = read.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    if let Err(e) = self.handle_message(&text) {
-                        error!("Error handling message: {}", e);
+        loop {
+            tokio::select! {
+                msg = read.next() => {
+                    let Some(msg) = msg else { break };
+                    match msg {
+                        Ok(Message::Text(text)) => {
+                            self.throughput.record();
+                            if let Ok(reply) = serde_json::from_str::<ControlReply>(&text) {
+                                self.resolve_control_reply(reply, &mut pending);
+                            } else if let Err(e) = self.handle_message(&text) {
+                                error!("Error handling message: {}", e);
+                            }
+                        }
+                        Ok(Message::Close(_)) => {
+                            warn!("WebSocket connection closed");
+                            break;
+                        }
+                        Err(e) => {
+                            error!("WebSocket error: {}", e);
+                            let _ = self.event_sender.send(MarketDataEvent::Error(e.to_string()));
+                        }
+                        _ => {}
                     }
                 }
-                Ok(Message::Close(_)) => {
-                    warn!("WebSocket connection closed");
+                _ = self.shutdown.notified() => {
+                    info!("Shutting down market data connection");
+                    self.stopped.store(true, Ordering::SeqCst);
+                    if let Err(e) = write.send(Message::Close(None)).await {
+                        debug!("Failed to send close frame: {}", e);
+                    }
                     break;
                 }
-                Err(e) => {
-                    error!("WebSocket error: {}", e);
-                    let _ = self.event_sender.send(MarketDataEvent::Error(e.to_string()));
-                }
-                _ => {}
+                Some(command) = commands.recv() => match command {
+                    StreamCommand::Subscribe { streams, reply } => {
+                        let id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
+                        let request = json!({ "method": "SUBSCRIBE", "params": streams, "id": id });
+                        info!("Subscribing to {:?} (request {})", streams, id);
+                        match write.send(Message::Text(request.to_string())).await {
+                            Ok(()) => { pending.insert(id, (streams, reply)); }
+                            Err(e) => { let _ = reply.send(Err(e.to_string())); }
+                        }
+                    }
+                },
             }
         }
 
         Ok(())
     }
 
-    fn build_stream_url(&self) -> String {
+    /// Match a control reply to its request and report the outcome
+    /// Successful subscriptions are remembered so reconnects include them
+    fn resolve_control_reply(&self, reply: ControlReply, pending: &mut HashMap<u64, PendingSubscribe>) {
+        let Some((streams, sender)) = pending.remove(&reply.id) else {
+            debug!("Control reply for unknown request {}", reply.id);
+            return;
+        };
+
+        let outcome = match reply.error {
+            Some(err) => {
+                warn!("Subscription to {:?} failed: {} (code {})", streams, err.msg, err.code);
+                Err(format!("{} (code {})", err.msg, err.code))
+            }
+            None => {
+                let mut subscriptions = self.subscriptions.lock().unwrap();
+                for stream in streams {
+                    if !subscriptions.contains(&stream) {
+                        subscriptions.push(stream);
+                    }
+                }
+                Ok(())
+            }
+        };
+        let _ = sender.send(outcome);
+    }
+
+    /// Build the handshake request for `url`, carrying our User-Agent
+    pub(crate) fn build_request(&self, url: &str) -> Result<Request, TradingError> {
+        let mut request = url.into_client_request()?;
+        request
+            .headers_mut()
+            .insert(USER_AGENT, HeaderValue::from_str(&self.user_agent)
+                .map_err(|e| TradingError::InvalidParameter(format!("user agent '{}': {}", self.user_agent, e)))?);
+        Ok(request)
+    }
+
+    /// Each stream appears once, lowercased, however the symbols were spelled
+    /// Errors when there is nothing to stream
+    pub(crate) fn build_stream_url(&self) -> Result<String, TradingError> {
         // Use Binance testnet WebSocket - free fake money trading!
-        if self.symbols.len() == 1 {
-            let symbol = self.symbols[0].to_lowercase();
-            format!("wss://stream.testnet.binance.vision/ws/{}@ticker", symbol)
-        } else {
-            let streams: Vec<String> = self
-                .symbols
-                .iter()
-                .map(|s| format!("{}@ticker", s.to_lowercase()))
-                .collect();
-            
-            format!(
-                "wss://stream.testnet.binance.vision/stream?streams={}",
+        let mut streams: Vec<String> = Vec::new();
+        let symbol_streams = self
+            .symbols
+            .iter()
+            .flat_map(|s| self.stream_types_for(s).iter().map(move |t| t.stream_name(s)));
+        for stream in symbol_streams.chain(self.subscriptions.lock().unwrap().iter().cloned()) {
+            if !streams.contains(&stream) {
+                streams.push(stream);
+            }
+        }
+
+        match streams.len() {
+            0 => Err("No symbols to stream".into()),
+            1 => Ok(format!("{}/ws/{}", self.stream_base_url, streams[0])),
+            _ => Ok(format!(
+                "{}/stream?streams={}",
+                self.stream_base_url,
                 streams.join("/")
-            )
+            )),
         }
     }
 
-    fn handle_message(&self, text: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Route one WebSocket message to the right parser based on its event type
+    /// Works for both single-stream frames and the combined `{"stream", "data"}` wrapper,
+    /// so one connection can carry any mix of ticker, trade, depth and kline streams
+    pub(crate) fn handle_message(&self, text: &str) -> Result<(), TradingError> {
         let data: Value = serde_json::from_str(text)?;
         
-        // Handle different message formats
-        if let Some(stream) = data.get("stream").and_then(|s| s.as_str()) {
-            // Combined stream format
-            if stream.contains("@ticker") {
-                let ticker_data = &data["data"];
-                self.parse_ticker(ticker_data)?;
+        // Combined stream format wraps the payload: {"stream": "btcusdt@ticker", "data": {...}}
+        let (stream, payload) = match data.get("stream").and_then(|s| s.as_str()) {
+            Some(stream) => (Some(stream), &data["data"]),
+            None => (None, &data),
+        };
+
+        match payload.get("e").and_then(|e| e.as_str()) {
+            Some("24hrTicker") => self.parse_ticker(payload),
+            Some("trade") | Some("aggTrade") => self.parse_trade(payload),
+            Some("depthUpdate") => self.parse_depth(payload),
+            Some("kline") => self.parse_kline(payload),
+            None if BookTickerFrame::matches(payload) => self.parse_book_ticker(payload),
+            None if PartialDepthFrame::matches(payload) => self.parse_partial_depth(stream, payload),
+            other => {
+                // Subscription acks, new event types, etc. - not an error, but worth seeing when debugging
+                debug!("Ignoring message with event type {:?} (stream {:?}): {}", other, stream, text);
+                Ok(())
             }
-        } else if data.get("e").and_then(|e| e.as_str()) == Some("24hrTicker") {
-            // Single stream format
-            self.parse_ticker(&data)?;
         }
+    }
 
+    fn parse_ticker(&self, ticker_data: &Value) -> Result<(), TradingError> {
+        // Deserialize straight into the typed frame - a missing or malformed
+        // field is now an error instead of silently becoming 0
+        let frame = TickerFrame::deserialize(ticker_data)?;
+        
+        let _ = self.event_sender.send(MarketDataEvent::Ticker(frame.into()));
         Ok(())
     }
 
-    fn parse_ticker(&self, ticker_data: &Value) -> Result<(), Box<dyn std::error::Error>> {
-        let ticker = Ticker {
-            symbol: ticker_data["s"].as_str().unwrap_or_default().to_string(),
-            price: ticker_data["c"].as_str().unwrap_or("0").parse()?,
-            volume: ticker_data["v"].as_str().unwrap_or("0").parse()?,
-            timestamp: ticker_data["E"].as_u64().unwrap_or(0),
+    fn parse_trade(&self, trade_data: &Value) -> Result<(), TradingError> {
+        let frame = TradeFrame::deserialize(trade_data)?;
+        let _ = self.event_sender.send(MarketDataEvent::Trade(frame.into()));
+        Ok(())
+    }
+
+    fn parse_depth(&self, depth_data: &Value) -> Result<(), TradingError> {
+        let frame = DepthUpdateFrame::deserialize(depth_data)?;
+        let _ = self.event_sender.send(MarketDataEvent::DepthUpdate(frame.try_into()
+            .map_err(|e| format!("Failed to parse depth level: {}", e))?));
+        Ok(())
+    }
+
+    fn parse_partial_depth(&self, stream: Option<&str>, depth_data: &Value) -> Result<(), TradingError> {
+        // The payload has no symbol: take it from the combined stream name, or from
+        // our only symbol on a single-stream connection
+        let symbol = match stream {
+            Some(stream) => stream.split('@').next().unwrap_or_default().to_uppercase(),
+            None => match self.symbols.as_slice() {
+                [symbol] => symbol.clone(),
+                _ => return Err("Partial depth frame without a stream name".into()),
+            },
         };
-        
-        let _ = self.event_sender.send(MarketDataEvent::Ticker(ticker));
+        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
+        let book = PartialDepthFrame::deserialize(depth_data)?
+            .into_order_book(symbol, timestamp)
+            .map_err(|e| format!("Failed to parse depth level: {}", e))?;
+        let _ = self.event_sender.send(MarketDataEvent::OrderBook(book));
+        Ok(())
+    }
+
+    fn parse_book_ticker(&self, book_ticker_data: &Value) -> Result<(), TradingError> {
+        let frame = BookTickerFrame::deserialize(book_ticker_data)?;
+        let _ = self.event_sender.send(frame.into());
+        Ok(())
+    }
+
+    fn parse_kline(&self, kline_data: &Value) -> Result<(), TradingError> {
+        let frame = KlineFrame::deserialize(kline_data)?;
+        let _ = self.event_sender.send(MarketDataEvent::Kline(frame.into()));
         Ok(())
     }
 }
 
-use futures_util::StreamExt;
\ No newline at end of file
+use futures_util::{SinkExt, StreamExt};
\ No newline at end of file
@@ -0,0 +1,74 @@
+use super::types::*;
+use std::collections::{HashMap, VecDeque};
+
+/// Closed candles kept per symbol unless configured otherwise
+const DEFAULT_HISTORY: usize = 500;
+
+/// Collects kline stream updates into per-symbol candles
+///
+/// The kline stream re-sends the forming candle on every trade, so the same
+/// candle shows up many times before its final `is_closed` update. With
+/// `closed_only` set, `on_kline` only passes candles through once they're
+/// final - what indicators need, since feeding them the same candle repeatedly
+/// skews their state. Without it, every update is passed through.
+pub struct CandleAggregator {
+    closed_only: bool,
+    max_history: usize,
+    forming: HashMap<String, Kline>,           // Latest in-progress candle per symbol
+    closed: HashMap<String, VecDeque<Kline>>,  // Finalized candles per symbol, oldest first
+}
+
+impl CandleAggregator {
+    pub fn new(closed_only: bool) -> Self {
+        Self {
+            closed_only,
+            max_history: DEFAULT_HISTORY,
+            forming: HashMap::new(),
+            closed: HashMap::new(),
+        }
+    }
+
+    /// Keep at most this many closed candles per symbol
+    pub fn with_max_history(mut self, max_history: usize) -> Self {
+        self.max_history = max_history;
+        self
+    }
+
+    pub fn is_closed_only(&self) -> bool {
+        self.closed_only
+    }
+
+    /// Record a kline update; returns it if it should be acted on in this mode
+    pub fn on_kline(&mut self, kline: Kline) -> Option<Kline> {
+        if kline.is_closed {
+            self.forming.remove(&kline.symbol);
+            let history = self.closed.entry(kline.symbol.clone()).or_default();
+            history.push_back(kline.clone());
+            while history.len() > self.max_history {
+                history.pop_front();
+            }
+            return Some(kline);
+        }
+
+        self.forming.insert(kline.symbol.clone(), kline.clone());
+        if self.closed_only { None } else { Some(kline) }
+    }
+
+    /// Same as `on_kline` for a stream event; non-kline events are ignored
+    pub fn on_event(&mut self, event: &MarketDataEvent) -> Option<Kline> {
+        match event {
+            MarketDataEvent::Kline(kline) => self.on_kline(kline.clone()),
+            _ => None,
+        }
+    }
+
+    /// The candle currently forming for a symbol, if any
+    pub fn forming(&self, symbol: &str) -> Option<&Kline> {
+        self.forming.get(symbol)
+    }
+
+    /// Finalized candles for a symbol, oldest first
+    pub fn closed_candles(&self, symbol: &str) -> impl Iterator<Item = &Kline> {
+        self.closed.get(symbol).into_iter().flatten()
+    }
+}
@@ -0,0 +1,257 @@
+// Raw WebSocket frames as sent by Binance, and their conversion into our types.
+// Binance uses single-letter keys, so fields are renamed to readable names.
+// Only the fields we actually use are listed - serde ignores the rest.
+use super::types::*;
+use crate::trading::types::string_to_f64;
+use serde::Deserialize;
+
+/// 24hr ticker frame from the `<symbol>@ticker` stream
+#[derive(Debug, Deserialize)]
+pub(crate) struct TickerFrame {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "c", deserialize_with = "string_to_f64")]
+    last_price: f64,      // Last traded price
+    #[serde(rename = "v", deserialize_with = "string_to_f64")]
+    volume: f64,          // 24h base asset volume
+    #[serde(rename = "q", deserialize_with = "string_to_f64", default)]
+    quote_volume: f64,    // 24h quote asset volume
+    #[serde(rename = "E")]
+    event_time: u64,      // Event time in milliseconds
+}
+
+impl From<TickerFrame> for Ticker {
+    fn from(frame: TickerFrame) -> Self {
+        Ticker {
+            symbol: frame.symbol,
+            price: frame.last_price,
+            volume: frame.volume,
+            quote_volume: frame.quote_volume,
+            timestamp: frame.event_time,
+        }
+    }
+}
+
+/// Frame from the `<symbol>@bookTicker` stream
+/// Unlike the other streams it has no `e` event type (or event time)
+#[derive(Debug, Deserialize)]
+pub(crate) struct BookTickerFrame {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "b", deserialize_with = "string_to_f64")]
+    bid: f64,
+    #[serde(rename = "B", deserialize_with = "string_to_f64")]
+    bid_qty: f64,
+    #[serde(rename = "a", deserialize_with = "string_to_f64")]
+    ask: f64,
+    #[serde(rename = "A", deserialize_with = "string_to_f64")]
+    ask_qty: f64,
+}
+
+impl BookTickerFrame {
+    /// bookTicker payloads are recognized by their shape: update id plus scalar best bid/ask
+    pub(crate) fn matches(payload: &serde_json::Value) -> bool {
+        payload.get("u").is_some()
+            && payload.get("b").is_some_and(|b| b.is_string())
+            && payload.get("a").is_some_and(|a| a.is_string())
+    }
+}
+
+impl From<BookTickerFrame> for MarketDataEvent {
+    fn from(frame: BookTickerFrame) -> Self {
+        MarketDataEvent::BookTicker {
+            symbol: frame.symbol,
+            bid: frame.bid,
+            bid_qty: frame.bid_qty,
+            ask: frame.ask,
+            ask_qty: frame.ask_qty,
+        }
+    }
+}
+
+/// Trade frame from the `<symbol>@trade` or `<symbol>@aggTrade` stream
+/// Both share the fields we need
+#[derive(Debug, Deserialize)]
+pub(crate) struct TradeFrame {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "p", deserialize_with = "string_to_f64")]
+    price: f64,
+    #[serde(rename = "q", deserialize_with = "string_to_f64")]
+    quantity: f64,
+    #[serde(rename = "T")]
+    trade_time: u64,
+    #[serde(rename = "m")]
+    buyer_is_maker: bool,  // True when the buyer's order was resting, i.e. the seller hit it
+}
+
+impl From<TradeFrame> for Trade {
+    fn from(frame: TradeFrame) -> Self {
+        Trade {
+            symbol: frame.symbol,
+            price: frame.price,
+            quantity: frame.quantity,
+            // The aggressor is whoever wasn't the maker
+            side: if frame.buyer_is_maker { TradeSide::Sell } else { TradeSide::Buy },
+            timestamp: frame.trade_time,
+        }
+    }
+}
+
+/// Diff depth frame from the `<symbol>@depth` stream
+/// Levels are `[price, quantity]` string pairs
+#[derive(Debug, Deserialize)]
+pub(crate) struct DepthUpdateFrame {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "E")]
+    event_time: u64,
+    #[serde(rename = "U")]
+    first_update_id: u64,
+    #[serde(rename = "u")]
+    final_update_id: u64,
+    #[serde(rename = "b")]
+    bids: Vec<[String; 2]>,
+    #[serde(rename = "a")]
+    asks: Vec<[String; 2]>,
+}
+
+/// Parse `[price, quantity]` string pairs into order book levels
+pub(crate) fn parse_levels(raw: &[[String; 2]]) -> Result<Vec<OrderBookLevel>, std::num::ParseFloatError> {
+    raw.iter()
+        .map(|[price, quantity]| {
+            Ok(OrderBookLevel {
+                price: price.parse()?,
+                quantity: quantity.parse()?,
+            })
+        })
+        .collect()
+}
+
+/// Parse both sides of a book, best level first: bids descending, asks ascending
+fn parse_book(
+    bids: &[[String; 2]],
+    asks: &[[String; 2]],
+) -> Result<(Vec<OrderBookLevel>, Vec<OrderBookLevel>), std::num::ParseFloatError> {
+    let mut bids = parse_levels(bids)?;
+    let mut asks = parse_levels(asks)?;
+    bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+    asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+    Ok((bids, asks))
+}
+
+impl TryFrom<DepthUpdateFrame> for DepthUpdate {
+    type Error = std::num::ParseFloatError;
+
+    fn try_from(frame: DepthUpdateFrame) -> Result<Self, Self::Error> {
+        let (bids, asks) = parse_book(&frame.bids, &frame.asks)?;
+        Ok(DepthUpdate {
+            symbol: frame.symbol,
+            first_update_id: frame.first_update_id,
+            final_update_id: frame.final_update_id,
+            bids,
+            asks,
+            timestamp: frame.event_time,
+        })
+    }
+}
+
+/// Snapshot frame from the `<symbol>@depth<levels>@100ms` stream
+/// Like bookTicker it has no `e`, and it doesn't carry the symbol either
+#[derive(Debug, Deserialize)]
+pub(crate) struct PartialDepthFrame {
+    bids: Vec<[String; 2]>,
+    asks: Vec<[String; 2]>,
+}
+
+impl PartialDepthFrame {
+    /// Partial depth payloads are recognized by their shape: lastUpdateId plus bid/ask arrays
+    pub(crate) fn matches(payload: &serde_json::Value) -> bool {
+        payload.get("lastUpdateId").is_some()
+            && payload.get("bids").is_some_and(|b| b.is_array())
+            && payload.get("asks").is_some_and(|a| a.is_array())
+    }
+
+    /// Build the book for `symbol` (taken from the stream name), stamped with `timestamp`
+    pub(crate) fn into_order_book(self, symbol: String, timestamp: u64) -> Result<OrderBook, std::num::ParseFloatError> {
+        let (bids, asks) = parse_book(&self.bids, &self.asks)?;
+        Ok(OrderBook { symbol, bids, asks, timestamp })
+    }
+}
+
+/// Kline frame from the `<symbol>@kline_<interval>` stream
+/// The candle itself is nested under `k`
+#[derive(Debug, Deserialize)]
+pub(crate) struct KlineFrame {
+    #[serde(rename = "k")]
+    kline: KlineData,
+}
+
+#[derive(Debug, Deserialize)]
+struct KlineData {
+    #[serde(rename = "s")]
+    symbol: String,
+    #[serde(rename = "i")]
+    interval: String,
+    #[serde(rename = "t")]
+    open_time: u64,
+    #[serde(rename = "T")]
+    close_time: u64,
+    #[serde(rename = "o", deserialize_with = "string_to_f64")]
+    open: f64,
+    #[serde(rename = "h", deserialize_with = "string_to_f64")]
+    high: f64,
+    #[serde(rename = "l", deserialize_with = "string_to_f64")]
+    low: f64,
+    #[serde(rename = "c", deserialize_with = "string_to_f64")]
+    close: f64,
+    #[serde(rename = "v", deserialize_with = "string_to_f64")]
+    volume: f64,
+    #[serde(rename = "q", deserialize_with = "string_to_f64", default)]
+    quote_volume: f64,
+    #[serde(rename = "n", default)]
+    trade_count: u64,
+    #[serde(rename = "V", deserialize_with = "string_to_f64", default)]
+    taker_buy_base: f64,
+    #[serde(rename = "Q", deserialize_with = "string_to_f64", default)]
+    taker_buy_quote: f64,
+    #[serde(rename = "x")]
+    is_closed: bool,      // False while the candle is still forming
+}
+
+impl From<KlineFrame> for Kline {
+    fn from(frame: KlineFrame) -> Self {
+        let k = frame.kline;
+        Kline {
+            symbol: k.symbol,
+            interval: k.interval,
+            open_time: k.open_time,
+            close_time: k.close_time,
+            open: k.open,
+            high: k.high,
+            low: k.low,
+            close: k.close,
+            volume: k.volume,
+            quote_volume: k.quote_volume,
+            trade_count: k.trade_count,
+            taker_buy_base: k.taker_buy_base,
+            taker_buy_quote: k.taker_buy_quote,
+            is_closed: k.is_closed,
+        }
+    }
+}
+
+/// Reply to a control request like SUBSCRIBE: `{"result":null,"id":1}` on success,
+/// `{"error":{"code":2,"msg":"..."},"id":1}` on failure
+#[derive(Debug, Deserialize)]
+pub(crate) struct ControlReply {
+    pub id: u64,
+    #[serde(default)]
+    pub error: Option<ControlError>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct ControlError {
+    pub code: i64,
+    pub msg: String,
+}
@@ -0,0 +1,81 @@
+use super::types::*;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Symbols ranked by 24h rolling quote volume, kept up to date from the ticker stream
+///
+/// Each ticker replaces its symbol's volume (the field is already a rolling 24h
+/// total), and the symbol moves to its new place in the ranking. Ties are broken
+/// alphabetically so the order is stable.
+#[derive(Debug, Clone, Default)]
+pub struct VolumeLeaderboard {
+    volumes: HashMap<String, f64>,  // symbol -> latest 24h quote volume
+    ranking: Vec<(String, f64)>,    // Highest volume first
+}
+
+impl VolumeLeaderboard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update from a ticker's 24h quote volume
+    pub fn on_ticker(&mut self, ticker: &Ticker) {
+        self.update(&ticker.symbol, ticker.quote_volume);
+    }
+
+    /// Feed any market data event; only tickers count
+    pub fn on_event(&mut self, event: &MarketDataEvent) {
+        if let MarketDataEvent::Ticker(ticker) = event {
+            self.on_ticker(ticker);
+        }
+    }
+
+    /// Set a symbol's 24h quote volume directly
+    pub fn update(&mut self, symbol: &str, quote_volume: f64) {
+        if let Some(previous) = self.volumes.insert(symbol.to_string(), quote_volume) {
+            if previous == quote_volume {
+                return;
+            }
+            let index = self.position(symbol, previous);
+            self.ranking.remove(index);
+        }
+
+        let index = self.position(symbol, quote_volume);
+        self.ranking.insert(index, (symbol.to_string(), quote_volume));
+    }
+
+    /// Where (`symbol`, `volume`) sits (or would sit) in the ranking
+    fn position(&self, symbol: &str, volume: f64) -> usize {
+        self.ranking.partition_point(|(s, v)| Self::rank_order((s, *v), (symbol, volume)) == Ordering::Less)
+    }
+
+    /// Higher volume first, then by symbol
+    fn rank_order(a: (&str, f64), b: (&str, f64)) -> Ordering {
+        b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0))
+    }
+
+    /// The `n` symbols with the highest 24h quote volume, highest first
+    pub fn top(&self, n: usize) -> Vec<(String, f64)> {
+        self.ranking.iter().take(n).cloned().collect()
+    }
+
+    /// 1-based rank of a symbol, if it has been seen
+    pub fn rank(&self, symbol: &str) -> Option<usize> {
+        let volume = *self.volumes.get(symbol)?;
+        Some(self.position(symbol, volume) + 1)
+    }
+
+    /// Latest 24h quote volume of a symbol
+    pub fn volume(&self, symbol: &str) -> Option<f64> {
+        self.volumes.get(symbol).copied()
+    }
+
+    /// Number of symbols ranked
+    pub fn len(&self) -> usize {
+        self.ranking.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranking.is_empty()
+    }
+}
@@ -1,6 +1,30 @@
 pub mod types;
 pub mod binance;
+mod frames;
 pub mod stream;
+pub mod supervisor;
+pub mod source;
+pub mod resample;
+pub mod candles;
+pub mod recorder;
+pub mod spread;
+pub mod prices;
+pub mod trade_flow;
+pub mod leaderboard;
+pub mod throughput;
 
 pub use types::*;
-pub use stream::MarketDataStream;
+pub use stream::{EventFilter, MarketDataStream, DEFAULT_BROADCAST_CAPACITY, SUBSCRIBE_TIMEOUT};
+pub use supervisor::{partition_symbols, ClientConfigurator, StreamSupervisor, MAX_STREAMS_PER_CONNECTION};
+pub use binance::{default_websocket_config, normalize_symbols, RestProbe, DEFAULT_CONNECT_TIMEOUT, DEFAULT_EXCHANGE_DOWN_BACKOFF, tls_connector_with_roots, ReconnectConfig, ReconnectHook, StreamType, DepthLevels, DEFAULT_MAX_FRAME_SIZE, DEFAULT_MAX_MESSAGE_SIZE};
+pub use tokio_tungstenite::Connector;
+pub use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+pub use source::{MarketDataSource, MockMarketDataSource, SimulatedLatency};
+pub use resample::resample;
+pub use candles::CandleAggregator;
+pub use prices::{microprice, mid_price};
+pub use trade_flow::{TradeFlow, MAX_FILL_ESTIMATE_DISTANCE};
+pub use leaderboard::VolumeLeaderboard;
+pub use throughput::{ThroughputCounter, DEFAULT_THROUGHPUT_WINDOW};
+pub use spread::{SpreadSample, SpreadTracker};
+pub use recorder::{EventRecorder, FileReplaySource, RecordedEvent};
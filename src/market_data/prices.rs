@@ -0,0 +1,20 @@
+// Reference prices derived from the top of the book.
+
+/// Simple midpoint between the best bid and ask
+pub fn mid_price(bid: f64, ask: f64) -> f64 {
+    (bid + ask) / 2.0
+}
+
+/// Size-weighted "microprice" between the best bid and ask
+///
+/// Each price is weighted by the size on the *opposite* side, so the result leans
+/// toward the side with less size - a thin ask is likely to be taken out first,
+/// so the fair price sits closer to it. Falls back to `mid_price` when both
+/// sizes are zero.
+pub fn microprice(bid: f64, bid_qty: f64, ask: f64, ask_qty: f64) -> f64 {
+    let total_qty = bid_qty + ask_qty;
+    if total_qty <= 0.0 {
+        return mid_price(bid, ask);
+    }
+    (bid * ask_qty + ask * bid_qty) / total_qty
+}
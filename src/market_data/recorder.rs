@@ -0,0 +1,179 @@
+use super::source::{MarketDataSource, SimulatedLatency};
+use super::types::MarketDataEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+use tracing::error;
+
+/// One captured event and when we received it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub received_at: u64,  // Local receive time, milliseconds since the Unix epoch
+    pub event: MarketDataEvent,
+}
+
+/// Captures market data events for replay/debugging
+///
+/// Events are kept in memory, and with `to_file` also written out as JSON lines
+/// as they arrive, so a crash doesn't lose the session. Attach one to a stream
+/// with `MarketDataStream::with_recorder`.
+#[derive(Default)]
+pub struct EventRecorder {
+    events: Vec<RecordedEvent>,
+    file: Option<BufWriter<File>>,  // JSON-lines tee, if recording to a file
+}
+
+impl EventRecorder {
+    /// Record into memory only
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Record into memory and tee every event to `path` (created or truncated)
+    pub fn to_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self {
+            events: Vec::new(),
+            file: Some(BufWriter::new(file)),
+        })
+    }
+
+    /// Capture one event, stamped with the current time
+    pub fn record(&mut self, event: &MarketDataEvent) {
+        let recorded = RecordedEvent {
+            received_at: chrono::Utc::now().timestamp_millis() as u64,
+            event: event.clone(),
+        };
+
+        if let Some(file) = self.file.as_mut() {
+            let written = serde_json::to_string(&recorded)
+                .map_err(std::io::Error::from)
+                .and_then(|line| writeln!(file, "{}", line));
+            if let Err(e) = written {
+                error!("Failed to write recorded event: {}", e);
+            }
+        }
+        self.events.push(recorded);
+    }
+
+    /// Everything recorded so far, oldest first
+    pub fn events(&self) -> &[RecordedEvent] {
+        &self.events
+    }
+
+    /// Write the buffered file output to disk (no-op for in-memory recorders)
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Write all recorded events to `path` as JSON lines, e.g. after an in-memory session
+    pub fn save(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        for recorded in &self.events {
+            writeln!(file, "{}", serde_json::to_string(recorded)?)?;
+        }
+        file.flush()
+    }
+}
+
+impl Drop for EventRecorder {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            error!("Failed to flush event recording: {}", e);
+        }
+    }
+}
+
+/// Replays a recording from `EventRecorder`, keeping the original gaps between events
+///
+/// `with_speed(10.0)` plays ten times faster; `f64::INFINITY` drops the delays entirely.
+pub struct FileReplaySource {
+    events: VecDeque<RecordedEvent>,
+    speed: f64,
+    last_received_at: Option<u64>,  // Receive time of the previously replayed event
+    latency: Option<SimulatedLatency>,  // Extra delay on top of the recorded timing
+}
+
+impl FileReplaySource {
+    /// Load a JSON-lines recording (blank lines are skipped)
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut events = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let recorded: RecordedEvent = serde_json::from_str(&line)
+                .map_err(|e| format!("Failed to parse recorded event on line {}: {}", index + 1, e))?;
+            events.push(recorded);
+        }
+        Ok(Self::from_events(events))
+    }
+
+    /// Replay events that are already in memory, e.g. `EventRecorder::events()`
+    pub fn from_events(events: Vec<RecordedEvent>) -> Self {
+        Self {
+            events: events.into(),
+            speed: 1.0,
+            last_received_at: None,
+            latency: None,
+        }
+    }
+
+    /// Replay faster (> 1.0) or slower (< 1.0) than real time
+    /// Non-positive values are ignored
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        if speed > 0.0 {
+            self.speed = speed;
+        }
+        self
+    }
+
+    /// Add `latency` plus up to `jitter` on top of the recorded gaps (not scaled by speed)
+    pub fn with_latency(mut self, latency: Duration, jitter: Duration) -> Self {
+        self.latency = Some(SimulatedLatency::new(latency, jitter));
+        self
+    }
+
+    /// Like `with_latency`, with full control over the delays (e.g. a fixed seed)
+    pub fn with_simulated_latency(mut self, latency: SimulatedLatency) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Events left to replay
+    pub fn remaining(&self) -> usize {
+        self.events.len()
+    }
+
+    /// How long to wait before replaying an event received at `received_at`
+    fn delay_before(&self, received_at: u64) -> Duration {
+        let Some(previous) = self.last_received_at else {
+            return Duration::ZERO;
+        };
+        let gap_ms = received_at.saturating_sub(previous) as f64;
+        Duration::from_secs_f64(gap_ms / 1000.0 / self.speed)
+    }
+}
+
+impl MarketDataSource for FileReplaySource {
+    async fn next_event(&mut self) -> Option<MarketDataEvent> {
+        let recorded = self.events.pop_front()?;
+        let delay = self.delay_before(recorded.received_at);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        if let Some(latency) = self.latency.as_mut() {
+            latency.wait().await;
+        }
+        self.last_received_at = Some(recorded.received_at);
+        Some(recorded.event)
+    }
+}
@@ -0,0 +1,48 @@
+use crate::market_data::types::Kline;
+
+/// Downsample klines by merging every `factor` consecutive candles into one,
+/// e.g. 15 one-minute candles into a single 15-minute candle
+/// Open comes from the first candle, close from the last, high/low are the
+/// extremes, and the volume fields and trade counts are summed. A trailing
+/// group with fewer than `factor` candles is kept but marked partial
+/// (`is_closed == false`)
+pub fn resample(klines: &[Kline], factor: usize) -> Vec<Kline> {
+    if factor == 0 {
+        return Vec::new();
+    }
+
+    klines
+        .chunks(factor)
+        .map(|group| {
+            let first = &group[0];
+            let last = &group[group.len() - 1];
+            Kline {
+                symbol: first.symbol.clone(),
+                interval: scale_interval(&first.interval, factor),
+                open_time: first.open_time,
+                close_time: last.close_time,
+                open: first.open,
+                high: group.iter().map(|k| k.high).fold(f64::MIN, f64::max),
+                low: group.iter().map(|k| k.low).fold(f64::MAX, f64::min),
+                close: last.close,
+                volume: group.iter().map(|k| k.volume).sum(),
+                quote_volume: group.iter().map(|k| k.quote_volume).sum(),
+                trade_count: group.iter().map(|k| k.trade_count).sum(),
+                taker_buy_base: group.iter().map(|k| k.taker_buy_base).sum(),
+                taker_buy_quote: group.iter().map(|k| k.taker_buy_quote).sum(),
+                // Partial if the group is short or its last candle is still forming
+                is_closed: group.len() == factor && last.is_closed,
+            }
+        })
+        .collect()
+}
+
+/// "1m" with factor 15 -> "15m"; intervals we can't parse get an "x{factor}" suffix
+fn scale_interval(interval: &str, factor: usize) -> String {
+    let split = interval.find(|c: char| !c.is_ascii_digit()).unwrap_or(interval.len());
+    let (count, unit) = interval.split_at(split);
+    match count.parse::<usize>() {
+        Ok(count) if !unit.is_empty() => format!("{}{}", count * factor, unit),
+        _ => format!("{}x{}", interval, factor),
+    }
+}
@@ -0,0 +1,116 @@
+use super::types::*;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::time::Duration;
+
+/// Anything that produces a sequence of market data events
+/// Implemented by the live `MarketDataStream` and by `MockMarketDataSource`,
+/// so strategies and the runner can be exercised without a network connection
+pub trait MarketDataSource {
+    /// Wait for the next event, or None once the source is exhausted/closed
+    fn next_event(&mut self) -> impl Future<Output = Option<MarketDataEvent>> + Send;
+}
+
+/// Artificial delivery delay for offline sources: a fixed latency plus random jitter
+///
+/// Simulates slow data so consumers can be tested against delays. Jitter is
+/// uniform in `0..=jitter` and comes from a small xorshift generator, so no
+/// extra dependency is needed and runs can be made repeatable with a seed.
+#[derive(Debug, Clone)]
+pub struct SimulatedLatency {
+    latency: Duration,
+    jitter: Duration,
+    rng_state: u64,
+}
+
+impl SimulatedLatency {
+    pub fn new(latency: Duration, jitter: Duration) -> Self {
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self::with_seed(latency, jitter, seed)
+    }
+
+    /// Same jitter sequence for the same seed
+    pub fn with_seed(latency: Duration, jitter: Duration, seed: u64) -> Self {
+        Self {
+            latency,
+            jitter,
+            rng_state: seed | 1,  // xorshift gets stuck at zero
+        }
+    }
+
+    /// Delay to apply before the next event
+    pub fn next_delay(&mut self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.latency;
+        }
+        self.rng_state ^= self.rng_state << 13;
+        self.rng_state ^= self.rng_state >> 7;
+        self.rng_state ^= self.rng_state << 17;
+        let fraction = (self.rng_state >> 11) as f64 / (1u64 << 53) as f64;
+        self.latency + self.jitter.mul_f64(fraction)
+    }
+
+    pub(crate) async fn wait(&mut self) {
+        let delay = self.next_delay();
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Replays a fixed list of events - handy for tests and offline experiments
+pub struct MockMarketDataSource {
+    events: VecDeque<MarketDataEvent>,
+    latency: Option<SimulatedLatency>,  // Delay before each event, if any
+}
+
+impl MockMarketDataSource {
+    pub fn new(events: Vec<MarketDataEvent>) -> Self {
+        Self {
+            events: events.into(),
+            latency: None,
+        }
+    }
+
+    /// Wait `latency` plus up to `jitter` before yielding each event
+    pub fn with_latency(mut self, latency: Duration, jitter: Duration) -> Self {
+        self.latency = Some(SimulatedLatency::new(latency, jitter));
+        self
+    }
+
+    /// Like `with_latency`, with full control over the delays (e.g. a fixed seed)
+    pub fn with_simulated_latency(mut self, latency: SimulatedLatency) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Build a source that emits one ticker per price, 1 second apart
+    pub fn from_prices(symbol: &str, prices: &[f64]) -> Self {
+        let events = prices
+            .iter()
+            .enumerate()
+            .map(|(i, &price)| {
+                MarketDataEvent::Ticker(Ticker {
+                    symbol: symbol.to_string(),
+                    price,
+                    volume: 0.0,
+                    quote_volume: 0.0,
+                    timestamp: 1_640_995_200_000 + i as u64 * 1000,
+                })
+            })
+            .collect();
+        Self::new(events)
+    }
+}
+
+impl MarketDataSource for MockMarketDataSource {
+    async fn next_event(&mut self) -> Option<MarketDataEvent> {
+        let event = self.events.pop_front()?;
+        if let Some(latency) = self.latency.as_mut() {
+            latency.wait().await;
+        }
+        Some(event)
+    }
+}
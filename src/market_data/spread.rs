@@ -0,0 +1,144 @@
+use super::types::*;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::Duration;
+use tracing::error;
+
+/// Samples kept unless configured otherwise: one hour
+const DEFAULT_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// Best bid/ask and the spread between them at one point in time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpreadSample {
+    pub timestamp: u64,  // Event time in milliseconds
+    pub bid: f64,
+    pub ask: f64,
+    pub spread: f64,     // ask - bid, in quote currency
+}
+
+/// Rolling time series of one symbol's bid-ask spread
+///
+/// Fed with order book or bookTicker updates (or any other source via `on_top_of_book`).
+/// Samples older than the retention period, measured from the newest sample,
+/// are dropped. Windows passed to `average`/`max` are measured the same way, so
+/// replayed data aggregates exactly like live data.
+pub struct SpreadTracker {
+    symbol: String,
+    retention: Duration,
+    samples: VecDeque<SpreadSample>,  // Oldest first
+    csv: Option<BufWriter<File>>,     // Every sample is appended here when set
+}
+
+impl SpreadTracker {
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            retention: DEFAULT_RETENTION,
+            samples: VecDeque::new(),
+            csv: None,
+        }
+    }
+
+    /// How much history to keep (default one hour)
+    pub fn with_retention(mut self, retention: Duration) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Also append every sample to a CSV file (`timestamp,bid,ask,spread`)
+    /// The file is created or truncated, and starts with a header row
+    pub fn with_csv(mut self, path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "timestamp,bid,ask,spread")?;
+        self.csv = Some(writer);
+        Ok(self)
+    }
+
+    /// Record the top of book from a market data event for our symbol
+    /// Returns the new sample, or None if the event didn't produce one
+    pub fn on_event(&mut self, event: &MarketDataEvent) -> Option<SpreadSample> {
+        match event {
+            MarketDataEvent::OrderBook(book) => self.on_book(book),
+            // bookTicker frames carry no event time, so they're stamped on arrival
+            MarketDataEvent::BookTicker { symbol, bid, ask, .. } if *symbol == self.symbol => {
+                let now = chrono::Utc::now().timestamp_millis() as u64;
+                Some(self.on_top_of_book(now, *bid, *ask))
+            }
+            _ => None,
+        }
+    }
+
+    /// Record the best bid/ask of an order book update
+    /// Ignored for other symbols or if either side is empty
+    pub fn on_book(&mut self, book: &OrderBook) -> Option<SpreadSample> {
+        if book.symbol != self.symbol {
+            return None;
+        }
+        let bid = book.bids.iter().map(|l| l.price).reduce(f64::max)?;
+        let ask = book.asks.iter().map(|l| l.price).reduce(f64::min)?;
+        Some(self.on_top_of_book(book.timestamp, bid, ask))
+    }
+
+    /// Record a best bid/ask pair observed at `timestamp` (ms)
+    pub fn on_top_of_book(&mut self, timestamp: u64, bid: f64, ask: f64) -> SpreadSample {
+        let sample = SpreadSample { timestamp, bid, ask, spread: ask - bid };
+
+        if let Some(csv) = self.csv.as_mut() {
+            if let Err(e) = writeln!(csv, "{},{},{},{}", timestamp, bid, ask, sample.spread) {
+                error!("Failed to write spread sample: {}", e);
+            }
+        }
+
+        self.samples.push_back(sample);
+        let cutoff = timestamp.saturating_sub(self.retention.as_millis() as u64);
+        while self.samples.front().is_some_and(|s| s.timestamp < cutoff) {
+            self.samples.pop_front();
+        }
+        sample
+    }
+
+    /// The most recent spread
+    pub fn current(&self) -> Option<f64> {
+        self.samples.back().map(|s| s.spread)
+    }
+
+    /// Mean spread over the last `window` (relative to the newest sample)
+    pub fn average(&self, window: Duration) -> Option<f64> {
+        let (sum, count) = self.window(window).fold((0.0, 0), |(sum, count), s| (sum + s.spread, count + 1));
+        if count == 0 { None } else { Some(sum / count as f64) }
+    }
+
+    /// Widest spread over the last `window` (relative to the newest sample)
+    pub fn max(&self, window: Duration) -> Option<f64> {
+        self.window(window).map(|s| s.spread).reduce(f64::max)
+    }
+
+    /// All retained samples, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = &SpreadSample> {
+        self.samples.iter()
+    }
+
+    /// Write any buffered CSV rows to disk
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        match self.csv.as_mut() {
+            Some(csv) => csv.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn window(&self, window: Duration) -> impl Iterator<Item = &SpreadSample> {
+        let newest = self.samples.back().map_or(0, |s| s.timestamp);
+        let start = newest.saturating_sub(window.as_millis() as u64);
+        self.samples.iter().filter(move |s| s.timestamp >= start)
+    }
+}
+
+impl Drop for SpreadTracker {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            error!("Failed to flush spread CSV: {}", e);
+        }
+    }
+}
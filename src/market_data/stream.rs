@@ -1,18 +1,91 @@
 use super::types::*;
-use super::binance::BinanceClient;
-use tokio::sync::mpsc;
+use super::binance::{normalize_symbols, BinanceClient, StreamCommand, StreamType};
+use super::recorder::EventRecorder;
+use super::source::MarketDataSource;
+use super::throughput::ThroughputCounter;
+use crate::error::TradingError;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, oneshot, watch, Notify};
 use tracing::info;
 
+/// How long `subscribe` waits for Binance to confirm
+pub const SUBSCRIBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Events each `subscribe_events` receiver can fall behind by before it starts losing them
+pub const DEFAULT_BROADCAST_CAPACITY: usize = 1024;
+
+/// Predicate deciding which events `next_event` hands out (true = keep)
+pub type EventFilter = Box<dyn Fn(&MarketDataEvent) -> bool + Send>;
+
 pub struct MarketDataStream {
     event_receiver: mpsc::UnboundedReceiver<MarketDataEvent>,
-    _client_handle: tokio::task::JoinHandle<()>,
+    state_receiver: watch::Receiver<ConnectionState>,
+    throughput: Arc<ThroughputCounter>,
+    commands: mpsc::UnboundedSender<StreamCommand>,
+    heartbeat_interval: Option<Duration>,  // None = no heartbeats (the default)
+    shutdown: Arc<Notify>,
+    recorder: Option<EventRecorder>,  // Tees every real event for later replay
+    filter: Option<EventFilter>,  // Events it rejects are dropped before `next_event` returns
+    broadcast: Option<broadcast::Sender<MarketDataEvent>>,  // Fan-out to `subscribe_events` receivers
+    client_handle: tokio::task::JoinHandle<()>,
 }
 
 impl MarketDataStream {
-    pub async fn new(symbols: Vec<String>) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Ticker streams for `symbols`; case and duplicates don't matter (see `normalize_symbols`)
+    /// Errors if no symbols are left after normalizing
+    pub async fn new(symbols: Vec<String>) -> Result<Self, TradingError> {
+        Self::new_with_client(symbols, |client| client).await
+    }
+
+    /// Like `new`, but picks the streams per symbol, e.g.
+    /// `[("BTCUSDT", StreamType::PartialDepth(DepthLevels::Twenty)), ("ETHUSDT", StreamType::Ticker)]`
+    /// A symbol listed more than once gets all of its stream types
+    pub async fn new_with_streams(streams: Vec<(String, StreamType)>) -> Result<Self, TradingError> {
+        let mut per_symbol: Vec<(String, Vec<StreamType>)> = Vec::new();
+        for (symbol, stream_type) in streams {
+            let symbol = symbol.trim().to_uppercase();
+            match per_symbol.iter_mut().find(|(s, _)| *s == symbol) {
+                Some((_, types)) if !types.contains(&stream_type) => types.push(stream_type),
+                Some(_) => {}
+                None => per_symbol.push((symbol, vec![stream_type])),
+            }
+        }
+        let symbols = per_symbol.iter().map(|(s, _)| s.clone()).collect();
+        Self::new_with_client(symbols, move |client| {
+            per_symbol.into_iter().fold(client, |client, (symbol, types)| {
+                client.with_symbol_stream_types(&symbol, types)
+            })
+        }).await
+    }
+
+    /// Like `new`, but lets you configure the underlying `BinanceClient` first:
+    ///
+    /// ```no_run
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// use rust_trading_system::market_data::MarketDataStream;
+    ///
+    /// let stream = MarketDataStream::new_with_client(vec!["BTCUSDT".to_string()], |client| {
+    ///     client.with_user_agent("my-bot/1.0".to_string())
+    /// }).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn new_with_client<F>(symbols: Vec<String>, configure: F) -> Result<Self, TradingError>
+    where
+        F: FnOnce(BinanceClient) -> BinanceClient,
+    {
+        let symbols = normalize_symbols(&symbols);
+        if symbols.is_empty() {
+            return Err("MarketDataStream needs at least one symbol".into());
+        }
         let (event_sender, event_receiver) = mpsc::unbounded_channel();
         
-        let client = BinanceClient::new(symbols.clone(), event_sender);
+        let client = configure(BinanceClient::new(symbols.clone(), event_sender));
+        let state_receiver = client.connection_state();
+        let throughput = client.throughput_counter();
+        let commands = client.command_sender();
+        let shutdown = client.shutdown_handle();
         
         let client_handle = tokio::spawn(async move {
             if let Err(e) = client.start().await {
@@ -24,11 +97,165 @@ impl MarketDataStream {
 
         Ok(Self {
             event_receiver,
-            _client_handle: client_handle,
+            state_receiver,
+            throughput,
+            commands,
+            heartbeat_interval: None,
+            shutdown,
+            recorder: None,
+            filter: None,
+            broadcast: None,
+            client_handle,
         })
     }
 
+    /// Emit `MarketDataEvent::Heartbeat` whenever `interval` passes without a real event,
+    /// so a consumer loop can do housekeeping while the market is quiet
+    pub fn with_heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat_interval = Some(interval);
+        self
+    }
+
+    /// Record every event that passes through `next_event` (heartbeats excepted),
+    /// e.g. to replay the session later with `FileReplaySource`
+    pub fn with_recorder(mut self, recorder: EventRecorder) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    /// The attached recorder, if any
+    pub fn recorder(&self) -> Option<&EventRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Detach the recorder, e.g. to `save` its events or flush its file
+    pub fn take_recorder(&mut self) -> Option<EventRecorder> {
+        self.recorder.take()
+    }
+
+    /// Only hand out events `filter` accepts, e.g. `|e| e.symbol().is_some_and(|s| s.ends_with("USDT"))`
+    /// Rejected events are dropped (and not recorded). Replaces any previous filter,
+    /// and takes effect from the next call to `next_event` - no re-subscribing needed.
+    /// Heartbeats are never filtered
+    pub fn set_filter(&mut self, filter: EventFilter) {
+        self.filter = Some(filter);
+    }
+
+    /// Remove the filter, so every event comes through again
+    pub fn clear_filter(&mut self) {
+        self.filter = None;
+    }
+
+    /// Fan events out to `subscribe_events` receivers, each able to lag `capacity` events behind
+    pub fn with_broadcast(mut self, capacity: usize) -> Self {
+        self.broadcast = Some(broadcast::channel(capacity.max(1)).0);
+        self
+    }
+
+    /// A new receiver getting a copy of every event `next_event` delivers from now on
+    ///
+    /// Events are fanned out as they pass through `next_event`, so whoever owns the
+    /// stream has to keep pulling - a plain `while stream.next_event().await.is_some() {}`
+    /// loop is enough if all the real consumers are subscribers. Filtered-out events and
+    /// heartbeats aren't broadcast. A receiver that falls more than the capacity behind
+    /// (`DEFAULT_BROADCAST_CAPACITY` unless set with `with_broadcast`) loses the oldest
+    /// events: its next `recv` returns `RecvError::Lagged(n)` with the number skipped,
+    /// then carries on from the oldest event still buffered. A slow receiver never
+    /// blocks the stream or the other receivers.
+    pub fn subscribe_events(&mut self) -> broadcast::Receiver<MarketDataEvent> {
+        self.broadcast
+            .get_or_insert_with(|| broadcast::channel(DEFAULT_BROADCAST_CAPACITY).0)
+            .subscribe()
+    }
+
     pub async fn next_event(&mut self) -> Option<MarketDataEvent> {
-        self.event_receiver.recv().await
+        // The heartbeat is due when no event has been *delivered* for a whole interval,
+        // so a stream of filtered-out events doesn't hold it back
+        let heartbeat_at = self.heartbeat_interval.map(|interval| tokio::time::Instant::now() + interval);
+
+        loop {
+            let event = match heartbeat_at {
+                None => self.event_receiver.recv().await?,
+                Some(at) => match tokio::time::timeout_at(at, self.event_receiver.recv()).await {
+                    Ok(event) => event?,
+                    Err(_) => {
+                        return Some(MarketDataEvent::Heartbeat {
+                            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+                        })
+                    }
+                },
+            };
+
+            if self.filter.as_ref().is_some_and(|keep| !keep(&event)) {
+                continue;
+            }
+            if let Some(recorder) = self.recorder.as_mut() {
+                recorder.record(&event);
+            }
+            if let Some(sender) = &self.broadcast {
+                let _ = sender.send(event.clone());  // Only fails when nobody is subscribed
+            }
+            return Some(event);
+        }
+    }
+
+    /// Add a symbol's ticker stream to the live connection and wait for Binance to confirm it
+    /// Errors if Binance rejects the request or doesn't answer within `SUBSCRIBE_TIMEOUT`.
+    /// Confirmed subscriptions are kept across reconnects
+    pub async fn subscribe(&self, symbol: &str) -> Result<(), TradingError> {
+        self.subscribe_with_timeout(symbol, SUBSCRIBE_TIMEOUT).await
+    }
+
+    /// `subscribe` with a custom confirmation timeout
+    pub async fn subscribe_with_timeout(&self, symbol: &str, timeout: Duration) -> Result<(), TradingError> {
+        let streams = vec![format!("{}@ticker", symbol.to_lowercase())];
+        let (reply, confirmation) = oneshot::channel();
+        self.commands
+            .send(StreamCommand::Subscribe { streams, reply })
+            .map_err(|_| "Market data client has stopped")?;
+
+        match tokio::time::timeout(timeout, confirmation).await {
+            Err(_) => Err(format!("No confirmation for {} subscription within {:?}", symbol, timeout).into()),
+            Ok(Err(_)) => Err(format!("Connection closed before {} subscription was confirmed", symbol).into()),
+            Ok(Ok(Err(reason))) => Err(format!("Subscription to {} rejected: {}", symbol, reason).into()),
+            Ok(Ok(Ok(()))) => {
+                info!("Subscribed to {}", symbol);
+                Ok(())
+            }
+        }
     }
-}
\ No newline at end of file
+
+    /// Close the WebSocket cleanly and wait for the background client to finish
+    /// Also stops any pending reconnect; the state ends as `Disconnected`
+    pub async fn shutdown(self) {
+        self.shutdown.notify_one();
+        if let Err(e) = self.client_handle.await {
+            tracing::error!("Market data client task failed: {}", e);
+        }
+        info!("Market data stream shut down");
+    }
+
+    /// Current state of the underlying WebSocket connection
+    pub fn connection_state(&self) -> ConnectionState {
+        *self.state_receiver.borrow()
+    }
+
+    /// WebSocket frames received per second, averaged over the last few seconds
+    /// Counts every frame, including ones the filter drops, so a near-zero rate while
+    /// `Connected` means the feed itself is starved
+    pub fn throughput(&self) -> f64 {
+        self.throughput.rate()
+    }
+
+    /// A watch receiver for the connection state, e.g. to drive a UI indicator
+    /// Use `changed().await` on it to wait for the next transition
+    pub fn watch_connection_state(&self) -> watch::Receiver<ConnectionState> {
+        self.state_receiver.clone()
+    }
+}
+
+impl MarketDataSource for MarketDataStream {
+    async fn next_event(&mut self) -> Option<MarketDataEvent> {
+        MarketDataStream::next_event(self).await
+    }
+}
@@ -0,0 +1,192 @@
+use super::binance::{BinanceClient, StreamCommand, StreamType};
+use super::source::MarketDataSource;
+use super::stream::SUBSCRIBE_TIMEOUT;
+use super::types::*;
+use crate::error::TradingError;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot, Notify};
+use tracing::info;
+
+/// Binance allows at most this many streams on a single WebSocket connection
+pub const MAX_STREAMS_PER_CONNECTION: usize = 1024;
+
+/// Applied to every `BinanceClient` the supervisor creates (base URL, reconnect policy...)
+pub type ClientConfigurator = Box<dyn Fn(BinanceClient) -> BinanceClient + Send + Sync>;
+
+/// Split `symbols` across as few connections as the stream limit allows, balancing the load
+///
+/// Each symbol weighs `streams_per_symbol` streams (one per `StreamType`). The number
+/// of connections is the minimum that fits everything under `max_streams`, and each
+/// symbol goes to the least-loaded one, so the groups end up within one symbol of each
+/// other. Duplicate symbols are only placed once.
+pub fn partition_symbols(symbols: &[String], streams_per_symbol: usize, max_streams: usize) -> Vec<Vec<String>> {
+    let weight = streams_per_symbol.max(1);
+    let per_connection = (max_streams / weight).max(1);  // Symbols that fit on one connection
+
+    let mut unique: Vec<&String> = Vec::new();
+    for symbol in symbols {
+        if !unique.contains(&symbol) {
+            unique.push(symbol);
+        }
+    }
+
+    let connections = unique.len().div_ceil(per_connection);
+    let mut groups: Vec<Vec<String>> = vec![Vec::new(); connections];
+    for symbol in unique {
+        let least_loaded = groups.iter_mut().min_by_key(|group| group.len()).unwrap();
+        least_loaded.push(symbol.clone());
+    }
+    groups
+}
+
+/// One WebSocket connection run by the supervisor
+struct Connection {
+    symbols: Vec<String>,
+    commands: mpsc::UnboundedSender<StreamCommand>,
+    shutdown: Arc<Notify>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+/// Runs as many Binance connections as needed to stay under the per-connection
+/// stream limit, merging everything into one event receiver
+///
+/// Symbols are partitioned with `partition_symbols` at start. `add_symbol` puts a new
+/// symbol on the least-loaded connection that still has room, or opens a new one.
+pub struct StreamSupervisor {
+    connections: Vec<Connection>,
+    stream_types: Vec<StreamType>,
+    max_streams_per_connection: usize,
+    configure: ClientConfigurator,
+    event_sender: mpsc::UnboundedSender<MarketDataEvent>,
+    event_receiver: mpsc::UnboundedReceiver<MarketDataEvent>,
+}
+
+impl StreamSupervisor {
+    /// Ticker streams for `symbols`, split at `max_streams_per_connection`
+    pub fn new(symbols: Vec<String>, max_streams_per_connection: usize) -> Self {
+        Self::with_client(symbols, vec![StreamType::Ticker], max_streams_per_connection, Box::new(|client| client))
+    }
+
+    /// Like `new`, with the stream types per symbol and a hook to configure each client
+    pub fn with_client(
+        symbols: Vec<String>,
+        stream_types: Vec<StreamType>,
+        max_streams_per_connection: usize,
+        configure: ClientConfigurator,
+    ) -> Self {
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let mut supervisor = Self {
+            connections: Vec::new(),
+            stream_types,
+            max_streams_per_connection,
+            configure,
+            event_sender,
+            event_receiver,
+        };
+
+        for group in partition_symbols(&symbols, supervisor.stream_types.len(), max_streams_per_connection) {
+            supervisor.spawn_connection(group);
+        }
+        info!("Started {} market data connection(s) for {} symbols",
+              supervisor.connections.len(), supervisor.symbol_count());
+        supervisor
+    }
+
+    fn spawn_connection(&mut self, symbols: Vec<String>) {
+        let client = (self.configure)(BinanceClient::new(symbols.clone(), self.event_sender.clone()))
+            .with_stream_types(self.stream_types.clone());
+        let commands = client.command_sender();
+        let shutdown = client.shutdown_handle();
+        let index = self.connections.len();
+
+        let handle = tokio::spawn(async move {
+            if let Err(e) = client.start().await {
+                tracing::error!("Binance connection {} error: {}", index, e);
+            }
+        });
+
+        self.connections.push(Connection { symbols, commands, shutdown, handle });
+    }
+
+    /// Streams a connection carries for `symbols`
+    fn load(&self, symbols: usize) -> usize {
+        symbols * self.stream_types.len().max(1)
+    }
+
+    /// Start streaming `symbol` on the least-loaded connection with room, or a new one
+    /// Waits for Binance to confirm when an existing connection is used
+    pub async fn add_symbol(&mut self, symbol: &str) -> Result<(), TradingError> {
+        if self.connections.iter().any(|c| c.symbols.iter().any(|s| s == symbol)) {
+            return Ok(());
+        }
+
+        let weight = self.load(1);
+        let least_loaded = self.connections
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, c)| c.symbols.len() * weight + weight <= self.max_streams_per_connection)
+            .min_by_key(|(_, c)| c.symbols.len());
+
+        let Some((index, connection)) = least_loaded else {
+            info!("All connections are full, opening a new one for {}", symbol);
+            self.spawn_connection(vec![symbol.to_string()]);
+            return Ok(());
+        };
+
+        let streams = self.stream_types.iter().map(|t| t.stream_name(symbol)).collect();
+        let (reply, confirmation) = oneshot::channel();
+        connection.commands
+            .send(StreamCommand::Subscribe { streams, reply })
+            .map_err(|_| format!("Market data connection {} has stopped", index))?;
+
+        match tokio::time::timeout(SUBSCRIBE_TIMEOUT, confirmation).await {
+            Err(_) => Err(format!("No confirmation for {} subscription within {:?}", symbol, SUBSCRIBE_TIMEOUT).into()),
+            Ok(Err(_)) => Err(format!("Connection closed before {} subscription was confirmed", symbol).into()),
+            Ok(Ok(Err(reason))) => Err(format!("Subscription to {} rejected: {}", symbol, reason).into()),
+            Ok(Ok(Ok(()))) => {
+                connection.symbols.push(symbol.to_string());
+                info!("Subscribed to {} on connection {}", symbol, index);
+                Ok(())
+            }
+        }
+    }
+
+    /// Next event from any of the connections
+    pub async fn next_event(&mut self) -> Option<MarketDataEvent> {
+        self.event_receiver.recv().await
+    }
+
+    /// Number of open connections
+    pub fn connection_count(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Streams carried by each connection, in the order they were opened
+    pub fn connection_loads(&self) -> Vec<usize> {
+        self.connections.iter().map(|c| self.load(c.symbols.len())).collect()
+    }
+
+    /// Total number of symbols across all connections
+    pub fn symbol_count(&self) -> usize {
+        self.connections.iter().map(|c| c.symbols.len()).sum()
+    }
+
+    /// Close every connection cleanly and wait for them to finish
+    pub async fn shutdown(self) {
+        for connection in &self.connections {
+            connection.shutdown.notify_one();
+        }
+        for (index, connection) in self.connections.into_iter().enumerate() {
+            if let Err(e) = connection.handle.await {
+                tracing::error!("Market data connection {} failed: {}", index, e);
+            }
+        }
+        info!("Stream supervisor shut down");
+    }
+}
+
+impl MarketDataSource for StreamSupervisor {
+    async fn next_event(&mut self) -> Option<MarketDataEvent> {
+        StreamSupervisor::next_event(self).await
+    }
+}
@@ -0,0 +1,73 @@
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Window the frame rate is averaged over unless configured otherwise
+pub const DEFAULT_THROUGHPUT_WINDOW: Duration = Duration::from_secs(5);
+
+#[derive(Debug)]
+struct Window {
+    started: Instant,
+    frames: u64,
+    last_rate: Option<f64>,  // Frames/sec of the last completed window
+}
+
+/// Frames-per-second counter for a WebSocket feed
+///
+/// Counts frames in fixed windows and resets at the end of each one, so recording
+/// a frame is just an increment. `rate` reports the last completed window, or the
+/// current one while the first is still running. A feed that goes quiet drops to
+/// 0 after one empty window.
+#[derive(Debug)]
+pub struct ThroughputCounter {
+    window: Duration,
+    state: Mutex<Window>,
+}
+
+impl ThroughputCounter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window: window.max(Duration::from_millis(1)),
+            state: Mutex::new(Window { started: Instant::now(), frames: 0, last_rate: None }),
+        }
+    }
+
+    /// Count one received frame
+    pub fn record(&self) {
+        let mut state = self.state.lock().unwrap();
+        self.roll_over(&mut state, Instant::now());
+        state.frames += 1;
+    }
+
+    /// Frames per second
+    pub fn rate(&self) -> f64 {
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        self.roll_over(&mut state, now);
+        state.last_rate.unwrap_or_else(|| {
+            let elapsed = now.duration_since(state.started).as_secs_f64();
+            if elapsed > 0.0 { state.frames as f64 / elapsed } else { 0.0 }
+        })
+    }
+
+    /// Close the window if it's over; a gap of several windows counts as silence
+    fn roll_over(&self, state: &mut Window, now: Instant) {
+        let elapsed = now.duration_since(state.started);
+        if elapsed < self.window {
+            return;
+        }
+        state.last_rate = Some(if elapsed < self.window * 2 {
+            state.frames as f64 / elapsed.as_secs_f64()
+        } else {
+            0.0  // At least one whole window without a single frame
+        });
+        state.frames = 0;
+        state.started = now;
+    }
+}
+
+impl Default for ThroughputCounter {
+    fn default() -> Self {
+        Self::new(DEFAULT_THROUGHPUT_WINDOW)
+    }
+}
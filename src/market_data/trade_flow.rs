@@ -0,0 +1,130 @@
+use super::types::*;
+use crate::trading::OrderSide;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Orders further than this from the best price (as a fraction, 0.005 = 0.5%) get no fill estimate
+pub const MAX_FILL_ESTIMATE_DISTANCE: f64 = 0.005;
+
+/// Each this much distance from the best price (0.1%) adds one order's worth of volume to wait for
+const FILL_DISTANCE_STEP: f64 = 0.001;
+
+/// Rolling buy vs sell aggressor volume for one symbol
+///
+/// Each trade counts toward the side that took liquidity (`Trade::side`). Trades
+/// older than the window, measured from the newest trade seen, drop out, so
+/// replayed data aggregates exactly like live data.
+pub struct TradeFlow {
+    symbol: String,
+    window: Duration,
+    trades: VecDeque<(u64, TradeSide, f64)>,  // (timestamp ms, aggressor, base quantity), sorted oldest first
+    buy_volume: f64,
+    sell_volume: f64,
+    newest: u64,  // Latest trade time seen; the window ends here
+}
+
+impl TradeFlow {
+    pub fn new(symbol: &str, window: Duration) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            window,
+            trades: VecDeque::new(),
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            newest: 0,
+        }
+    }
+
+    /// Add a trade for our symbol; trades for other symbols are ignored
+    pub fn on_trade(&mut self, trade: &Trade) {
+        if trade.symbol != self.symbol {
+            return;
+        }
+
+        match trade.side {
+            TradeSide::Buy => self.buy_volume += trade.quantity,
+            TradeSide::Sell => self.sell_volume += trade.quantity,
+        }
+        // Keep the queue in timestamp order even when trades arrive out of order,
+        // so eviction from the front removes exactly the trades outside the window
+        let index = self.trades.partition_point(|(timestamp, ..)| *timestamp <= trade.timestamp);
+        self.trades.insert(index, (trade.timestamp, trade.side.clone(), trade.quantity));
+
+        self.newest = self.newest.max(trade.timestamp);
+        let cutoff = self.newest.saturating_sub(self.window.as_millis() as u64);
+        while let Some((timestamp, side, quantity)) = self.trades.front() {
+            if *timestamp >= cutoff {
+                break;
+            }
+            match side {
+                TradeSide::Buy => self.buy_volume -= quantity,
+                TradeSide::Sell => self.sell_volume -= quantity,
+            }
+            self.trades.pop_front();
+        }
+    }
+
+    /// Same as `on_trade` for a stream event; other events are ignored
+    pub fn on_event(&mut self, event: &MarketDataEvent) {
+        if let MarketDataEvent::Trade(trade) = event {
+            self.on_trade(trade);
+        }
+    }
+
+    /// Volume bought by aggressors (takers lifting the ask) within the window
+    pub fn buy_volume(&self) -> f64 {
+        self.buy_volume.max(0.0)
+    }
+
+    /// Volume sold by aggressors (takers hitting the bid) within the window
+    pub fn sell_volume(&self) -> f64 {
+        self.sell_volume.max(0.0)
+    }
+
+    /// Buy minus sell aggressor volume; positive means net buying pressure
+    pub fn delta(&self) -> f64 {
+        self.buy_volume() - self.sell_volume()
+    }
+
+    /// Share of aggressor volume that was buying, from 0.0 (all sells) to 1.0 (all buys)
+    /// None while the window is empty
+    pub fn ratio(&self) -> Option<f64> {
+        let total = self.buy_volume() + self.sell_volume();
+        if total > 0.0 { Some(self.buy_volume() / total) } else { None }
+    }
+
+    /// Number of trades in the window
+    pub fn trade_count(&self) -> usize {
+        self.trades.len()
+    }
+
+    /// Rough time until a resting limit order fills, from the recent aggressor volume
+    ///
+    /// A heuristic, not a queue model. It assumes:
+    /// - only aggressors on the other side fill us (sells hit a resting buy, buys lift a sell),
+    ///   at the average rate they traded over the window, which is taken to be full;
+    /// - an order at or through `best_price` (the best bid for a buy, best ask for a sell)
+    ///   needs `quantity` of that volume - queue position ahead of us is ignored;
+    /// - an order behind the best price must wait for the market to come to it, which
+    ///   adds one `quantity` of volume per `FILL_DISTANCE_STEP` of distance.
+    ///
+    /// None when the order is more than `MAX_FILL_ESTIMATE_DISTANCE` away from the best
+    /// price, or when nothing traded on the filling side within the window.
+    pub fn time_to_fill(&self, side: OrderSide, price: f64, quantity: f64, best_price: f64) -> Option<Duration> {
+        if best_price <= 0.0 {
+            return None;
+        }
+        let (behind, filling_volume) = match side {
+            OrderSide::Buy => (best_price - price, self.sell_volume()),
+            OrderSide::Sell => (price - best_price, self.buy_volume()),
+        };
+        let distance = behind.max(0.0) / best_price;
+        if distance > MAX_FILL_ESTIMATE_DISTANCE || filling_volume <= 0.0 {
+            return None;
+        }
+
+        let volume_per_second = filling_volume / self.window.as_secs_f64();
+        let needed = quantity * (1.0 + distance / FILL_DISTANCE_STEP);
+        Some(Duration::from_secs_f64(needed / volume_per_second))
+    }
+}
@@ -1,13 +1,57 @@
+use crate::trading::OrderSide;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Ticker {
     pub symbol: String,
     pub price: f64,
     pub volume: f64,
+    #[serde(default)]
+    pub quote_volume: f64,  // 24h volume in the quote asset (e.g. USDT for BTCUSDT)
     pub timestamp: u64,
 }
 
+impl Ticker {
+    /// Event time as a UTC DateTime
+    pub fn datetime(&self) -> DateTime<Utc> {
+        millis_to_datetime(self.timestamp)
+    }
+
+    /// One-line summary with fixed decimals, e.g. "BTCUSDT | Price: 45000.12 | Volume: 1234.57"
+    pub fn format(&self, price_decimals: usize, volume_decimals: usize) -> String {
+        format!(
+            "{} | Price: {:.*} | Volume: {:.*}",
+            self.symbol, price_decimals, self.price, volume_decimals, self.volume
+        )
+    }
+}
+
+/// Shows the price and volume with `display_decimals` each, so cheap symbols don't print as 0.00
+impl std::fmt::Display for Ticker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.format(display_decimals(self.price), display_decimals(self.volume)))
+    }
+}
+
+/// Decimals that show about five significant digits of `value`, between 2 and 8
+/// 45000.12 -> 2, 1.23456 -> 4, 0.00001234 -> 8
+pub fn display_decimals(value: f64) -> usize {
+    if value == 0.0 || !value.is_finite() {
+        return 2;
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    (4 - magnitude).clamp(2, 8) as usize
+}
+
+/// Binance timestamps are milliseconds since the Unix epoch
+/// Out-of-range values fall back to the epoch rather than panicking
+fn millis_to_datetime(millis: u64) -> DateTime<Utc> {
+    DateTime::from_timestamp_millis(millis as i64).unwrap_or_default()
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct OrderBookLevel {
     pub price: f64,
@@ -22,6 +66,78 @@ pub struct OrderBook {
     pub timestamp: u64,
 }
 
+/// Changes to the order book from the `<symbol>@depth` stream - not a full book
+/// A level with quantity 0 is removed. Apply the updates in order on top of a REST
+/// snapshot whose lastUpdateId is below `first_update_id`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthUpdate {
+    pub symbol: String,
+    pub first_update_id: u64,  // `U`: first update covered by this event
+    pub final_update_id: u64,  // `u`: last update covered by this event
+    pub bids: Vec<OrderBookLevel>,
+    pub asks: Vec<OrderBookLevel>,
+    pub timestamp: u64,
+}
+
+/// Result of walking the order book for a hypothetical market order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillEstimate {
+    pub average_price: f64,     // Volume-weighted average fill price
+    pub worst_price: f64,       // Price of the last (deepest) level touched
+    pub filled_quantity: f64,   // How much of the requested quantity the book could absorb
+    pub fully_filled: bool,     // False if the visible book wasn't deep enough
+}
+
+impl OrderBook {
+    /// Estimate the fill of a market order by walking the book:
+    /// buys consume asks from the lowest price up, sells consume bids from the highest down.
+    /// Returns None if the relevant side is empty or the quantity isn't positive.
+    /// If the book runs out first, the estimate covers what was available and
+    /// `fully_filled` is false.
+    pub fn estimate_fill(&self, side: OrderSide, quantity: f64) -> Option<FillEstimate> {
+        if quantity <= 0.0 {
+            return None;
+        }
+
+        // Walk best-to-worst regardless of how the levels were stored
+        let mut levels: Vec<&OrderBookLevel> = match side {
+            OrderSide::Buy => self.asks.iter().collect(),
+            OrderSide::Sell => self.bids.iter().collect(),
+        };
+        match side {
+            OrderSide::Buy => levels.sort_by(|a, b| a.price.total_cmp(&b.price)),
+            OrderSide::Sell => levels.sort_by(|a, b| b.price.total_cmp(&a.price)),
+        }
+
+        let mut remaining = quantity;
+        let mut cost = 0.0;
+        let mut worst_price = None;
+
+        for level in levels {
+            if remaining <= 0.0 {
+                break;
+            }
+            let take = remaining.min(level.quantity);
+            if take <= 0.0 {
+                continue;
+            }
+            cost += take * level.price;
+            remaining -= take;
+            worst_price = Some(level.price);
+        }
+
+        let worst_price = worst_price?;
+        let filled_quantity = quantity - remaining.max(0.0);
+
+        Some(FillEstimate {
+            average_price: cost / filled_quantity,
+            worst_price,
+            filled_quantity,
+            fully_filled: remaining <= 1e-12,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Trade {
     pub symbol: String,
@@ -31,16 +147,140 @@ pub struct Trade {
     pub timestamp: u64,
 }
 
+impl Trade {
+    /// Trade time as a UTC DateTime
+    pub fn datetime(&self) -> DateTime<Utc> {
+        millis_to_datetime(self.timestamp)
+    }
+}
+
+/// One candlestick (OHLCV bar)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Kline {
+    pub symbol: String,
+    pub interval: String,   // e.g. "1m", "15m", "1h"
+    pub open_time: u64,     // Milliseconds
+    pub close_time: u64,    // Milliseconds (last ms of the candle)
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,        // Base asset volume
+    #[serde(default)]
+    pub quote_volume: f64,  // Quote asset volume
+    #[serde(default)]
+    pub trade_count: u64,   // Number of trades in the candle
+    #[serde(default)]
+    pub taker_buy_base: f64,   // Base volume bought by aggressive (taker) buyers
+    #[serde(default)]
+    pub taker_buy_quote: f64,  // Quote volume of those taker buys
+    pub is_closed: bool,    // False while the candle is still forming
+}
+
+impl Kline {
+    /// Candle open time as a UTC DateTime
+    pub fn datetime(&self) -> DateTime<Utc> {
+        millis_to_datetime(self.open_time)
+    }
+
+    /// Candle close time as a UTC DateTime
+    pub fn close_datetime(&self) -> DateTime<Utc> {
+        millis_to_datetime(self.close_time)
+    }
+
+    /// Parse one row of `/api/v3/klines`, a positional array:
+    /// `[openTime, open, high, low, close, volume, closeTime, quoteAssetVolume,
+    ///   numberOfTrades, takerBuyBaseVolume, takerBuyQuoteVolume, ignore]`
+    /// Prices and volumes come as strings, but plain numbers are accepted too. The
+    /// trailing placeholder (and anything Binance might append later) is ignored.
+    /// The REST response doesn't say whether a candle is final, so `is_closed` is
+    /// whether `close_time` has passed by `now_ms`
+    pub fn from_rest_row(symbol: &str, interval: &str, row: &[Value], now_ms: u64) -> Result<Kline, String> {
+        if row.len() < 11 {
+            return Err(format!("Kline row has {} fields, expected at least 11", row.len()));
+        }
+        let number = |index: usize, name: &str| -> Result<f64, String> {
+            match &row[index] {
+                Value::String(s) => s.parse().ok(),
+                value => value.as_f64(),
+            }
+            .ok_or_else(|| format!("Kline field {} ({}) is not a number: {}", index, name, row[index]))
+        };
+        let integer = |index: usize, name: &str| -> Result<u64, String> {
+            row[index].as_u64()
+                .ok_or_else(|| format!("Kline field {} ({}) is not an integer: {}", index, name, row[index]))
+        };
+
+        let close_time = integer(6, "closeTime")?;
+        Ok(Kline {
+            symbol: symbol.to_string(),
+            interval: interval.to_string(),
+            open_time: integer(0, "openTime")?,
+            close_time,
+            open: number(1, "open")?,
+            high: number(2, "high")?,
+            low: number(3, "low")?,
+            close: number(4, "close")?,
+            volume: number(5, "volume")?,
+            quote_volume: number(7, "quoteAssetVolume")?,
+            trade_count: integer(8, "numberOfTrades")?,
+            taker_buy_base: number(9, "takerBuyBaseVolume")?,
+            taker_buy_quote: number(10, "takerBuyQuoteVolume")?,
+            is_closed: close_time < now_ms,
+        })
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TradeSide {
     Buy,
     Sell,
 }
 
-#[derive(Debug, Clone)]
+/// State of the WebSocket connection behind a market data stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConnectionState {
+    Connecting,    // Initial handshake in progress
+    Connected,     // Receiving data
+    Reconnecting,  // Connection dropped, waiting for/attempting a new one
+    Disconnected,  // Stopped for good - no more data will arrive
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum MarketDataEvent {
     Ticker(Ticker),
+    /// A full book: the top levels from the `<symbol>@depth<levels>@100ms` stream
     OrderBook(OrderBook),
+    /// Changed levels from the `<symbol>@depth` stream (see `DepthUpdate`)
+    DepthUpdate(DepthUpdate),
     Trade(Trade),
+    Kline(Kline),
+    ConnectionStateChanged(ConnectionState),
     Error(String),
-}
\ No newline at end of file
+    /// Best bid/ask from the `<symbol>@bookTicker` stream, sent on every top-of-book change
+    BookTicker { symbol: String, bid: f64, bid_qty: f64, ask: f64, ask_qty: f64 },
+    /// No real event arrived within the configured heartbeat interval
+    /// Only emitted when enabled with `MarketDataStream::with_heartbeat`
+    Heartbeat { timestamp: u64 },  // Milliseconds since the Unix epoch
+    /// The connection dropped and reconnect attempt `attempt` starts after `delay`
+    /// Sent for every attempt, unlike `ConnectionStateChanged` which only fires once
+    Reconnecting { attempt: u32, delay: Duration },
+}
+
+impl MarketDataEvent {
+    /// The symbol the event is about; None for connection, error and heartbeat events
+    pub fn symbol(&self) -> Option<&str> {
+        match self {
+            MarketDataEvent::Ticker(ticker) => Some(&ticker.symbol),
+            MarketDataEvent::OrderBook(book) => Some(&book.symbol),
+            MarketDataEvent::DepthUpdate(update) => Some(&update.symbol),
+            MarketDataEvent::Trade(trade) => Some(&trade.symbol),
+            MarketDataEvent::Kline(kline) => Some(&kline.symbol),
+            MarketDataEvent::BookTicker { symbol, .. } => Some(symbol),
+            MarketDataEvent::ConnectionStateChanged(_)
+            | MarketDataEvent::Error(_)
+            | MarketDataEvent::Heartbeat { .. }
+            | MarketDataEvent::Reconnecting { .. } => None,
+        }
+    }
+}
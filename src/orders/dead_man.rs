@@ -0,0 +1,103 @@
+use crate::trading::TestnetTrader;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
+
+/// Timeout to use when the bot has no better idea of its own loop period
+pub const DEFAULT_DEAD_MAN_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Client-side dead man's switch: cancels every open order on the tracked symbols
+/// when `heartbeat` hasn't been called for `timeout`
+///
+/// Binance spot has no server-side equivalent, so this runs as a background task.
+/// It protects against a hung main loop (the task keeps running while the loop is
+/// stuck), not against the whole process dying. After firing it stays quiet until
+/// the next heartbeat re-arms it. Dropping the switch disarms it without firing.
+pub struct DeadMansSwitch {
+    kicks: watch::Sender<Instant>,
+    symbols: Arc<Mutex<Vec<String>>>,
+    triggered: Arc<AtomicU32>,
+    task: JoinHandle<()>,
+}
+
+impl DeadMansSwitch {
+    /// Arm the switch now for `symbols`; the first heartbeat is due within `timeout`
+    pub fn start(trader: Arc<TestnetTrader>, symbols: Vec<String>, timeout: Duration) -> Self {
+        let (kicks, receiver) = watch::channel(Instant::now());
+        let symbols = Arc::new(Mutex::new(symbols));
+        let triggered = Arc::new(AtomicU32::new(0));
+        let task = tokio::spawn(Self::run(trader, symbols.clone(), timeout, receiver, triggered.clone()));
+        info!("Dead man's switch armed with a {:?} timeout", timeout);
+        Self { kicks, symbols, triggered, task }
+    }
+
+    /// Prove the bot is alive; resets the countdown (and re-arms after firing)
+    pub fn heartbeat(&self) {
+        self.kicks.send_replace(Instant::now());
+    }
+
+    /// Also cancel orders on `symbol` when the switch fires
+    pub fn track_symbol(&self, symbol: &str) {
+        let mut symbols = self.symbols.lock().unwrap();
+        if !symbols.iter().any(|s| s == symbol) {
+            symbols.push(symbol.to_string());
+        }
+    }
+
+    /// Symbols whose orders are canceled when the switch fires
+    pub fn symbols(&self) -> Vec<String> {
+        self.symbols.lock().unwrap().clone()
+    }
+
+    /// How many times the switch has fired
+    pub fn trigger_count(&self) -> u32 {
+        self.triggered.load(Ordering::SeqCst)
+    }
+
+    async fn run(
+        trader: Arc<TestnetTrader>,
+        symbols: Arc<Mutex<Vec<String>>>,
+        timeout: Duration,
+        mut kicks: watch::Receiver<Instant>,
+        triggered: Arc<AtomicU32>,
+    ) {
+        loop {
+            let deadline = *kicks.borrow_and_update() + timeout;
+            tokio::select! {
+                kicked = kicks.changed() => {
+                    if kicked.is_err() {
+                        return;  // Switch dropped
+                    }
+                    continue;
+                }
+                _ = tokio::time::sleep_until(deadline) => {}
+            }
+
+            warn!("No heartbeat for {:?}, canceling all open orders", timeout);
+            let tracked = symbols.lock().unwrap().clone();
+            for symbol in tracked {
+                match trader.cancel_all_orders(&symbol).await {
+                    Ok(canceled) => warn!("Dead man's switch canceled {} order(s) on {}", canceled.len(), symbol),
+                    Err(e) => error!("Dead man's switch failed to cancel orders on {}: {}", symbol, e),
+                }
+            }
+            triggered.fetch_add(1, Ordering::SeqCst);
+
+            // Stay quiet until the bot shows signs of life again
+            if kicks.changed().await.is_err() {
+                return;
+            }
+            info!("Heartbeat received, dead man's switch re-armed");
+        }
+    }
+}
+
+impl Drop for DeadMansSwitch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
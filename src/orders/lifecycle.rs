@@ -0,0 +1,62 @@
+use crate::trading::{CancelOutcome, NewOrderRequest, OrderResponse, OrderStatus, TestnetTrader};
+use crate::error::TradingError;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Longest wait between status polls while managing an order
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Place an order and see it through to the end:
+/// wait up to `fill_timeout` for it to fill, cancel whatever is left if it doesn't,
+/// and call `on_fill` or `on_timeout` with the final order state.
+///
+/// On timeout the order passed to `on_timeout` is the cancel response, so
+/// `executed_qty` shows any partial fill. Orders that end some other way
+/// (rejected, expired, canceled elsewhere) call neither callback.
+/// Returns the final order state.
+pub async fn place_and_manage<F, T>(
+    trader: &TestnetTrader,
+    order: &NewOrderRequest,
+    fill_timeout: Duration,
+    on_fill: F,
+    on_timeout: T,
+) -> Result<OrderResponse, TradingError>
+where
+    F: FnOnce(&OrderResponse),
+    T: FnOnce(&OrderResponse),
+{
+    let placed = trader.place_order(order).await?;
+
+    // Market orders (and dry-run orders) usually come back already filled
+    let current = if placed.status.is_final() {
+        placed
+    } else {
+        let poll_interval = (fill_timeout / 4).min(MAX_POLL_INTERVAL);
+        trader.wait_for_order(&order.symbol, placed.order_id, fill_timeout, poll_interval).await?
+    };
+
+    if current.status.is_final() {
+        if current.status == OrderStatus::Filled {
+            on_fill(&current);
+        } else {
+            warn!("Order {} ended as {:?} without filling", current.order_id, current.status);
+        }
+        return Ok(current);
+    }
+
+    info!("Order {} not filled after {:?}, canceling", current.order_id, fill_timeout);
+    match trader.cancel_order_if_open(&order.symbol, current.order_id).await? {
+        CancelOutcome::Canceled(canceled) => {
+            on_timeout(&canceled);
+            Ok(canceled)
+        }
+        CancelOutcome::AlreadyGone => {
+            // Filled (or otherwise closed) between the last poll and the cancel
+            let last = trader.get_order(&order.symbol, current.order_id).await?;
+            if last.status == OrderStatus::Filled {
+                on_fill(&last);
+            }
+            Ok(last)
+        }
+    }
+}
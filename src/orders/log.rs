@@ -0,0 +1,103 @@
+use crate::trading::OrderResponse;
+use serde_json::json;
+use std::path::Path;
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::error;
+
+/// How often buffered entries are written out even if nobody calls `flush`
+const FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+enum LogCommand {
+    Entry(String),
+    Flush(oneshot::Sender<std::io::Result<()>>),
+}
+
+/// Append-only JSON-lines log of orders, written by a background task
+///
+/// `record` only queues the entry on a channel, so placing orders never waits
+/// on disk I/O. The background task batches writes through a buffer and flushes
+/// every second. Call `flush().await` for a durability point and
+/// `shutdown().await` before exit. If the log is simply dropped, the task still
+/// writes out whatever was queued as long as the runtime keeps running.
+pub struct OrderLog {
+    sender: mpsc::UnboundedSender<LogCommand>,
+    writer_task: Option<JoinHandle<()>>,
+}
+
+impl OrderLog {
+    /// Open (or create) the log file for appending and start the writer task
+    pub async fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path).await?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let writer_task = tokio::spawn(Self::run_writer(BufWriter::new(file), receiver));
+        Ok(Self { sender, writer_task: Some(writer_task) })
+    }
+
+    /// Queue an order for the log (never blocks)
+    /// `event` says what happened, e.g. "placed" or "canceled"
+    pub fn record(&self, event: &str, order: &OrderResponse) {
+        let line = json!({
+            "logged_at": chrono::Utc::now().timestamp_millis(),
+            "event": event,
+            "order": order,
+        }).to_string();
+        if self.sender.send(LogCommand::Entry(line)).is_err() {
+            error!("Order log writer has stopped; dropping entry for order {}", order.order_id);
+        }
+    }
+
+    /// Wait until everything recorded so far is written and flushed to the file
+    pub async fn flush(&self) -> std::io::Result<()> {
+        let (done, result) = oneshot::channel();
+        self.sender.send(LogCommand::Flush(done)).map_err(|_| Self::writer_gone())?;
+        result.await.map_err(|_| Self::writer_gone())?
+    }
+
+    /// Flush and stop the writer task
+    pub async fn shutdown(self) -> std::io::Result<()> {
+        let result = self.flush().await;
+        // Dropping the only sender ends the writer loop
+        let OrderLog { sender, writer_task } = self;
+        drop(sender);
+        if let Some(task) = writer_task {
+            let _ = task.await;
+        }
+        result
+    }
+
+    fn writer_gone() -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::BrokenPipe, "order log writer has stopped")
+    }
+
+    async fn run_writer(mut writer: BufWriter<tokio::fs::File>, mut receiver: mpsc::UnboundedReceiver<LogCommand>) {
+        let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            tokio::select! {
+                command = receiver.recv() => match command {
+                    Some(LogCommand::Entry(line)) => {
+                        if let Err(e) = writer.write_all(format!("{}\n", line).as_bytes()).await {
+                            error!("Failed to write order log entry: {}", e);
+                        }
+                    }
+                    Some(LogCommand::Flush(done)) => {
+                        let _ = done.send(writer.flush().await);
+                    }
+                    None => break,  // Every sender is gone: log was shut down or dropped
+                },
+                _ = ticker.tick() => {
+                    if let Err(e) = writer.flush().await {
+                        error!("Failed to flush order log: {}", e);
+                    }
+                }
+            }
+        }
+
+        // Best effort for entries still buffered when the log was dropped
+        if let Err(e) = writer.flush().await {
+            error!("Failed to flush order log on close: {}", e);
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use crate::trading::{AccountTrade, OrderId, OrderResponse};
+use std::collections::HashMap;
+
+/// Free-form labels kept with an order, e.g. {"strategy": "momentum", "purpose": "entry"}
+pub type OrderTags = HashMap<String, String>;
+
+/// An order as last seen, plus its local tags
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    pub order: OrderResponse,
+    pub tags: OrderTags,
+}
+
+/// Local record of the bot's orders, with metadata that is never sent to Binance
+///
+/// Tags say which strategy placed an order and why, so fills can be attributed
+/// back without encoding anything in the clientOrderId. Recording an order again
+/// (e.g. after a status update) keeps its tags.
+#[derive(Debug, Default)]
+pub struct OrderManager {
+    orders: HashMap<OrderId, TrackedOrder>,
+}
+
+impl OrderManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `order`, replacing an older copy of it
+    /// `tags` are added to the ones it already has (same key = new value wins)
+    pub fn record(&mut self, order: OrderResponse, tags: Option<OrderTags>) {
+        let tracked = self.orders.entry(order.order_id).or_insert_with(|| TrackedOrder {
+            order: order.clone(),
+            tags: OrderTags::new(),
+        });
+        tracked.order = order;
+        if let Some(tags) = tags {
+            tracked.tags.extend(tags);
+        }
+    }
+
+    /// The latest recorded copy of an order
+    pub fn get(&self, order_id: OrderId) -> Option<&OrderResponse> {
+        self.orders.get(&order_id).map(|tracked| &tracked.order)
+    }
+
+    /// All tags of an order; None if the order was never recorded
+    pub fn tags(&self, order_id: OrderId) -> Option<&OrderTags> {
+        self.orders.get(&order_id).map(|tracked| &tracked.tags)
+    }
+
+    /// One tag of an order
+    pub fn tag(&self, order_id: OrderId, key: &str) -> Option<&str> {
+        self.tags(order_id)?.get(key).map(String::as_str)
+    }
+
+    /// Tags of the order a fill belongs to, to attribute it to a strategy
+    pub fn tags_for_trade(&self, trade: &AccountTrade) -> Option<&OrderTags> {
+        self.tags(trade.order_id)
+    }
+
+    /// Orders whose tag `key` is `value`, e.g. everything the "momentum" strategy placed
+    pub fn orders_tagged(&self, key: &str, value: &str) -> Vec<&OrderResponse> {
+        let mut orders: Vec<&OrderResponse> = self.orders.values()
+            .filter(|tracked| tracked.tags.get(key).is_some_and(|v| v == value))
+            .map(|tracked| &tracked.order)
+            .collect();
+        orders.sort_by_key(|order| order.order_id);
+        orders
+    }
+
+    /// Stop tracking an order, returning what was stored for it
+    pub fn remove(&mut self, order_id: OrderId) -> Option<TrackedOrder> {
+        self.orders.remove(&order_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
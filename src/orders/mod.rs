@@ -1 +1,12 @@
 // Order management module
+pub mod dead_man;
+pub mod lifecycle;
+pub mod log;
+pub mod manager;
+pub mod quotes;
+
+pub use dead_man::{DeadMansSwitch, DEFAULT_DEAD_MAN_TIMEOUT};
+pub use lifecycle::place_and_manage;
+pub use log::OrderLog;
+pub use manager::{OrderManager, OrderTags, TrackedOrder};
+pub use quotes::{refresh_quotes, Quote};
@@ -0,0 +1,58 @@
+use crate::trading::{CancelOutcome, OrderResponse, OrderSide, TestnetTrader};
+use crate::error::TradingError;
+use tracing::info;
+
+/// A resting limit order we want to have on the book
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub side: OrderSide,
+    pub price: f64,
+    pub quantity: f64,
+}
+
+// Float slack when comparing an order's remaining size with the quote's
+const QUANTITY_EPSILON: f64 = 1e-9;
+
+impl Quote {
+    /// True if an open order already represents this quote: same side, price within
+    /// `tolerance`, and no more left to fill than the quote's quantity
+    fn matches(&self, order: &OrderResponse, tolerance: f64) -> bool {
+        let price: f64 = order.price.parse().unwrap_or(f64::NAN);
+        order.side == self.side.as_str()
+            && (price - self.price).abs() <= tolerance
+            && order.normalize().is_ok_and(|o| o.remaining_qty() <= self.quantity + QUANTITY_EPSILON)
+    }
+}
+
+/// Make the open orders on `symbol` match `desired`
+///
+/// Open orders that no desired quote matches (same side, price within
+/// `price_tolerance`, remaining size no larger than the quote) are canceled, and
+/// desired quotes without a matching open order are placed as limit orders. Quotes that are already on the book are
+/// left alone so they keep their queue position.
+/// Returns the newly placed orders.
+pub async fn refresh_quotes(
+    trader: &TestnetTrader,
+    symbol: &str,
+    desired: &[Quote],
+    price_tolerance: f64,
+) -> Result<Vec<OrderResponse>, TradingError> {
+    let open_orders = trader.get_open_orders(Some(symbol)).await?;
+
+    for order in &open_orders {
+        if !desired.iter().any(|q| q.matches(order, price_tolerance)) {
+            if let CancelOutcome::AlreadyGone = trader.cancel_order_if_open(symbol, order.order_id).await? {
+                info!("Stale quote {} already gone", order.order_id);
+            }
+        }
+    }
+
+    let mut placed = Vec::new();
+    for quote in desired {
+        if open_orders.iter().any(|o| quote.matches(o, price_tolerance)) {
+            continue;
+        }
+        placed.push(trader.place_limit_order(symbol, quote.side, quote.quantity, quote.price).await?);
+    }
+    Ok(placed)
+}
@@ -0,0 +1,50 @@
+use serde::Serialize;
+use serde_json::json;
+
+/// Environment variable that switches the binaries to JSON output (`TRADING_OUTPUT=json`)
+pub const OUTPUT_ENV_VAR: &str = "TRADING_OUTPUT";
+
+/// How the binaries print their results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    Human,  // Decorated text for a terminal
+    Json,   // One JSON object per line, for scripts scraping stdout
+}
+
+impl OutputMode {
+    /// JSON if `--json` was passed or `TRADING_OUTPUT=json` is set, otherwise human text
+    pub fn detect() -> Self {
+        let flag = std::env::args().any(|arg| arg == "--json");
+        let env = std::env::var(OUTPUT_ENV_VAR)
+            .map(|v| v.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+        if flag || env { OutputMode::Json } else { OutputMode::Human }
+    }
+
+    pub fn is_json(&self) -> bool {
+        *self == OutputMode::Json
+    }
+
+    /// Set up tracing so logs never mix with JSON lines on stdout
+    pub fn init_logging(&self) {
+        match self {
+            OutputMode::Human => tracing_subscriber::fmt::init(),
+            OutputMode::Json => tracing_subscriber::fmt().with_writer(std::io::stderr).init(),
+        }
+    }
+}
+
+/// Format one output line: `{"type": <kind>, "data": <data>}`
+pub fn json_line<T: Serialize>(kind: &str, data: &T) -> String {
+    json!({ "type": kind, "data": data }).to_string()
+}
+
+/// Print one JSON line to stdout
+pub fn emit_json<T: Serialize>(kind: &str, data: &T) {
+    println!("{}", json_line(kind, data));
+}
+
+/// Print an error as a JSON line, e.g. `{"type":"error","data":{"message":"..."}}`
+pub fn emit_json_error(message: &str) {
+    emit_json("error", &json!({ "message": message }));
+}
@@ -0,0 +1,57 @@
+// Peak-to-trough drawdown on an equity series, for backtest reports and live risk checks.
+
+/// Largest peak-to-trough drop in `equity`, as a fraction of the peak (0.25 = 25%)
+///
+/// Returns 0.0 for an empty or never-falling series. Non-positive peaks are
+/// skipped since a fraction of them is meaningless.
+pub fn max_drawdown(equity: &[f64]) -> f64 {
+    let mut tracker = DrawdownTracker::new();
+    for &value in equity {
+        tracker.update(value);
+    }
+    tracker.max_drawdown()
+}
+
+/// Tracks drawdown incrementally as equity points arrive
+#[derive(Debug, Clone, Default)]
+pub struct DrawdownTracker {
+    peak: Option<f64>,  // Highest equity seen so far
+    current_drawdown: f64,  // Drop from the peak to the latest point
+    max_drawdown: f64,  // Worst drawdown seen so far
+}
+
+impl DrawdownTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed the next equity point and return the current drawdown
+    pub fn update(&mut self, equity: f64) -> f64 {
+        let peak = match self.peak {
+            Some(peak) if peak >= equity => peak,
+            _ => {
+                self.peak = Some(equity);
+                equity
+            }
+        };
+
+        self.current_drawdown = if peak > 0.0 { (peak - equity) / peak } else { 0.0 };
+        self.max_drawdown = self.max_drawdown.max(self.current_drawdown);
+        self.current_drawdown
+    }
+
+    /// Drop from the peak to the latest point, as a fraction of the peak
+    pub fn current_drawdown(&self) -> f64 {
+        self.current_drawdown
+    }
+
+    /// Worst drawdown seen so far, as a fraction of its peak
+    pub fn max_drawdown(&self) -> f64 {
+        self.max_drawdown
+    }
+
+    /// Highest equity seen so far
+    pub fn peak(&self) -> Option<f64> {
+        self.peak
+    }
+}
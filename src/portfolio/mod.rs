@@ -1 +1,8 @@
 // Portfolio management module
+pub mod types;
+pub mod tracker;
+pub mod drawdown;
+
+pub use types::*;
+pub use tracker::{Portfolio, DEFAULT_QUOTE_DECIMALS};
+pub use drawdown::{max_drawdown, DrawdownTracker};
@@ -0,0 +1,363 @@
+use super::types::*;
+use crate::trading::{AccountTrade, ExecutionReport, OrderId, OrderResponse, OrderSide, OrderStatus};
+use crate::trading::precision::step_decimals;
+use std::collections::{HashMap, HashSet, VecDeque};
+use tracing::{info, warn};
+
+/// Decimals for quote amounts when the symbol's tick size isn't known (cents for USDT)
+pub const DEFAULT_QUOTE_DECIMALS: usize = 2;
+
+/// How much of an order has already been applied to the positions
+#[derive(Debug, Clone, Copy, Default)]
+struct AppliedFill {
+    quantity: f64,     // executedQty already counted
+    quote: f64,        // cummulativeQuoteQty already counted
+}
+
+/// Portfolio keeps local positions up to date from order results
+pub struct Portfolio {
+    positions: HashMap<String, Position>,
+    applied: HashMap<OrderId, AppliedFill>,  // Keyed by order id, so re-applying an order only adds the new part
+    applied_trades: HashSet<(String, u64)>,  // (symbol, trade id) already applied from myTrades or execution reports
+    conversion_rates: HashMap<String, f64>,  // Quote-asset price of commission assets like BNB
+    quote_decimals: usize,                   // Display precision for symbols without a known tick size
+    symbol_decimals: HashMap<String, usize>, // Display precision from each symbol's tick size
+    aliases: HashMap<String, String>,        // Renamed symbol -> its current name
+    lots: HashMap<String, VecDeque<Lot>>,    // Open buy lots per symbol, oldest first
+    fifo_realized: HashMap<String, f64>,     // Realized P&L per symbol with FIFO cost basis
+}
+
+impl Portfolio {
+    pub fn new() -> Self {
+        Self {
+            positions: HashMap::new(),
+            applied: HashMap::new(),
+            applied_trades: HashSet::new(),
+            conversion_rates: HashMap::new(),
+            quote_decimals: DEFAULT_QUOTE_DECIMALS,
+            symbol_decimals: HashMap::new(),
+            aliases: HashMap::new(),
+            lots: HashMap::new(),
+            fifo_realized: HashMap::new(),
+        }
+    }
+
+    /// Decimals used for rounded outputs of symbols with no tick size set
+    pub fn with_quote_decimals(mut self, decimals: usize) -> Self {
+        self.quote_decimals = decimals;
+        self
+    }
+
+    /// Treat `old` as a former name of `new`, e.g. after Binance renames a pair
+    ///
+    /// Whatever is stored under `old` (position, tick size, applied trades) moves to
+    /// `new` - merged with an existing `new` position at average cost - and every later
+    /// lookup or fill for `old` goes to `new`, with a warning so stale callers show up.
+    pub fn add_symbol_alias(&mut self, old: &str, new: &str) {
+        if old == new {
+            return;
+        }
+        self.aliases.insert(old.to_string(), new.to_string());
+
+        if let Some(mut moved) = self.positions.remove(old) {
+            moved.symbol = new.to_string();
+            let merged = match self.positions.remove(new) {
+                Some(current) => Self::merge_positions(current, moved),
+                None => moved,
+            };
+            self.positions.insert(new.to_string(), merged);
+            info!("Moved position {} to its new name {}", old, new);
+        }
+        if let Some(mut moved) = self.lots.remove(old) {
+            // Holdings under the old name were bought first
+            let lots = self.lots.entry(new.to_string()).or_default();
+            moved.extend(lots.drain(..));
+            *lots = moved;
+        }
+        if let Some(pnl) = self.fifo_realized.remove(old) {
+            *self.fifo_realized.entry(new.to_string()).or_insert(0.0) += pnl;
+        }
+        if let Some(decimals) = self.symbol_decimals.remove(old) {
+            self.symbol_decimals.entry(new.to_string()).or_insert(decimals);
+        }
+        let trades: Vec<(String, u64)> = self.applied_trades.iter().filter(|(s, _)| s == old).cloned().collect();
+        for (_, id) in trades {
+            self.applied_trades.remove(&(old.to_string(), id));
+            self.applied_trades.insert((new.to_string(), id));
+        }
+    }
+
+    /// Builder form of `add_symbol_alias`
+    pub fn with_symbol_alias(mut self, old: &str, new: &str) -> Self {
+        self.add_symbol_alias(old, new);
+        self
+    }
+
+    /// The current name for `symbol`, following aliases
+    pub fn resolve_symbol<'a>(&'a self, symbol: &'a str) -> &'a str {
+        match self.aliases.get(symbol) {
+            Some(new) => {
+                warn!("{} was renamed to {}, using the new name", symbol, new);
+                new
+            }
+            None => symbol,
+        }
+    }
+
+    fn merge_positions(a: Position, b: Position) -> Position {
+        let quantity = a.quantity + b.quantity;
+        let average_price = if quantity > 0.0 {
+            (a.quantity * a.average_price + b.quantity * b.average_price) / quantity
+        } else {
+            0.0
+        };
+        Position {
+            quantity,
+            average_price,
+            realized_pnl: a.realized_pnl + b.realized_pnl,
+            fees_paid: a.fees_paid + b.fees_paid,
+            ..a
+        }
+    }
+
+    /// Round this symbol's amounts to its price tick precision,
+    /// e.g. a tick size of 0.01 (from the PRICE_FILTER) -> 2 decimals
+    pub fn set_tick_size(&mut self, symbol: &str, tick_size: f64) {
+        let symbol = self.resolve_symbol(symbol).to_string();
+        self.symbol_decimals.insert(symbol, step_decimals(tick_size));
+    }
+
+    /// Decimals used when rounding this symbol's amounts for display
+    pub fn display_decimals(&self, symbol: &str) -> usize {
+        self.symbol_decimals.get(self.resolve_symbol(symbol)).copied().unwrap_or(self.quote_decimals)
+    }
+
+    /// Position with price, P&L and fees rounded to the symbol's display decimals
+    pub fn rounded_position(&self, symbol: &str) -> Option<Position> {
+        self.position(symbol).map(|p| p.rounded(self.display_decimals(symbol)))
+    }
+
+    /// `realized_pnl_report` with every row rounded to its symbol's display decimals
+    pub fn rounded_pnl_report(&self) -> HashMap<String, PnlRow> {
+        self.realized_pnl_report()
+            .into_iter()
+            .map(|(symbol, row)| {
+                let rounded = row.rounded(self.display_decimals(&symbol));
+                (symbol, rounded)
+            })
+            .collect()
+    }
+
+    /// Set the quote-asset price of a commission asset, e.g. ("BNB", 300.0)
+    /// Needed for fees charged in an asset that isn't part of the traded symbol
+    pub fn set_conversion_rate(&mut self, asset: &str, quote_price: f64) {
+        self.conversion_rates.insert(asset.to_string(), quote_price);
+    }
+
+    /// Position for `symbol` (or the symbol it was renamed to)
+    pub fn position(&self, symbol: &str) -> Option<&Position> {
+        self.positions.get(self.resolve_symbol(symbol))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = &Position> {
+        self.positions.values()
+    }
+
+    /// Update positions from an order's fill state
+    ///
+    /// Uses `executedQty`/`cummulativeQuoteQty` (what actually traded), not `origQty`.
+    /// Safe to call repeatedly for the same order as it fills: only the part executed
+    /// since the last call is applied, priced at that part's own average price.
+    /// Returns the quantity newly applied (0.0 if nothing changed).
+    pub fn apply_fill(&mut self, order: &OrderResponse) -> Result<f64, Box<dyn std::error::Error>> {
+        let executed: f64 = order.executed_qty.parse()?;
+        let quote: f64 = order.cummulative_quote_qty.parse()?;
+        let side: OrderSide = order.side.parse()?;
+
+        let previous = self.applied.get(&order.order_id).copied().unwrap_or_default();
+        let delta_quantity = executed - previous.quantity;
+        let delta_quote = quote - previous.quote;
+
+        if delta_quantity <= 0.0 {
+            return Ok(0.0);  // Already applied (or nothing executed yet)
+        }
+
+        let fill_price = delta_quote / delta_quantity;
+        let symbol = self.resolve_symbol(&order.symbol).to_string();
+        self.apply_trade(&symbol, side, delta_quantity, fill_price);
+        self.applied.insert(order.order_id, AppliedFill { quantity: executed, quote });
+
+        info!("Applied fill for order {}: {} {} @ {:.8} ({:?})",
+              order.order_id, delta_quantity, order.symbol, fill_price, order.status);
+        Ok(delta_quantity)
+    }
+
+    /// Update positions from one `myTrades` execution, including its commission
+    ///
+    /// The commission is converted to quote terms: as-is when charged in the quote
+    /// asset, at the trade price when charged in the base asset, and via
+    /// `set_conversion_rate` for anything else (e.g. BNB). Errors without changing
+    /// anything if no rate is known. Applying the same trade twice is a no-op.
+    /// Returns the fee in quote asset.
+    pub fn apply_account_trade(&mut self, trade: &AccountTrade) -> Result<f64, Box<dyn std::error::Error>> {
+        let symbol = self.resolve_symbol(&trade.symbol).to_string();
+        if self.applied_trades.contains(&(symbol.clone(), trade.id)) {
+            return Ok(0.0);
+        }
+
+        let fee = self.fee_in_quote(&symbol, trade.price, trade.commission, &trade.commission_asset)
+            .ok_or_else(|| format!("No conversion rate for commission asset {} (trade {} on {})",
+                                   trade.commission_asset, trade.id, symbol))?;
+        let side = if trade.is_buyer { OrderSide::Buy } else { OrderSide::Sell };
+        self.apply_trade(&symbol, side, trade.qty, trade.price);
+        if let Some(position) = self.positions.get_mut(&symbol) {
+            position.fees_paid += fee;
+        }
+        self.applied_trades.insert((symbol, trade.id));
+        Ok(fee)
+    }
+
+    /// Update positions from a user data stream `executionReport` as fills happen
+    ///
+    /// The real-time counterpart to `apply_fill`: only TRADE reports with a
+    /// FILLED/PARTIALLY_FILLED status count, each applied at its own last-filled
+    /// quantity and price, with the commission added to `fees_paid` in quote terms.
+    /// Trades are deduplicated by trade id (shared with `apply_account_trade`, so
+    /// backfilling from myTrades afterwards won't double count). A commission in
+    /// an asset without a conversion rate is logged and left out of the fees
+    /// rather than dropping the fill. Returns the quantity newly applied.
+    pub fn apply_execution_report(&mut self, report: &ExecutionReport) -> f64 {
+        let is_fill = report.execution_type == "TRADE"
+            && matches!(report.order_status, OrderStatus::Filled | OrderStatus::PartiallyFilled);
+        if !is_fill || report.last_filled_qty <= 0.0 || report.trade_id < 0 {
+            return 0.0;
+        }
+        let symbol = self.resolve_symbol(&report.symbol).to_string();
+        if !self.applied_trades.insert((symbol.clone(), report.trade_id as u64)) {
+            return 0.0;  // Already applied
+        }
+
+        self.apply_trade(&symbol, report.side, report.last_filled_qty, report.last_filled_price);
+
+        let asset = report.commission_asset.as_deref().unwrap_or_default();
+        let fee = self.fee_in_quote(&symbol, report.last_filled_price, report.commission, asset)
+            .unwrap_or_else(|| {
+                warn!("No conversion rate for commission asset {} on {}, fee not counted", asset, symbol);
+                0.0
+            });
+        if let Some(position) = self.positions.get_mut(&symbol) {
+            position.fees_paid += fee;
+        }
+
+        info!("Applied execution report for order {}: {} {} @ {:.8} ({:?})",
+              report.order_id, report.last_filled_qty, report.symbol, report.last_filled_price, report.order_status);
+        report.last_filled_qty
+    }
+
+    /// Gross P&L, fees and net P&L per symbol, in quote asset
+    pub fn realized_pnl_report(&self) -> HashMap<String, PnlRow> {
+        self.positions
+            .values()
+            .map(|p| {
+                let row = PnlRow {
+                    gross_pnl: p.realized_pnl,
+                    fees: p.fees_paid,
+                    net_pnl: p.realized_pnl - p.fees_paid,
+                };
+                (p.symbol.clone(), row)
+            })
+            .collect()
+    }
+
+    /// Total realized P&L (before fees) under each cost basis method, from the same fills
+    /// Average cost and FIFO agree on the total once everything is sold, but differ
+    /// while part of a position bought at different prices is still held
+    pub fn pnl_by_method(&self) -> HashMap<CostBasisMethod, f64> {
+        HashMap::from([
+            (CostBasisMethod::AverageCost, self.positions.values().map(|p| p.realized_pnl).sum()),
+            (CostBasisMethod::Fifo, self.fifo_realized.values().sum()),
+        ])
+    }
+
+    /// `pnl_by_method` for one symbol
+    pub fn symbol_pnl_by_method(&self, symbol: &str) -> HashMap<CostBasisMethod, f64> {
+        let symbol = self.resolve_symbol(symbol);
+        HashMap::from([
+            (CostBasisMethod::AverageCost, self.positions.get(symbol).map_or(0.0, |p| p.realized_pnl)),
+            (CostBasisMethod::Fifo, self.fifo_realized.get(symbol).copied().unwrap_or(0.0)),
+        ])
+    }
+
+    /// Buy lots still held for `symbol`, oldest first
+    pub fn open_lots(&self, symbol: &str) -> impl Iterator<Item = &Lot> {
+        self.lots.get(self.resolve_symbol(symbol)).into_iter().flatten()
+    }
+
+    /// Commission of a trade converted to the symbol's quote asset
+    /// None if the commission asset needs a conversion rate we don't have
+    fn fee_in_quote(&self, symbol: &str, price: f64, commission: f64, asset: &str) -> Option<f64> {
+        if commission == 0.0 {
+            Some(0.0)
+        } else if symbol.ends_with(asset) {
+            Some(commission)  // Charged in the quote asset
+        } else if symbol.starts_with(asset) {
+            Some(commission * price)  // Charged in the base asset
+        } else {
+            self.conversion_rates.get(asset).map(|rate| commission * rate)
+        }
+    }
+
+    /// Apply a single trade to the position at average cost, and to the FIFO lots
+    fn apply_trade(&mut self, symbol: &str, side: OrderSide, quantity: f64, price: f64) {
+        let position = self.positions.entry(symbol.to_string()).or_insert_with(|| Position {
+            symbol: symbol.to_string(),
+            ..Position::default()
+        });
+
+        let lots = self.lots.entry(symbol.to_string()).or_default();
+
+        match side {
+            OrderSide::Buy => {
+                let cost = position.quantity * position.average_price + quantity * price;
+                position.quantity += quantity;
+                position.average_price = cost / position.quantity;
+                lots.push_back(Lot { quantity, price });
+            }
+            OrderSide::Sell => {
+                // Spot can't go short: anything beyond the held amount is ignored
+                let sold = if quantity > position.quantity {
+                    warn!("Sell of {} {} exceeds tracked position of {}", quantity, symbol, position.quantity);
+                    position.quantity
+                } else {
+                    quantity
+                };
+                position.realized_pnl += sold * (price - position.average_price);
+                position.quantity -= sold;
+
+                // FIFO: the same quantity taken from the oldest lots
+                let mut remaining = sold;
+                let fifo_pnl = self.fifo_realized.entry(symbol.to_string()).or_insert(0.0);
+                while remaining > 0.0 {
+                    let Some(lot) = lots.front_mut() else { break };
+                    let taken = remaining.min(lot.quantity);
+                    *fifo_pnl += taken * (price - lot.price);
+                    lot.quantity -= taken;
+                    remaining -= taken;
+                    if lot.quantity <= 1e-12 {
+                        lots.pop_front();
+                    }
+                }
+                if position.quantity <= 0.0 {
+                    position.quantity = 0.0;
+                    position.average_price = 0.0;
+                }
+            }
+        }
+    }
+}
+
+impl Default for Portfolio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,58 @@
+use crate::trading::precision::round_to_decimals;
+use serde::{Deserialize, Serialize};
+
+/// Holdings of one symbol's base asset, tracked at average cost
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    pub quantity: f64,        // Base asset held
+    pub average_price: f64,   // Average cost per unit of what's currently held
+    pub realized_pnl: f64,    // Profit/loss locked in by sells, in quote asset
+    #[serde(default)]
+    pub fees_paid: f64,       // Commissions converted to quote asset (from `apply_account_trade` and `apply_execution_report`)
+}
+
+impl Position {
+    /// Copy with the quote-asset amounts (price, P&L, fees) rounded to `decimals`
+    /// The quantity is left as is - it's on the symbol's step grid already
+    pub fn rounded(&self, decimals: usize) -> Position {
+        Position {
+            average_price: round_to_decimals(self.average_price, decimals),
+            realized_pnl: round_to_decimals(self.realized_pnl, decimals),
+            fees_paid: round_to_decimals(self.fees_paid, decimals),
+            ..self.clone()
+        }
+    }
+}
+
+/// How the cost of a sold quantity is determined when computing realized P&L
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CostBasisMethod {
+    AverageCost,  // Every unit held costs the running average price
+    Fifo,         // Sells consume the oldest buys first, each at its own price
+}
+
+/// Quantity bought in one fill, still held, at its fill price
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Lot {
+    pub quantity: f64,
+    pub price: f64,
+}
+
+/// One line of `Portfolio::realized_pnl_report`, all in quote asset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct PnlRow {
+    pub gross_pnl: f64,  // Realized P&L before fees
+    pub fees: f64,       // Commissions paid, converted to quote asset
+    pub net_pnl: f64,    // gross_pnl - fees
+}
+
+impl PnlRow {
+    /// Copy with every amount rounded to `decimals`
+    /// net_pnl is recomputed from the rounded parts, so the row still adds up
+    pub fn rounded(&self, decimals: usize) -> PnlRow {
+        let gross_pnl = round_to_decimals(self.gross_pnl, decimals);
+        let fees = round_to_decimals(self.fees, decimals);
+        PnlRow { gross_pnl, fees, net_pnl: round_to_decimals(gross_pnl - fees, decimals) }
+    }
+}
@@ -0,0 +1,74 @@
+use super::types::*;
+use crate::market_data::Ticker;
+use std::collections::VecDeque;
+
+/// Donchian-style channel breakout strategy
+/// Buys when price closes above the highest close of the last `lookback` closes,
+/// sells when it closes below the lowest. An optional confirmation percentage
+/// requires the breakout to clear the channel by that margin, filtering out noise.
+pub struct BreakoutStrategy {
+    lookback: usize,
+    confirmation_pct: f64,    // e.g. 0.5 means price must exceed the channel by 0.5%
+    closes: VecDeque<f64>,    // Ring buffer holding the last `lookback` closes
+}
+
+impl BreakoutStrategy {
+    pub fn new(lookback: usize) -> Self {
+        let lookback = lookback.max(1);
+        Self {
+            lookback,
+            confirmation_pct: 0.0,
+            closes: VecDeque::with_capacity(lookback),
+        }
+    }
+
+    pub fn with_confirmation_pct(mut self, confirmation_pct: f64) -> Self {
+        self.confirmation_pct = confirmation_pct.max(0.0);
+        self
+    }
+
+    /// Highest and lowest close in the current window, once it is full
+    pub fn channel(&self) -> Option<(f64, f64)> {
+        if self.closes.len() < self.lookback {
+            return None;
+        }
+        let high = self.closes.iter().cloned().fold(f64::MIN, f64::max);
+        let low = self.closes.iter().cloned().fold(f64::MAX, f64::min);
+        Some((high, low))
+    }
+
+    /// Feed one close (a candle close, or a ticker price) and get the signal
+    /// The new close is compared against the previous window before being added to it
+    pub fn on_close(&mut self, close: f64) -> Signal {
+        let signal = match self.channel() {
+            Some((high, low)) => {
+                let margin = self.confirmation_pct / 100.0;
+                if close > high * (1.0 + margin) {
+                    Signal::Buy
+                } else if close < low * (1.0 - margin) {
+                    Signal::Sell
+                } else {
+                    Signal::Hold
+                }
+            }
+            None => Signal::Hold,  // Not enough history yet
+        };
+
+        if self.closes.len() == self.lookback {
+            self.closes.pop_front();
+        }
+        self.closes.push_back(close);
+
+        signal
+    }
+}
+
+impl Strategy for BreakoutStrategy {
+    fn name(&self) -> &str {
+        "breakout"
+    }
+
+    fn on_ticker(&mut self, ticker: &Ticker) -> Signal {
+        self.on_close(ticker.price)
+    }
+}
@@ -0,0 +1,65 @@
+use crate::orders::Quote;
+use crate::trading::OrderSide;
+
+/// Spread-capture market maker: quotes both sides around the mid price
+///
+/// Quotes sit `half_spread` (in quote asset) either side of the mid. Both
+/// prices are shifted against the current inventory so fills tend to bring the
+/// position back to zero: when long, quotes move down (selling is easier,
+/// buying less attractive); when short, they move up. At a full
+/// `max_inventory` the shift is `skew_factor * half_spread`, and the side that
+/// would grow the position further is dropped.
+/// The output feeds straight into `orders::refresh_quotes`.
+pub struct MarketMaker {
+    half_spread: f64,     // Distance from mid to each quote, in quote asset
+    quote_size: f64,      // Base asset quantity per quote
+    max_inventory: f64,   // Position limit either way, in base asset
+    skew_factor: f64,     // Shift at full inventory, as a multiple of half_spread
+}
+
+impl MarketMaker {
+    pub fn new(half_spread: f64, quote_size: f64, max_inventory: f64) -> Self {
+        Self {
+            half_spread: half_spread.max(0.0),
+            quote_size: quote_size.max(0.0),
+            max_inventory: max_inventory.max(0.0),
+            skew_factor: 1.0,
+        }
+    }
+
+    pub fn with_skew_factor(mut self, skew_factor: f64) -> Self {
+        self.skew_factor = skew_factor.max(0.0);
+        self
+    }
+
+    /// Price shift applied to both quotes for this inventory
+    pub fn skew(&self, inventory: f64) -> f64 {
+        if self.max_inventory <= 0.0 {
+            return 0.0;
+        }
+        let ratio = (inventory / self.max_inventory).clamp(-1.0, 1.0);
+        -ratio * self.skew_factor * self.half_spread
+    }
+
+    /// Desired bid and ask for the current book and inventory
+    /// Sizes are capped so a fill can't push the position past `max_inventory`
+    pub fn quotes(&self, best_bid: f64, best_ask: f64, inventory: f64) -> Vec<Quote> {
+        if best_bid <= 0.0 || best_ask < best_bid {
+            return Vec::new();  // Empty or crossed book - don't quote
+        }
+
+        let mid = (best_bid + best_ask) / 2.0;
+        let skew = self.skew(inventory);
+        let mut quotes = Vec::with_capacity(2);
+
+        let buy_room = (self.max_inventory - inventory).min(self.quote_size);
+        if buy_room > 0.0 {
+            quotes.push(Quote { side: OrderSide::Buy, price: mid - self.half_spread + skew, quantity: buy_room });
+        }
+        let sell_room = (self.max_inventory + inventory).min(self.quote_size);
+        if sell_room > 0.0 {
+            quotes.push(Quote { side: OrderSide::Sell, price: mid + self.half_spread + skew, quantity: sell_room });
+        }
+        quotes
+    }
+}
@@ -1 +1,10 @@
 // Trading strategies module
+pub mod types;
+pub mod runner;
+pub mod breakout;
+pub mod market_maker;
+
+pub use types::*;
+pub use runner::StrategyRunner;
+pub use breakout::BreakoutStrategy;
+pub use market_maker::MarketMaker;
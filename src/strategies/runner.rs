@@ -0,0 +1,148 @@
+use super::types::*;
+use crate::market_data::{MarketDataEvent, MarketDataSource};
+use crate::trading::{OrderSide, TestnetTrader};
+use std::collections::HashMap;
+use tracing::{error, info, warn};
+
+/// Counters describing what happened during a run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunnerStats {
+    pub tickers_processed: u64,
+    pub signals: u64,         // Buy/Sell signals emitted (Hold is not counted)
+    pub signals_suppressed: u64,  // Signals dropped by the position mode
+    pub signals_debounced: u64,   // Reversals dropped by the signal debounce
+    pub orders_placed: u64,
+    pub order_errors: u64,
+}
+
+/// StrategyRunner wires a market data source to a strategy and a trader:
+/// every ticker goes to the strategy, and Buy/Sell signals become market orders
+pub struct StrategyRunner<S: MarketDataSource> {
+    source: S,                      // Live MarketDataStream or a mock source
+    strategy: Box<dyn Strategy>,
+    trader: TestnetTrader,
+    position_size: f64,             // Base asset quantity used for each order
+    position_mode: Option<PositionMode>,  // None = act on every signal
+    positions: HashMap<String, f64>,  // Signed net quantity per symbol from the runner's own fills
+    debounce: Option<SignalDebounce>,  // None = act on reversals right away
+    last_acted: HashMap<String, ActedSignal>,  // Last signal per symbol that became an order
+    stats: RunnerStats,
+}
+
+impl<S: MarketDataSource> StrategyRunner<S> {
+    pub fn new(source: S, strategy: Box<dyn Strategy>, trader: TestnetTrader) -> Self {
+        Self {
+            source,
+            strategy,
+            trader,
+            position_size: 0.001,  // Small default so a misconfigured runner can't do much damage
+            position_mode: None,
+            positions: HashMap::new(),
+            debounce: None,
+            last_acted: HashMap::new(),
+            stats: RunnerStats::default(),
+        }
+    }
+
+    pub fn with_position_size(mut self, quantity: f64) -> Self {
+        self.position_size = quantity;
+        self
+    }
+
+    /// Drop signals the mode says are redundant, e.g. a Buy while already long
+    /// Positions come from the fills of the runner's own orders (plus `with_position`)
+    pub fn with_position_mode(mut self, mode: PositionMode) -> Self {
+        self.position_mode = Some(mode);
+        self
+    }
+
+    /// Start from an existing position (negative = short), e.g. read from a `Portfolio`
+    pub fn with_position(mut self, symbol: &str, quantity: f64) -> Self {
+        self.positions.insert(symbol.to_string(), quantity);
+        self
+    }
+
+    /// Ignore a signal reversing the last order's direction until the debounce's
+    /// time or price-move threshold is met, to avoid whipsaw trades around a crossover
+    pub fn with_signal_debounce(mut self, debounce: SignalDebounce) -> Self {
+        self.debounce = Some(debounce);
+        self
+    }
+
+    /// Net quantity the runner holds in `symbol` (negative = short)
+    pub fn position(&self, symbol: &str) -> f64 {
+        self.positions.get(symbol).copied().unwrap_or(0.0)
+    }
+
+    pub fn stats(&self) -> &RunnerStats {
+        &self.stats
+    }
+
+    /// Process events until the source ends
+    /// Order failures are logged and counted, they never stop the loop
+    pub async fn run(&mut self) -> RunnerStats {
+        info!("Starting strategy runner with '{}'", self.strategy.name());
+
+        while let Some(event) = self.source.next_event().await {
+            match event {
+                MarketDataEvent::Ticker(ticker) => {
+                    self.stats.tickers_processed += 1;
+
+                    let signal = self.strategy.on_ticker(&ticker);
+                    let side = match signal {
+                        Signal::Buy => OrderSide::Buy,
+                        Signal::Sell => OrderSide::Sell,
+                        Signal::Hold => continue,
+                    };
+                    self.stats.signals += 1;
+
+                    let position = self.position(&ticker.symbol);
+                    if self.position_mode.is_some_and(|mode| !mode.allows(signal, position)) {
+                        self.stats.signals_suppressed += 1;
+                        info!("Ignoring {:?} for {}: position is already {}", signal, ticker.symbol, position);
+                        continue;
+                    }
+
+                    if let (Some(debounce), Some(last)) = (self.debounce, self.last_acted.get_mut(&ticker.symbol)) {
+                        if !debounce.allows(Some(last), signal, ticker.timestamp, ticker.price) {
+                            last.reversed = true;
+                            self.stats.signals_debounced += 1;
+                            info!("Debouncing {:?} for {} at {:.2}: too soon after the last order", signal, ticker.symbol, ticker.price);
+                            continue;
+                        }
+                    }
+
+                    info!(
+                        "{} signaled {:?} for {} at {:.2}, placing market order for {}",
+                        self.strategy.name(), side, ticker.symbol, ticker.price, self.position_size
+                    );
+
+                    match self.trader.place_market_order(&ticker.symbol, side, self.position_size).await {
+                        Ok(order) => {
+                            self.stats.orders_placed += 1;
+                            let filled: f64 = order.executed_qty.parse().unwrap_or(0.0);
+                            let signed = if side == OrderSide::Buy { filled } else { -filled };
+                            *self.positions.entry(ticker.symbol.clone()).or_insert(0.0) += signed;
+                            self.last_acted.insert(ticker.symbol.clone(), ActedSignal {
+                                signal,
+                                timestamp: ticker.timestamp,
+                                price: ticker.price,
+                                reversed: false,
+                            });
+                            info!("Order {} placed ({:?})", order.order_id, order.status);
+                        }
+                        Err(e) => {
+                            self.stats.order_errors += 1;
+                            error!("Order failed, continuing: {}", e);
+                        }
+                    }
+                }
+                MarketDataEvent::Error(err) => warn!("Market data error: {}", err),
+                _ => {}
+            }
+        }
+
+        info!("Market data source ended, runner stopping: {:?}", self.stats);
+        self.stats.clone()
+    }
+}
@@ -0,0 +1,98 @@
+use crate::market_data::Ticker;
+use std::time::Duration;
+
+/// What a strategy wants to do after seeing new market data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Buy,
+    Sell,
+    Hold,  // Do nothing
+}
+
+/// Which positions the runner may hold, used to drop signals that wouldn't change anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionMode {
+    LongOnly,   // Buy only when not long, Sell only to close a long
+    LongShort,  // Buy unless already long, Sell unless already short
+    Flat,       // Reduce-only: signals may close a position but never open one
+}
+
+impl PositionMode {
+    /// Whether `signal` should be acted on while holding `position` (signed, base asset)
+    pub fn allows(self, signal: Signal, position: f64) -> bool {
+        let long = position > 0.0;
+        let short = position < 0.0;
+        match (self, signal) {
+            (_, Signal::Hold) => false,
+            (PositionMode::LongOnly, Signal::Buy) => !long,
+            (PositionMode::LongOnly, Signal::Sell) => long,
+            (PositionMode::LongShort, Signal::Buy) => !long,
+            (PositionMode::LongShort, Signal::Sell) => !short,
+            (PositionMode::Flat, Signal::Buy) => short,
+            (PositionMode::Flat, Signal::Sell) => long,
+        }
+    }
+}
+
+/// A signal the runner placed an order for, kept per symbol for `SignalDebounce`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActedSignal {
+    pub signal: Signal,
+    pub timestamp: u64,  // Ticker time in milliseconds
+    pub price: f64,
+    pub reversed: bool,  // An opposite signal has been debounced since
+}
+
+/// Holds back a signal that reverses the last acted-upon one until enough time has
+/// passed or the price has moved far enough, so a price hovering at a crossover
+/// doesn't flip the position on every tick
+///
+/// The reversal goes through once either configured threshold is met; with neither
+/// set nothing is held back. Repeating the acted-upon direction is not a reversal,
+/// unless the strategy flipped away from it in between (Buy, Sell, Buy all count).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SignalDebounce {
+    pub min_interval: Option<Duration>,  // Time since the last acted-upon signal
+    pub min_price_move: Option<f64>,     // Relative move since then, e.g. 0.002 = 0.2%
+}
+
+impl SignalDebounce {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_min_interval(mut self, interval: Duration) -> Self {
+        self.min_interval = Some(interval);
+        self
+    }
+
+    pub fn with_min_price_move(mut self, fraction: f64) -> Self {
+        self.min_price_move = Some(fraction);
+        self
+    }
+
+    /// Whether `signal` seen at `timestamp` (ms) and `price` may be acted on after `last`
+    pub fn allows(&self, last: Option<&ActedSignal>, signal: Signal, timestamp: u64, price: f64) -> bool {
+        let Some(last) = last else { return true };
+        if (signal == last.signal && !last.reversed) || (self.min_interval.is_none() && self.min_price_move.is_none()) {
+            return true;
+        }
+        let waited = self.min_interval.is_some_and(|interval| {
+            timestamp.saturating_sub(last.timestamp) as u128 >= interval.as_millis()
+        });
+        let moved = self.min_price_move.is_some_and(|fraction| {
+            last.price > 0.0 && ((price - last.price) / last.price).abs() >= fraction
+        });
+        waited || moved
+    }
+}
+
+/// A trading strategy turns a stream of tickers into buy/sell signals
+/// Strategies only decide - sizing and order placement are handled by `StrategyRunner`
+pub trait Strategy: Send {
+    /// Short name used in log messages
+    fn name(&self) -> &str;
+
+    /// Feed the next ticker and get the strategy's decision
+    fn on_ticker(&mut self, ticker: &Ticker) -> Signal;
+}
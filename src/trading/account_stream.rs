@@ -0,0 +1,163 @@
+use super::client::TestnetTrader;
+use super::types::{AccountSnapshot, OutboundAccountPosition};
+use crate::error::TradingError;
+use futures_util::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::protocol::Message;
+use tracing::{debug, error, info, warn};
+
+/// WebSocket host for the testnet user data stream
+pub const USER_STREAM_BASE_URL: &str = "wss://stream.testnet.binance.vision";
+
+/// Listen keys expire after 60 minutes; Binance recommends a keepalive every 30
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// Wait before reconnecting after the user data stream drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Real-time account balances from the user data stream, without polling
+///
+/// Starts from a REST `get_spot_account` snapshot, then applies every
+/// `outboundAccountPosition` event. Subscribers get a `watch::Receiver` that only
+/// wakes up when a balance actually changed. After a disconnect the snapshot is
+/// re-fetched over REST (updates may have been missed) before streaming resumes.
+pub struct AccountWatcher {
+    snapshot: watch::Receiver<AccountSnapshot>,
+    task: JoinHandle<()>,
+}
+
+impl AccountWatcher {
+    /// Fetch the initial snapshot and start following the testnet user data stream
+    pub async fn start(trader: Arc<TestnetTrader>) -> Result<Self, TradingError> {
+        Self::start_with_url(trader, USER_STREAM_BASE_URL.to_string()).await
+    }
+
+    /// `start` against a different WebSocket host (e.g. a local server in tests)
+    pub async fn start_with_url(trader: Arc<TestnetTrader>, stream_base_url: String) -> Result<Self, TradingError> {
+        let account = trader.get_spot_account().await?;
+        let listen_key = trader.create_listen_key().await?;
+        let (sender, snapshot) = watch::channel(AccountSnapshot::from_account_info(&account));
+
+        let task = tokio::spawn(Self::run(trader, stream_base_url, listen_key, sender));
+        Ok(Self { snapshot, task })
+    }
+
+    /// A receiver that always holds the latest snapshot; `changed().await` waits for the next change
+    pub fn subscribe(&self) -> watch::Receiver<AccountSnapshot> {
+        self.snapshot.clone()
+    }
+
+    /// The current balances
+    pub fn snapshot(&self) -> AccountSnapshot {
+        self.snapshot.borrow().clone()
+    }
+
+    async fn run(
+        trader: Arc<TestnetTrader>,
+        stream_base_url: String,
+        mut listen_key: String,
+        sender: watch::Sender<AccountSnapshot>,
+    ) {
+        let mut first_connection = true;
+        loop {
+            if !first_connection {
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                if let Err(e) = Self::resync(&trader, &mut listen_key, &sender).await {
+                    error!("Failed to resync account after disconnect: {}", e);
+                    continue;
+                }
+            }
+            first_connection = false;
+
+            if let Err(e) = Self::follow(&trader, &stream_base_url, &listen_key, &sender).await {
+                error!("User data stream failed: {}", e);
+            }
+            if sender.is_closed() {
+                return;  // Every receiver is gone, including the watcher's own
+            }
+            warn!("User data stream disconnected, reconnecting in {:?}", RECONNECT_DELAY);
+        }
+    }
+
+    /// Re-fetch balances over REST and get a fresh listen key
+    async fn resync(
+        trader: &TestnetTrader,
+        listen_key: &mut String,
+        sender: &watch::Sender<AccountSnapshot>,
+    ) -> Result<(), TradingError> {
+        let account = trader.get_spot_account().await?;
+        *listen_key = trader.create_listen_key().await?;
+        let fresh = AccountSnapshot::from_account_info(&account);
+        sender.send_if_modified(|snapshot| {
+            let changed = snapshot.balances != fresh.balances;
+            *snapshot = fresh;
+            changed
+        });
+        Ok(())
+    }
+
+    /// Apply account updates from one connection until it closes
+    async fn follow(
+        trader: &TestnetTrader,
+        stream_base_url: &str,
+        listen_key: &str,
+        sender: &watch::Sender<AccountSnapshot>,
+    ) -> Result<(), TradingError> {
+        let url = format!("{}/ws/{}", stream_base_url, listen_key);
+        let (mut ws_stream, _) = connect_async(url).await?;
+        info!("Connected to user data stream");
+
+        let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+        keepalive.tick().await;  // The first tick is immediate; the key is brand new
+
+        loop {
+            tokio::select! {
+                msg = ws_stream.next() => match msg {
+                    Some(Ok(Message::Text(text))) => Self::handle_message(&text, sender),
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Err(e)) => return Err(e.into()),
+                    Some(Ok(_)) => {}
+                },
+                _ = keepalive.tick() => {
+                    if let Err(e) = trader.keepalive_listen_key(listen_key).await {
+                        warn!("Listen key keepalive failed: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_message(text: &str, sender: &watch::Sender<AccountSnapshot>) {
+        let data: Value = match serde_json::from_str(text) {
+            Ok(data) => data,
+            Err(e) => {
+                error!("Invalid user data message: {}", e);
+                return;
+            }
+        };
+
+        if data.get("e").and_then(|e| e.as_str()) != Some("outboundAccountPosition") {
+            debug!("Ignoring user data event: {}", text);
+            return;
+        }
+
+        match OutboundAccountPosition::deserialize(&data) {
+            Ok(update) => {
+                sender.send_if_modified(|snapshot| snapshot.apply(&update));
+            }
+            Err(e) => error!("Failed to parse outboundAccountPosition: {}", e),
+        }
+    }
+}
+
+impl Drop for AccountWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -1,29 +1,186 @@
+use super::precision::{format_decimal, format_decimal_to_step, format_price_to_tick, round_to_step, Amount, RoundingMode};
+use super::conversion::PriceGraph;
+use super::fees::FeeEstimator;
+use super::rate_limit::RateLimiter;
+use super::pagination::{collect_pages, collect_windowed_pages, PageCursor, MAX_PAGE_SIZE, MAX_TIME_WINDOW};
 use super::types::*;
+use crate::market_data::{Kline, Trade};
+use crate::orders::OrderLog;
+use crate::error::TradingError;
 use hmac::{Hmac, Mac};
-use reqwest::Client;
+use reqwest::{Client, Method, StatusCode};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
 use sha2::Sha256;
-use std::collections::HashMap;
-use tracing::{error, info};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{error, info, warn};
 
 type HmacSha256 = Hmac<Sha256>;
 
+/// Parse a JSON response body, logging the body when it doesn't have the expected shape
+fn parse_response<T: DeserializeOwned>(what: &str, response_text: &str) -> Result<T, TradingError> {
+    serde_json::from_str(response_text).map_err(|e| {
+        error!("Failed to parse {}: {}. Response was: {}", what, e, response_text);
+        TradingError::Deserialize(e)
+    })
+}
+
+// HTTP connection pool tuning
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+const POOL_MAX_IDLE_PER_HOST: usize = 8;
+const TCP_KEEPALIVE: Duration = Duration::from_secs(30);
+
+/// Trailing deltas Binance accepts by default (TRAILING_DELTA filter), in basis points
+pub const TRAILING_DELTA_RANGE_BPS: std::ops::RangeInclusive<u32> = 10..=2000;
+
+/// recvWindow sent with every signed request, in ms (Binance's own default)
+pub const DEFAULT_RECV_WINDOW: u64 = 5000;
+
+/// Largest recvWindow Binance accepts, in ms
+pub const MAX_RECV_WINDOW: u64 = 60_000;
+
+/// Clock drift beyond this is reported by `sync_time` (Binance's default recvWindow is 5s)
+pub const DEFAULT_CLOCK_DRIFT_THRESHOLD: Duration = Duration::from_millis(1000);
+
+/// Callback invoked by `sync_time` when the measured drift exceeds the threshold,
+/// with the drift in milliseconds (positive = local clock is behind the server)
+pub type ClockDriftHook = Box<dyn Fn(i64) + Send + Sync>;
+
+/// Retries of an idempotent GET after a 5xx, and the wait before the first one (doubled each time)
+pub const DEFAULT_SERVER_ERROR_RETRIES: u32 = 3;
+pub const DEFAULT_SERVER_ERROR_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Placement attempts `place_order_idempotent` makes while the outcome stays unknown
+const IDEMPOTENT_PLACE_ATTEMPTS: u32 = 3;
+
+/// How long exchange info is reused before being fetched again (listings change rarely)
+const EXCHANGE_INFO_TTL: Duration = Duration::from_secs(60 * 60);
+
+// Worked example from the Binance API docs ("SIGNED Endpoint Examples")
+const DOC_EXAMPLE_SECRET: &str = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+const DOC_EXAMPLE_QUERY: &str = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+const DOC_EXAMPLE_SIGNATURE: &str = "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71";
+
+/// One API key pair. The secret never leaves this struct except to sign requests
+#[derive(Clone)]
+pub struct Credentials {
+    pub api_key: String,  // Sent in the X-MBX-APIKEY header
+    secret_key: String,   // Used to sign requests
+}
+
+impl Credentials {
+    pub fn new(api_key: String, secret_key: String) -> Self {
+        Self { api_key, secret_key }
+    }
+
+    /// Shortened api key for log messages, e.g. "vmPUZE6m…"
+    pub fn masked_api_key(&self) -> String {
+        let prefix: String = self.api_key.chars().take(8).collect();
+        format!("{}…", prefix)
+    }
+}
+
+// Hand-written so the secret key can't end up in logs via {:?}
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("api_key", &self.masked_api_key())
+            .finish()
+    }
+}
+
+/// Exact amounts for an order on its way to the API
+/// The typed order methods keep what they were given (maybe a `Decimal`);
+/// a plain `NewOrderRequest` converts its f64 fields
+#[derive(Debug, Clone, Copy, Default)]
+struct OrderAmounts {
+    quantity: Option<Amount>,
+    quote_quantity: Option<Amount>,
+    price: Option<Amount>,
+}
+
+impl OrderAmounts {
+    fn of(order: &NewOrderRequest) -> Self {
+        Self {
+            quantity: order.quantity.map(Amount::from),
+            quote_quantity: order.quote_order_qty.map(Amount::from),
+            price: order.price.map(Amount::from),
+        }
+    }
+}
+
 /// TestnetTrader is the main struct for interacting with Binance's testnet API
 /// It handles authentication, API calls, and order management with fake money
 pub struct TestnetTrader {
-    api_key: String,      // Your testnet API key
-    secret_key: String,   // Your testnet secret key (for signing requests)
+    credentials: Vec<Credentials>,  // One or more key pairs; extra ones are failover keys
+    active_key: AtomicUsize,        // Index of the key pair currently used for signing
     client: Client,       // HTTP client for making requests
+    user_agent: String,   // Sent with every request so the bot is identifiable in logs
     base_url: String,     // Base URL for the API (can be changed for testing)
+    dry_run: bool,        // When true, orders are logged and simulated instead of sent
+    max_order_notional: Option<f64>,  // Safety cap on price * quantity for any single order
+    next_dry_run_id: AtomicU64,  // Fake order ids handed out in dry-run mode
+    time_unit: TimeUnit,  // Precision of signed timestamps and response times
+    last_timestamp: AtomicU64,  // Last signed timestamp, so each request gets a later one
+    clock_offset_ms: AtomicI64,  // Server time minus local time, from the last `sync_time`
+    clock_synced: AtomicBool,    // Set once `sync_time` has measured the offset
+    last_time_sync: Mutex<Option<Instant>>,  // When `sync_time` last succeeded
+    time_sync_interval: Option<Duration>,    // Re-sync before a signed call once this much has passed
+    clock_drift_threshold: Duration,
+    on_clock_drift: Option<ClockDriftHook>,
+    request_deadline: Option<Duration>,  // Upper bound on one signed call, retries included
+    recv_window: u64,  // ms after `timestamp` Binance still accepts a signed request
+    server_error_retries: u32,  // GETs answered with a 5xx are retried this many times
+    server_error_backoff: Duration,  // Wait before the first of those retries, doubled after each
+    exchange_info_cache: Mutex<Option<(Instant, Arc<ExchangeInfo>)>>,  // Last exchangeInfo and when it was fetched
+    order_log: Option<OrderLog>,  // Placed and canceled orders are recorded here when set
+    precision_overrides: HashMap<String, (u32, u32)>,  // symbol -> (qty_decimals, price_decimals)
+    symbol_aliases: HashMap<String, String>,  // Renamed symbol -> current name, for precision lookups
+    sell_guard: SellGuard,  // Whether SELLs are checked against the free base balance
+    halt_check: bool,       // Refuse orders for symbols that aren't TRADING
+    placed_client_ids: Mutex<HashSet<String>>,  // clientOrderIds known to have reached the exchange
+    bnb_fee_burn: AtomicBool,  // Last known spot BNB-burn setting (paying fees in BNB)
+    rate_limiter: Mutex<Option<Arc<RateLimiter>>>,  // Set from exchange info rateLimits, or by hand
 }
 
 impl TestnetTrader {
     pub fn new(api_key: String, secret_key: String) -> Self {
         Self {
-            api_key,
-            secret_key,
-            client: Client::new(),
+            credentials: vec![Credentials::new(api_key, secret_key)],
+            active_key: AtomicUsize::new(0),
+            client: Self::build_http_client(crate::DEFAULT_USER_AGENT, TimeUnit::default()),
+            user_agent: crate::DEFAULT_USER_AGENT.to_string(),
             base_url: "https://testnet.binance.vision".to_string(),
+            dry_run: false,
+            max_order_notional: None,
+            next_dry_run_id: AtomicU64::new(1),
+            time_unit: TimeUnit::default(),
+            last_timestamp: AtomicU64::new(0),
+            clock_offset_ms: AtomicI64::new(0),
+            clock_synced: AtomicBool::new(false),
+            last_time_sync: Mutex::new(None),
+            time_sync_interval: None,
+            clock_drift_threshold: DEFAULT_CLOCK_DRIFT_THRESHOLD,
+            on_clock_drift: None,
+            request_deadline: None,
+            recv_window: DEFAULT_RECV_WINDOW,
+            server_error_retries: DEFAULT_SERVER_ERROR_RETRIES,
+            server_error_backoff: DEFAULT_SERVER_ERROR_BACKOFF,
+            exchange_info_cache: Mutex::new(None),
+            order_log: None,
+            precision_overrides: HashMap::new(),
+            symbol_aliases: HashMap::new(),
+            sell_guard: SellGuard::default(),
+            halt_check: false,
+            placed_client_ids: Mutex::new(HashSet::new()),
+            bnb_fee_burn: AtomicBool::new(false),
+            rate_limiter: Mutex::new(None),
         }
     }
 
@@ -32,227 +189,1765 @@ impl TestnetTrader {
         self
     }
 
-    pub async fn get_account_info(&self) -> Result<AccountInfo, Box<dyn std::error::Error>> {
-        let endpoint = "/api/v3/account";
-        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
-        
-        // Build parameters for the API call
-        let mut params = HashMap::new();
-        params.insert("timestamp".to_string(), timestamp.to_string());
-        
-        // Create query string and sign it
-        let query_string = self.build_query_string(&params);
-        let signature = self.sign(&query_string);
-        
-        let url = format!("{}{}?{}&signature={}", 
-                         self.base_url, endpoint, query_string, signature);
-        
-        let response = self.client
-            .get(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .send()
-            .await?;
+    /// Override the default `rust-trading-system/<version>` User-Agent,
+    /// e.g. to tag this bot's requests in proxy logs
+    pub fn with_user_agent(mut self, user_agent: String) -> Self {
+        self.client = Self::build_http_client(&user_agent, self.time_unit);
+        self.user_agent = user_agent;
+        self
+    }
+
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    /// The underlying HTTP client (cheap to clone, shares the connection pool)
+    /// e.g. for a market data `RestProbe`
+    pub fn http_client(&self) -> Client {
+        self.client.clone()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Use microsecond timestamps: signed requests send a microsecond `timestamp`
+    /// and every response reports times in microseconds (`TimeUnit::to_datetime` converts them)
+    pub fn with_time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.client = Self::build_http_client(&self.user_agent, time_unit);
+        self.time_unit = time_unit;
+        self
+    }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            error!("API Error Response: {}", error_text);
-            return Err(format!("API Error: {}", error_text).into());
+    pub fn time_unit(&self) -> TimeUnit {
+        self.time_unit
+    }
+
+    fn build_http_client(user_agent: &str, time_unit: TimeUnit) -> Client {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if time_unit != TimeUnit::Millisecond {
+            // Milliseconds is Binance's default, so the header is only needed for micros
+            headers.insert("X-MBX-TIME-UNIT", reqwest::header::HeaderValue::from_static(time_unit.header_value()));
         }
 
-        let response_text = response.text().await?;
+        Client::builder()
+            .user_agent(user_agent)
+            .default_headers(headers)
+            // One client per trader, reused for every call, so connections are pooled.
+            // Keep idle ones around between strategy ticks instead of re-handshaking TLS
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .pool_max_idle_per_host(POOL_MAX_IDLE_PER_HOST)
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .tcp_nodelay(true)
+            // Pings keep HTTP/2 connections alive through NAT/proxy idle timeouts
+            .http2_keep_alive_interval(TCP_KEEPALIVE)
+            .http2_keep_alive_timeout(Duration::from_secs(10))
+            .http2_keep_alive_while_idle(true)
+            .build()
+            .expect("HTTP client configuration is valid")
+    }
+
+    /// Add a backup key pair. When the active key gets rate-limited or rejected
+    /// (bad key, bad signature, disabled), the trader rotates to the next one
+    pub fn with_additional_credentials(mut self, api_key: String, secret_key: String) -> Self {
+        self.credentials.push(Credentials::new(api_key, secret_key));
+        self
+    }
+
+    /// Index of the key pair currently used to sign requests
+    pub fn active_key_index(&self) -> usize {
+        self.active_key.load(Ordering::SeqCst)
+    }
+
+    /// The API key currently in use
+    pub fn active_api_key(&self) -> &str {
+        &self.credentials[self.active_key_index()].api_key
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.credentials.len()
+    }
+
+    /// Switch to the next key pair (wrapping around) and return its index
+    pub fn rotate_key(&self) -> usize {
+        let count = self.credentials.len();
+        let previous = self.active_key.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |i| Some((i + 1) % count))
+            .unwrap_or_default();
+        let next = (previous + 1) % count;
+        if count > 1 {
+            warn!("Rotating API key: {} -> {}",
+                  self.credentials[previous].masked_api_key(),
+                  self.credentials[next].masked_api_key());
+        }
+        next
+    }
+
+    /// Enable dry-run mode: order placement is logged and answered with a
+    /// simulated response instead of being sent to the exchange.
+    /// Read-only calls (balances, prices, open orders) still hit the API.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Record every placed and canceled order in `log` (written in the background)
+    pub fn with_order_log(mut self, log: OrderLog) -> Self {
+        self.order_log = Some(log);
+        self
+    }
+
+    pub fn order_log(&self) -> Option<&OrderLog> {
+        self.order_log.as_ref()
+    }
+
+    /// Force the number of decimals used for a symbol's order quantity and price,
+    /// as `symbol -> (qty_decimals, price_decimals)`. These take priority over
+    /// any other precision source, which makes precision edge cases reproducible
+    /// in tests. Quantities are rounded down (never more than asked), prices to nearest
+    pub fn with_precision_overrides(mut self, overrides: HashMap<String, (u32, u32)>) -> Self {
+        self.precision_overrides = overrides;
+        self
+    }
+
+    /// Look up precision and symbol info for `old` under `new`, e.g. after Binance renames a pair
+    /// Covers the precision overrides and every exchange info lookup; each lookup
+    /// through the old name logs a warning
+    pub fn with_symbol_alias(mut self, old: &str, new: &str) -> Self {
+        self.symbol_aliases.insert(old.to_string(), new.to_string());
+        self
+    }
+
+    fn resolve_symbol<'a>(&'a self, symbol: &'a str) -> &'a str {
+        match self.symbol_aliases.get(symbol) {
+            Some(new) => {
+                warn!("{} was renamed to {}, using the new name", symbol, new);
+                new
+            }
+            None => symbol,
+        }
+    }
+
+    /// (qty_decimals, price_decimals) forced for a symbol, following aliases
+    fn precision_override(&self, symbol: &str) -> Option<(u32, u32)> {
+        self.precision_overrides.get(self.resolve_symbol(symbol)).copied()
+    }
+
+    /// Run `sync_time` before a signed call whenever the last sync is older than `interval`
+    /// (or never happened), so a slowly drifting clock is corrected without a restart.
+    /// Independently of this, a -1021 rejection always triggers one re-sync and retry
+    pub fn with_time_sync_interval(mut self, interval: Duration) -> Self {
+        self.time_sync_interval = Some(interval);
+        self
+    }
+
+    /// Drift that `sync_time` reports as dangerous (default `DEFAULT_CLOCK_DRIFT_THRESHOLD`)
+    pub fn with_clock_drift_threshold(mut self, threshold: Duration) -> Self {
+        self.clock_drift_threshold = threshold;
+        self
+    }
+
+    /// Register a callback for when `sync_time` measures drift over the threshold,
+    /// e.g. to raise an alert before orders start failing with -1021
+    pub fn on_clock_drift(mut self, hook: ClockDriftHook) -> Self {
+        self.on_clock_drift = Some(hook);
+        self
+    }
+
+    /// Check SELL orders against the free balance of the base asset before sending them
+    /// (an extra account request per sell). `Cap` trims the quantity to what we hold,
+    /// `Strict` refuses the order instead. Buys are never affected
+    pub fn with_sell_guard(mut self, sell_guard: SellGuard) -> Self {
+        self.sell_guard = sell_guard;
+        self
+    }
+
+    /// Check the symbol's status (from cached exchange info) before placing an order,
+    /// failing fast with `TradingError::SymbolHalted` instead of a Binance rejection
+    pub fn with_halt_check(mut self, halt_check: bool) -> Self {
+        self.halt_check = halt_check;
+        self
+    }
+
+    /// With the halt check on, error out if `symbol` isn't trading
+    async fn check_symbol_trading(&self, symbol: &str) -> Result<(), TradingError> {
+        if !self.halt_check {
+            return Ok(());
+        }
+        match self.symbol_status(symbol).await? {
+            Some(status) if status == "TRADING" => Ok(()),
+            status => {
+                let status = status.unwrap_or_else(|| "UNKNOWN".to_string());
+                error!("Refusing {} order: symbol status is {}", symbol, status);
+                Err(TradingError::SymbolHalted { symbol: symbol.to_string(), status })
+            }
+        }
+    }
+
+    /// Apply the sell guard: the quantity to actually send, or an error
+    async fn guard_sell_quantity(&self, symbol: &str, side: &OrderSide, quantity: f64) -> Result<f64, TradingError> {
+        if self.sell_guard == SellGuard::Off || *side == OrderSide::Buy {
+            return Ok(quantity);
+        }
+
+        let symbol_info = self.lookup_symbol_info(symbol).await?;
+        let asset = &symbol_info.base_asset;
+
+        let account = self.get_spot_account().await?;
+        let available = account.balances.iter()
+            .find(|b| &b.asset == asset)
+            .map_or(0.0, |b| b.free);
+        if quantity <= available {
+            return Ok(quantity);
+        }
+
+        let step_size = symbol_info.lot_step_size().unwrap_or(0.0);
+        let capped = round_to_step(available, step_size, RoundingMode::Down);
+        if self.sell_guard == SellGuard::Strict || capped <= 0.0 {
+            error!("Refusing to sell {} {}: only {} free", quantity, asset, available);
+            return Err(TradingError::InsufficientHoldings { asset: asset.clone(), requested: quantity, available });
+        }
+
+        warn!("Capping {} sell from {} to the free balance of {} {}", symbol, quantity, capped, asset);
+        Ok(capped)
+    }
+
+    /// `guard_sell_quantity` for an exact amount - it stays exact unless the guard caps it
+    async fn guard_sell_amount(&self, symbol: &str, side: &OrderSide, quantity: Amount) -> Result<Amount, TradingError> {
+        let requested = quantity.to_f64();
+        let guarded = self.guard_sell_quantity(symbol, side, requested).await?;
+        Ok(if guarded == requested { quantity } else { Amount::from(guarded) })
+    }
+
+    /// Quantity as sent in the `quantity` parameter, formatted straight from the decimal
+    /// Precision comes from an override, else the cached LOT_SIZE step, else 8 decimals
+    fn format_order_quantity(&self, symbol: &str, quantity: Decimal) -> String {
+        if let Some((qty_decimals, _)) = self.precision_override(symbol) {
+            return format_decimal_to_step(quantity, Decimal::new(1, qty_decimals), RoundingMode::Down);
+        }
+        match self.cached_symbol_info(symbol).and_then(|info| info.lot_step_size()).and_then(Decimal::from_f64) {
+            Some(step_size) => format_decimal_to_step(quantity, step_size, RoundingMode::Down),
+            None => format_decimal_to_step(quantity, Decimal::new(1, 8), RoundingMode::Nearest),
+        }
+    }
+
+    /// Price as sent in the `price` parameter, formatted straight from the decimal
+    /// Precision comes from an override, else the cached tick size, else 2 decimals
+    fn format_order_price(&self, symbol: &str, price: Decimal) -> String {
+        if let Some((_, price_decimals)) = self.precision_override(symbol) {
+            return format_decimal_to_step(price, Decimal::new(1, price_decimals), RoundingMode::Nearest);
+        }
+        match self.cached_symbol_info(symbol).and_then(|info| info.tick_size()) {
+            Some(tick_size) => format_price_to_tick(price, tick_size),
+            None => format_decimal_to_step(price, Decimal::new(1, 2), RoundingMode::Nearest),
+        }
+    }
+
+    /// Quote amount as sent in the `quoteOrderQty` parameter, rounded down so we never spend more
+    /// Precision comes from an override's price decimals, else the cached quoteAssetPrecision,
+    /// else 8 decimals
+    fn format_order_quote_quantity(&self, symbol: &str, quote_quantity: Decimal) -> String {
+        let decimals = match self.precision_override(symbol) {
+            Some((_, price_decimals)) => price_decimals,
+            None => self.cached_symbol_info(symbol).and_then(|info| info.quote_asset_precision).unwrap_or(8),
+        };
+        format_decimal_to_step(quote_quantity, Decimal::new(1, decimals), RoundingMode::Down)
+    }
+
+    /// Symbol info from exchangeInfo if it's already cached - never fetches
+    fn cached_symbol_info(&self, symbol: &str) -> Option<SymbolInfo> {
+        let cache = self.exchange_info_cache.lock().unwrap();
+        let (_, info) = cache.as_ref()?;
+        let symbol = self.resolve_symbol(symbol);
+        info.symbols.iter().find(|s| s.symbol == symbol).cloned()
+    }
+
+    /// Symbol info from exchangeInfo, fetching it if the cache is stale; follows aliases
+    async fn lookup_symbol_info(&self, symbol: &str) -> Result<SymbolInfo, TradingError> {
+        let info = self.cached_exchange_info().await?;
+        let resolved = self.resolve_symbol(symbol);
+        info.symbols.iter().find(|s| s.symbol == resolved).cloned()
+            .ok_or_else(|| TradingError::InvalidSymbol(symbol.to_string()))
+    }
+
+    /// Cap the total time of each signed request, including every retry, at `deadline`.
+    /// Past it the call fails with `TradingError::DeadlineExceeded` instead of retrying
+    pub fn with_request_deadline(mut self, deadline: Duration) -> Self {
+        self.request_deadline = Some(deadline);
+        self
+    }
+
+    /// Enforce `limits` right away instead of waiting for the first exchange info fetch
+    /// Fetching exchange info later replaces them with the account's actual caps
+    pub fn with_rate_limits(self, limits: RateLimits) -> Self {
+        self.apply_rate_limits(&limits);
+        self
+    }
+
+    /// The caps requests are currently held to; None until exchange info was fetched
+    /// (or `with_rate_limits` was used)
+    pub fn rate_limits(&self) -> Option<RateLimits> {
+        self.rate_limiter().map(|limiter| limiter.limits())
+    }
+
+    /// The active limiter, e.g. to show `usage()` next to the limits
+    pub fn rate_limiter(&self) -> Option<Arc<RateLimiter>> {
+        self.rate_limiter.lock().unwrap().clone()
+    }
+
+    fn apply_rate_limits(&self, limits: &RateLimits) {
+        if limits.is_empty() {
+            return;
+        }
+        let mut limiter = self.rate_limiter.lock().unwrap();
+        match limiter.as_ref() {
+            Some(existing) => existing.set_limits(limits),
+            None => *limiter = Some(Arc::new(RateLimiter::new(limits))),
+        }
+        info!("Rate limits: {}", limits.all().map(|l| l.to_string()).collect::<Vec<_>>().join(", "));
+    }
+
+    /// Wait until one more request (an order, if `is_order`) fits under the rate limits
+    /// Every request counts as weight 1: the real endpoint weights aren't known here,
+    /// so heavy calls like exchangeInfo are undercounted
+    async fn throttle(&self, is_order: bool) {
+        if let Some(limiter) = self.rate_limiter() {
+            limiter.acquire(1, is_order).await;
+        }
+    }
+
+    /// How long after its timestamp a signed request may arrive, sent as `recvWindow`
+    /// A larger window helps on slow links (fewer -1021 rejections). Values above
+    /// `MAX_RECV_WINDOW`, which Binance would reject, are clamped to it; 0 becomes 1
+    pub fn with_recv_window(mut self, ms: u64) -> Self {
+        let clamped = ms.clamp(1, MAX_RECV_WINDOW);
+        if clamped != ms {
+            warn!("recvWindow {}ms is outside 1-{}ms, using {}ms", ms, MAX_RECV_WINDOW, clamped);
+        }
+        self.recv_window = clamped;
+        self
+    }
+
+    pub fn recv_window(&self) -> u64 {
+        self.recv_window
+    }
+
+    /// How GETs answered with a 5xx are retried: up to `retries` times, waiting `backoff`
+    /// before the first retry and twice as long before each next one. Signed calls also
+    /// stop retrying when the request deadline would pass. Other methods are never retried;
+    /// they fail with `TradingError::ServerError` right away
+    pub fn with_server_error_retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.server_error_retries = retries;
+        self.server_error_backoff = backoff;
+        self
+    }
+
+    /// Wait before the next retry after a 5xx, or None when retries are used up
+    /// (or the wait would run past `deadline`)
+    fn server_error_backoff(&self, retry: u32, deadline: Option<Instant>) -> Option<Duration> {
+        if retry >= self.server_error_retries {
+            return None;
+        }
+        let delay = self.server_error_backoff.saturating_mul(2u32.saturating_pow(retry));
+        match deadline {
+            Some(at) if Instant::now() + delay >= at => None,
+            _ => Some(delay),
+        }
+    }
+
+    /// Send a public (unsigned) GET, retrying on 5xx
+    /// Other responses are returned as-is for the caller to parse
+    async fn send_public(&self, request: reqwest::RequestBuilder) -> Result<(StatusCode, String), TradingError> {
+        let mut retries = 0;
+        loop {
+            let attempt = request.try_clone().ok_or("Request can't be retried")?;
+            self.throttle(false).await;
+            let response = attempt.send().await?;
+            let status = response.status();
+            let response_text = response.text().await?;
+            if !status.is_server_error() {
+                return Ok((status, response_text));
+            }
+
+            let Some(delay) = self.server_error_backoff(retries, None) else {
+                return Err(TradingError::ServerError { status: status.as_u16() });
+            };
+            retries += 1;
+            warn!("Server error {} on a public request, retry {} in {:?}", status, retries, delay);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Reject any order whose notional value (price * quantity, in quote asset)
+    /// exceeds `limit` with `TradingError::OrderTooLarge`, before it is sent.
+    /// Market orders are valued at the current price.
+    pub fn with_max_order_notional(mut self, limit: f64) -> Self {
+        self.max_order_notional = Some(limit);
+        self
+    }
+
+    /// Enforce the max order notional, if one is configured
+    /// `price` is None for market orders, which are valued at the current price
+    async fn check_order_notional(&self, symbol: &str, quantity: f64, price: Option<f64>) -> Result<(), TradingError> {
+        let Some(limit) = self.max_order_notional else {
+            return Ok(());
+        };
+
+        let price = match price {
+            Some(p) => p,
+            None => self.get_current_price(symbol).await?,
+        };
+
+        let notional = price * quantity;
+        if notional > limit {
+            error!("Refusing {} order: notional {:.2} exceeds limit {:.2}", symbol, notional, limit);
+            return Err(TradingError::OrderTooLarge { notional, limit });
+        }
+        Ok(())
+    }
+
+    /// Build the fake response returned for orders placed in dry-run mode
+    /// Market orders are reported as filled, limit orders as resting (NEW)
+    fn simulated_order(&self, symbol: &str, side: &OrderSide, quantity: f64, price: Option<f64>) -> OrderResponse {
+        let order_id = OrderId(self.next_dry_run_id.fetch_add(1, Ordering::Relaxed));
+        info!("[DRY RUN] {} {} {} (order {} not sent)", side.as_str(), quantity, symbol, order_id);
+
+        let order = OrderResponse {
+            symbol: symbol.to_string(),
+            order_id,
+            order_list_id: -1,
+            client_order_id: format!("dry_run_{}", order_id),
+            transact_time: Some(self.time_unit.now()),
+            price: format_decimal(price.unwrap_or(0.0), 8),
+            orig_qty: format_decimal(quantity, 8),
+            executed_qty: format_decimal(if price.is_some() { 0.0 } else { quantity }, 8),
+            cummulative_quote_qty: "0.00000000".to_string(),
+            status: if price.is_some() { OrderStatus::New } else { OrderStatus::Filled },
+            time_in_force: "GTC".to_string(),
+            order_type: if price.is_some() { "LIMIT" } else { "MARKET" }.to_string(),
+            side: side.as_str().to_string(),
+            time: None,
+            rejection: None,
+        };
+        self.log_order("simulated", &order);
+        order
+    }
+
+    /// Timestamp for the next signed request, strictly greater than the previous one
+    /// Local time corrected by the offset from `sync_time`. Requests in the same
+    /// millisecond (or microsecond) get bumped by one unit, so rapid identical
+    /// requests still differ in their query and signature
+    fn next_timestamp(&self) -> u64 {
+        let offset_ms = self.clock_offset_ms.load(Ordering::SeqCst);
+        let offset = match self.time_unit {
+            TimeUnit::Millisecond => offset_ms,
+            TimeUnit::Microsecond => offset_ms * 1000,
+        };
+        let now = self.time_unit.now().saturating_add_signed(offset);
+        let previous = self.last_timestamp
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| Some(now.max(last + 1)))
+            .unwrap_or_else(|last| last);
+        now.max(previous + 1)
+    }
+
+    /// Sign and send a request to a private endpoint, returning the raw response body
+    /// A strictly increasing `timestamp` and the `recvWindow` are added here. GET requests carry the signed query in the URL,
+    /// everything else sends it as a form body (that's what Binance expects).
+    ///
+    /// The key pair is picked once per attempt, so the api key header and the
+    /// signature always belong to the same pair. On auth/rate-limit errors the
+    /// trader rotates to the next key and retries - a rejected request was never
+    /// executed, so this is safe even for orders.
+    ///
+    /// A 5xx becomes `TradingError::ServerError`. GETs are retried first (see
+    /// `with_server_error_retries`); anything else may have been executed, so it isn't.
+    async fn send_signed(
+        &self,
+        method: Method,
+        endpoint: &str,
+        params: &HashMap<String, String>,
+    ) -> Result<String, TradingError> {
+        let deadline = self.request_deadline.map(|budget| (budget, Instant::now() + budget));
+        let is_order = method == Method::POST && endpoint.starts_with("/api/v3/order");
+        let mut attempts = 0;
+        let mut server_error_retries = 0;
+        let mut resynced = false;
+
+        let sync_due = self.time_sync_interval.is_some_and(|interval| {
+            self.last_time_sync.lock().unwrap().is_none_or(|at| at.elapsed() >= interval)
+        });
+        if sync_due {
+            if let Err(e) = self.sync_time().await {
+                warn!("Periodic time sync failed, keeping the old offset: {}", e);
+            }
+        }
+
+        loop {
+            attempts += 1;
+            self.throttle(is_order).await;
+            let key_index = self.active_key_index();
+            let credentials = &self.credentials[key_index];
+
+            let mut params = params.clone();
+            let timestamp = self.next_timestamp();
+            params.insert("timestamp".to_string(), timestamp.to_string());
+            params.insert("recvWindow".to_string(), self.recv_window.to_string());
+
+            let query_string = self.build_query_string(&params);
+            let signature = Self::sign_with(&credentials.secret_key, &query_string);
+            let signed_query = format!("{}&signature={}", query_string, signature);
+
+            let request = if method == Method::GET {
+                self.client.get(format!("{}{}?{}", self.base_url, endpoint, signed_query))
+            } else {
+                self.client
+                    .request(method.clone(), format!("{}{}", self.base_url, endpoint))
+                    .header("Content-Type", "application/x-www-form-urlencoded")
+                    .body(signed_query)
+            };
+
+            let exchange = async {
+                let response = request
+                    .header("X-MBX-APIKEY", &credentials.api_key)
+                    .send()
+                    .await?;
+                let status = response.status();
+                Ok::<_, reqwest::Error>((status, response.text().await?))
+            };
+
+            // Each attempt only gets what's left of the overall budget
+            let (status, response_text) = match deadline {
+                Some((budget, at)) => tokio::time::timeout_at(at, exchange).await
+                    .map_err(|_| TradingError::DeadlineExceeded { budget })??,
+                None => exchange.await?,
+            };
+
+            if status.is_success() {
+                return Ok(response_text);
+            }
+
+            if status.is_server_error() {
+                // Only a GET is safe to repeat: a POST may have been executed despite the 5xx
+                let retry = (method == Method::GET)
+                    .then(|| self.server_error_backoff(server_error_retries, deadline.map(|(_, at)| at)))
+                    .flatten();
+                let Some(delay) = retry else {
+                    error!("Server error from {} ({}): {}", endpoint, status, response_text);
+                    return Err(TradingError::ServerError { status: status.as_u16() });
+                };
+                server_error_retries += 1;
+                attempts -= 1;  // Key rotation only counts key problems
+                warn!("Server error {} from {}, retry {} in {:?}", status, endpoint, server_error_retries, delay);
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+
+            error!("API Error Response from {} ({}): {}", endpoint, status, response_text);
+            let api_error = TradingError::from_response_body(&response_text);
+
+            let out_of_time = deadline.is_some_and(|(_, at)| Instant::now() >= at);
+            let timestamp_rejected = matches!(
+                api_error,
+                Some(TradingError::ApiError { code: TradingError::TIMESTAMP_OUT_OF_WINDOW, .. })
+            );
+            if timestamp_rejected && !resynced && !out_of_time {
+                // Rejected before execution, so resending is safe even for orders
+                resynced = true;
+                warn!("Timestamp rejected by {}, re-syncing the clock and retrying", endpoint);
+                match self.sync_time().await {
+                    Ok(_) => {
+                        attempts -= 1;
+                        continue;
+                    }
+                    Err(e) => warn!("Time sync failed: {}", e),
+                }
+            }
+
+            if Self::is_key_problem(status, api_error.as_ref()) && attempts < self.credentials.len() && !out_of_time {
+                // Only rotate if nobody else already did while this request was in flight
+                if self.active_key_index() == key_index {
+                    self.rotate_key();
+                }
+                continue;
+            }
+
+            return match api_error {
+                Some(api_error) => Err(api_error),
+                None => Err(format!("API Error: {}", response_text).into()),
+            };
+        }
+    }
+
+    /// Append an order event to the order log, if one is configured
+    fn log_order(&self, event: &str, order: &OrderResponse) {
+        if let Some(log) = &self.order_log {
+            log.record(event, order);
+        }
+    }
+
+    /// Log why the exchange refused an order, so the logs say more than "failed"
+    fn log_rejection(symbol: &str, err: &TradingError) {
+        if let Some(reason) = err.reject_reason() {
+            error!("{} order rejected by exchange: {}", symbol, reason);
+        }
+    }
+
+    /// Errors that are tied to the key pair in use rather than to the request itself
+    fn is_key_problem(status: StatusCode, api_error: Option<&TradingError>) -> bool {
+        if status == StatusCode::UNAUTHORIZED || status == StatusCode::TOO_MANY_REQUESTS {
+            return true;
+        }
+        matches!(
+            api_error,
+            Some(TradingError::ApiError { code: -1015, .. })  // Too many new orders
+                | Some(TradingError::Signature(_))  // Invalid signature (wrong secret)
+                | Some(TradingError::ApiError { code: -2014, .. })  // API-key format invalid
+                | Some(TradingError::ApiError { code: -2015, .. })  // Invalid API-key, IP, or permissions
+        )
+    }
+
+    /// The spot account as Binance returns it: balances plus permissions and commission rates
+    pub async fn get_spot_account(&self) -> Result<AccountInfo, TradingError> {
+        let params = HashMap::new();
+        let response_text = self.send_signed(Method::GET, "/api/v3/account", &params).await?;
         info!("Account API Response: {}", response_text);
         
-        let account_info: AccountInfo = serde_json::from_str(&response_text)
-            .map_err(|e| format!("Failed to parse account info: {}. Response was: {}", e, response_text))?;
+        let account_info: AccountInfo = parse_response("account info", &response_text)?;
         
         Ok(account_info)
     }
 
+    /// Fetch balances for the requested wallet types as one combined view
+    /// Only `AccountType::Spot` is implemented; other types are skipped with a
+    /// warning and simply don't appear in the result
+    pub async fn get_account_info(&self, account_types: &[AccountType]) -> Result<CombinedBalances, TradingError> {
+        let mut combined = CombinedBalances::default();
+
+        for account_type in account_types {
+            match account_type {
+                AccountType::Spot => {
+                    let account_info = self.get_spot_account().await?;
+                    combined.wallets.insert(AccountType::Spot, account_info.balances);
+                }
+                AccountType::Margin => {
+                    warn!("{:?} balances are not supported yet, skipping", account_type);
+                }
+            }
+        }
+
+        Ok(combined)
+    }
+
     pub async fn place_market_order(
         &self,
         symbol: &str,
         side: OrderSide,
-        quantity: f64,
-    ) -> Result<OrderResponse, Box<dyn std::error::Error>> {
-        let endpoint = "/api/v3/order";
-        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
-        
+        quantity: impl Into<Amount>,
+    ) -> Result<OrderResponse, TradingError> {
+        let quantity = quantity.into();
+        let order = NewOrderRequest {
+            symbol: symbol.to_string(),
+            side,
+            order_type: OrderType::Market,
+            time_in_force: None,
+            quantity: Some(quantity.to_f64()),
+            quote_order_qty: None,
+            price: None,
+            timestamp: None,
+        };
+        self.submit_order(&order, OrderAmounts { quantity: Some(quantity), ..OrderAmounts::of(&order) }, None).await
+    }
+
+    /// Market order for a fixed amount of the quote asset, e.g. "buy $100 worth of BTC"
+    /// Sent as `quoteOrderQty`, so Binance works out the base quantity from the fill prices
+    pub async fn place_market_order_by_quote(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        quote_qty: impl Into<Amount>,
+    ) -> Result<OrderResponse, TradingError> {
+        let quote_qty = quote_qty.into();
+        let order = NewOrderRequest {
+            symbol: symbol.to_string(),
+            side,
+            order_type: OrderType::Market,
+            time_in_force: None,
+            quantity: None,
+            quote_order_qty: Some(quote_qty.to_f64()),
+            price: None,
+            timestamp: None,
+        };
+        let amounts = OrderAmounts { quantity: None, quote_quantity: Some(quote_qty), price: None };
+        self.submit_order(&order, amounts, None).await
+    }
+
+    pub async fn place_limit_order(
+        &self,
+        symbol: &str,
+        side: OrderSide,
+        quantity: impl Into<Amount>,
+        price: impl Into<Amount>,
+    ) -> Result<OrderResponse, TradingError> {
+        let (quantity, price) = (quantity.into(), price.into());
+        let order = NewOrderRequest {
+            symbol: symbol.to_string(),
+            side,
+            order_type: OrderType::Limit,
+            time_in_force: Some(TimeInForce::GoodTillCanceled),
+            quantity: Some(quantity.to_f64()),
+            quote_order_qty: None,
+            price: Some(price.to_f64()),
+            timestamp: None,
+        };
+        let amounts = OrderAmounts { quantity: Some(quantity), quote_quantity: None, price: Some(price) };
+        self.submit_order(&order, amounts, None).await
+    }
+
+    /// Place any order described by a `NewOrderRequest`
+    ///
+    /// Needs exactly one of `quantity` and `quote_order_qty` (the latter only for
+    /// MARKET orders), and a `price` for LIMIT orders; LIMIT orders without a
+    /// `time_in_force` rest until canceled (GTC). `timestamp` is ignored - every
+    /// signed request gets a fresh one. The usual guards (halt check, sell guard,
+    /// max notional, dry run) apply as for the other order methods.
+    pub async fn place_order(&self, order: &NewOrderRequest) -> Result<OrderResponse, TradingError> {
+        self.submit_order(order, OrderAmounts::of(order), None).await
+    }
+
+    /// Validate, guard and send an order, optionally under our own clientOrderId
+    /// `amounts` carries the exact values to send; the f64s in `order` are only a fallback
+    async fn submit_order(
+        &self,
+        order: &NewOrderRequest,
+        amounts: OrderAmounts,
+        client_order_id: Option<&str>,
+    ) -> Result<OrderResponse, TradingError> {
+        let symbol = order.symbol.as_str();
+        let side = order.side;
+        let invalid = |reason: &str| TradingError::InvalidParameter(format!("{} order for {} {}", order.order_type.as_str(), symbol, reason));
+
+        let price = match order.order_type {
+            OrderType::Market => None,
+            OrderType::Limit => Some(amounts.price.ok_or_else(|| invalid("needs a price"))?),
+        };
+        if amounts.quote_quantity.is_some() && (amounts.quantity.is_some() || price.is_some()) {
+            return Err(invalid("takes either quantity or quoteOrderQty, and quoteOrderQty only at market"));
+        }
+
+        self.check_symbol_trading(symbol).await?;
+        let quantity = match (amounts.quantity, amounts.quote_quantity) {
+            (Some(quantity), _) => {
+                let quantity = self.guard_sell_amount(symbol, &side, quantity).await?;
+                self.check_order_notional(symbol, quantity.to_f64(), price.map(Amount::to_f64)).await?;
+                Some(quantity)
+            }
+            (None, Some(quote_quantity)) => {
+                // Spending a fixed quote amount - that amount is the notional
+                self.check_order_notional(symbol, quote_quantity.to_f64(), Some(1.0)).await?;
+                None
+            }
+            (None, None) => return Err(invalid("needs a quantity or quoteOrderQty")),
+        };
+
+        if self.dry_run {
+            let simulated_quantity = match (quantity, amounts.quote_quantity) {
+                (Some(quantity), _) => quantity.to_f64(),
+                (None, quote_quantity) => quote_quantity.unwrap_or_default().to_f64() / self.get_current_price(symbol).await?,
+            };
+            return Ok(self.simulated_order(symbol, &side, simulated_quantity, price.map(Amount::to_f64)));
+        }
+
         let mut params = HashMap::new();
         params.insert("symbol".to_string(), symbol.to_string());
-        params.insert("side".to_string(), match side {
-            OrderSide::Buy => "BUY".to_string(),
-            OrderSide::Sell => "SELL".to_string(),
-        });
-        params.insert("type".to_string(), "MARKET".to_string());
-        params.insert("quantity".to_string(), format!("{:.8}", quantity));
-        params.insert("timestamp".to_string(), timestamp.to_string());
-        
-        let query_string = self.build_query_string(&params);
-        let signature = self.sign(&query_string);
-        
-        let url = format!("{}{}", self.base_url, endpoint);
-        let body = format!("{}&signature={}", query_string, signature);
-        
-        info!("Placing {} order for {} {} on testnet", 
-              match side { OrderSide::Buy => "BUY", OrderSide::Sell => "SELL" },
-              quantity, symbol);
-        
-        let response = self.client
-            .post(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .body(body)
-            .send()
-            .await?;
+        params.insert("side".to_string(), side.as_str().to_string());
+        params.insert("type".to_string(), order.order_type.as_str().to_string());
+        if let Some(quantity) = quantity {
+            params.insert("quantity".to_string(), self.format_order_quantity(symbol, quantity.decimal()));
+        }
+        if let Some(quote_quantity) = amounts.quote_quantity {
+            // The quote precision comes from the symbol info, so make sure it's cached
+            if self.precision_override(symbol).is_none() {
+                if let Err(e) = self.cached_exchange_info().await {
+                    warn!("No exchange info for {}, rounding quoteOrderQty down to 8 decimals: {}", symbol, e);
+                }
+            }
+            params.insert("quoteOrderQty".to_string(), self.format_order_quote_quantity(symbol, quote_quantity.decimal()));
+        }
+        if let Some(price) = price {
+            let time_in_force = order.time_in_force.as_ref().unwrap_or(&TimeInForce::GoodTillCanceled);
+            params.insert("timeInForce".to_string(), time_in_force.as_str().to_string());
+            params.insert("price".to_string(), self.format_order_price(symbol, price.decimal()));
+        }
+        if let Some(client_order_id) = client_order_id {
+            params.insert("newClientOrderId".to_string(), client_order_id.to_string());
+        }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            error!("Order placement failed: {}", error_text);
-            return Err(format!("Order Error: {}", error_text).into());
+        let amount = match (quantity, amounts.quote_quantity) {
+            (Some(quantity), _) => format!("{} {}", quantity, symbol),
+            (_, quote_quantity) => format!("{} worth of {}", quote_quantity.unwrap_or_default(), symbol),
+        };
+        match price {
+            Some(price) => info!("Placing {} limit order for {} at ${} on testnet", side.as_str(), amount, price),
+            None => info!("Placing {} order for {} on testnet", side.as_str(), amount),
         }
 
-        let order_response: OrderResponse = response.json().await?;
-        info!("Order placed successfully: ID {}", order_response.order_id);
+        let response_text = self.send_signed(Method::POST, "/api/v3/order", &params).await
+            .inspect_err(|e| Self::log_rejection(symbol, e))?;
+
+        let order_response: OrderResponse = serde_json::from_str(&response_text)?;
+        let kind = if price.is_some() { "Limit order" } else { "Order" };
+        if let Some(reason) = order_response.reject_reason() {
+            warn!("{} {} was rejected: {}", kind, order_response.order_id, reason);
+        } else {
+            info!("{} placed successfully: ID {}", kind, order_response.order_id);
+        }
+        self.log_order("placed", &order_response);
         Ok(order_response)
     }
 
-    pub async fn place_limit_order(
+    /// Place a batch of orders, at most `concurrency` in flight at once
+    ///
+    /// Every order is attempted; one failing doesn't stop the others, so check each
+    /// result. The results are in the same order as `requests`.
+    pub async fn place_orders(&self, requests: Vec<NewOrderRequest>, concurrency: usize) -> Vec<Result<OrderResponse, TradingError>> {
+        use futures_util::stream::{self, StreamExt};
+
+        stream::iter(requests.iter())
+            .map(|request| async move {
+                self.place_order(request).await
+                    .inspect_err(|e| warn!("Batch order for {} failed: {}", request.symbol, e))
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Place a market or limit order exactly once, keyed by `client_order_id`
+    ///
+    /// When placement times out (or Binance answers -1007 "send status unknown")
+    /// the order may or may not exist, so it's looked up by its clientOrderId
+    /// before anything is resent: if it landed, that order is returned; only if
+    /// Binance has never seen it is the placement retried, with the same id.
+    /// An id this trader has already placed is never sent again - its current
+    /// state is fetched instead. Either way the result is the order as the
+    /// exchange knows it.
+    pub async fn place_order_idempotent(
+        &self,
+        order: &NewOrderRequest,
+        client_order_id: &str,
+    ) -> Result<OrderResponse, TradingError> {
+        if self.placed_client_ids.lock().unwrap().contains(client_order_id) {
+            info!("Order {} was already placed, fetching it instead of resending", client_order_id);
+            return self.get_order_by_client_id(&order.symbol, client_order_id).await;
+        }
+
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let placed = self.submit_order(order, OrderAmounts::of(order), Some(client_order_id)).await;
+            let error = match placed {
+                Ok(order_response) => {
+                    self.placed_client_ids.lock().unwrap().insert(client_order_id.to_string());
+                    return Ok(order_response);
+                }
+                Err(e) if Self::is_send_status_unknown(&e) => e,
+                Err(e) => return Err(e),
+            };
+
+            warn!("Placing {} timed out ({}), checking whether it reached the exchange", client_order_id, error);
+            match self.get_order_by_client_id(&order.symbol, client_order_id).await {
+                Ok(existing) => {
+                    info!("Order {} did land (ID {}), not resending", client_order_id, existing.order_id);
+                    self.placed_client_ids.lock().unwrap().insert(client_order_id.to_string());
+                    return Ok(existing);
+                }
+                Err(TradingError::ApiError { code: TradingError::NO_SUCH_ORDER, .. }) => {
+                    if attempts >= IDEMPOTENT_PLACE_ATTEMPTS {
+                        return Err(error);
+                    }
+                    info!("Order {} never reached the exchange, retrying", client_order_id);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Whether a failed placement may still have gone through (timeouts, dropped connections)
+    fn is_send_status_unknown(err: &TradingError) -> bool {
+        match err {
+            TradingError::Network(e) => e.is_timeout() || e.is_request(),
+            TradingError::DeadlineExceeded { .. }
+            | TradingError::ServerError { .. }
+            | TradingError::ApiError { code: TradingError::SEND_STATUS_UNKNOWN, .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Place a server-side trailing stop that exits at market
+    ///
+    /// On Binance spot this is a STOP_LOSS order with `trailingDelta`: once the
+    /// price moves `trailing_delta_bps` basis points (1 bp = 0.01%) against the
+    /// best price seen since activation, a market order is triggered. With an
+    /// `activation_price` the trailing only starts once that price is reached;
+    /// without one it starts immediately. The delta must be within Binance's
+    /// allowed range (`TRAILING_DELTA_RANGE_BPS`), otherwise the order is rejected
+    /// locally with `TradingError::InvalidParameter`.
+    pub async fn place_trailing_stop_order(
         &self,
         symbol: &str,
         side: OrderSide,
-        quantity: f64,
-        price: f64,
-    ) -> Result<OrderResponse, Box<dyn std::error::Error>> {
-        let endpoint = "/api/v3/order";
-        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
-        
+        quantity: impl Into<Amount>,
+        trailing_delta_bps: u32,
+        activation_price: Option<f64>,
+    ) -> Result<OrderResponse, TradingError> {
+        if !TRAILING_DELTA_RANGE_BPS.contains(&trailing_delta_bps) {
+            return Err(TradingError::InvalidParameter(format!(
+                "trailing delta of {} bps is outside the allowed {}-{} bps",
+                trailing_delta_bps, TRAILING_DELTA_RANGE_BPS.start(), TRAILING_DELTA_RANGE_BPS.end()
+            )));
+        }
+        self.check_symbol_trading(symbol).await?;
+        let quantity = self.guard_sell_amount(symbol, &side, quantity.into()).await?;
+        self.check_order_notional(symbol, quantity.to_f64(), activation_price).await?;
+
+        if self.dry_run {
+            // Reported as resting (NEW) - a stop isn't filled when it's placed
+            return Ok(self.simulated_order(symbol, &side, quantity.to_f64(), Some(activation_price.unwrap_or(0.0))));
+        }
+
         let mut params = HashMap::new();
         params.insert("symbol".to_string(), symbol.to_string());
-        params.insert("side".to_string(), match side {
-            OrderSide::Buy => "BUY".to_string(),
-            OrderSide::Sell => "SELL".to_string(),
-        });
-        params.insert("type".to_string(), "LIMIT".to_string());
-        params.insert("timeInForce".to_string(), "GTC".to_string()); // Good Till Canceled
-        params.insert("quantity".to_string(), format!("{:.8}", quantity));
-        params.insert("price".to_string(), format!("{:.2}", price));
-        params.insert("timestamp".to_string(), timestamp.to_string());
-        
-        let query_string = self.build_query_string(&params);
-        let signature = self.sign(&query_string);
-        
-        let url = format!("{}{}", self.base_url, endpoint);
-        let body = format!("{}&signature={}", query_string, signature);
-        
-        info!("Placing {} limit order for {} {} at ${} on testnet", 
-              match side { OrderSide::Buy => "BUY", OrderSide::Sell => "SELL" },
-              quantity, symbol, price);
-        
-        let response = self.client
-            .post(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .body(body)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            error!("Limit order placement failed: {}", error_text);
-            return Err(format!("Order Error: {}", error_text).into());
+        params.insert("side".to_string(), side.as_str().to_string());
+        params.insert("type".to_string(), "STOP_LOSS".to_string());
+        params.insert("quantity".to_string(), self.format_order_quantity(symbol, quantity.decimal()));
+        params.insert("trailingDelta".to_string(), trailing_delta_bps.to_string());
+        if let Some(activation) = activation_price {
+            params.insert("stopPrice".to_string(), self.format_order_price(symbol, Amount::from(activation).decimal()));
         }
 
-        let order_response: OrderResponse = response.json().await?;
-        info!("Limit order placed successfully: ID {}", order_response.order_id);
+        info!("Placing {} trailing stop for {} {} ({} bps) on testnet",
+              side.as_str(),
+              quantity, symbol, trailing_delta_bps);
+
+        let response_text = self.send_signed(Method::POST, "/api/v3/order", &params).await
+            .inspect_err(|e| Self::log_rejection(symbol, e))?;
+
+        let order_response: OrderResponse = serde_json::from_str(&response_text)?;
+        info!("Trailing stop placed successfully: ID {}", order_response.order_id);
+        self.log_order("placed", &order_response);
         Ok(order_response)
     }
 
-    pub async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<OrderResponse>, Box<dyn std::error::Error>> {
-        let endpoint = "/api/v3/openOrders";
-        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
-        
+    pub async fn get_open_orders(&self, symbol: Option<&str>) -> Result<Vec<OrderResponse>, TradingError> {
         let mut params = HashMap::new();
         if let Some(s) = symbol {
             params.insert("symbol".to_string(), s.to_string());
         }
-        params.insert("timestamp".to_string(), timestamp.to_string());
         
-        let query_string = self.build_query_string(&params);
-        let signature = self.sign(&query_string);
+        let response_text = self.send_signed(Method::GET, "/api/v3/openOrders", &params).await?;
+        info!("Open orders API response: {}", response_text);
         
-        let url = format!("{}{}?{}&signature={}", 
-                         self.base_url, endpoint, query_string, signature);
+        let orders: Vec<OrderResponse> = parse_response("open orders", &response_text)?;
         
-        let response = self.client
-            .get(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .send()
-            .await?;
+        Ok(orders)
+    }
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            error!("Get orders API Error: {}", error_text);
-            return Err(format!("API Error: {}", error_text).into());
+    /// Cancel every open order on `symbol` that has been resting longer than `age`
+    ///
+    /// Age comes from `OrderResponse::timestamp()`; orders without a timestamp are
+    /// left alone. The cancels run concurrently. Orders that fill or disappear in
+    /// the meantime are skipped, and failed cancels are logged rather than aborting
+    /// the rest. Returns the orders that were actually canceled.
+    pub async fn cancel_orders_older_than(&self, symbol: &str, age: Duration) -> Result<Vec<OrderResponse>, TradingError> {
+        let now = chrono::Utc::now();
+        let stale: Vec<OrderId> = self.get_open_orders(Some(symbol)).await?
+            .into_iter()
+            .filter(|order| {
+                order.timestamp()
+                    .and_then(|ts| self.time_unit.to_datetime(ts))
+                    .and_then(|placed_at| (now - placed_at).to_std().ok())
+                    .is_some_and(|resting| resting > age)
+            })
+            .map(|order| order.order_id)
+            .collect();
+
+        if stale.is_empty() {
+            return Ok(Vec::new());
         }
+        info!("Canceling {} {} order(s) older than {:?}", stale.len(), symbol, age);
+
+        let results = futures_util::future::join_all(
+            stale.iter().map(|&order_id| self.cancel_order_if_open(symbol, order_id))
+        ).await;
+
+        let mut canceled = Vec::new();
+        for (order_id, result) in stale.into_iter().zip(results) {
+            match result {
+                Ok(CancelOutcome::Canceled(order)) => canceled.push(order),
+                Ok(CancelOutcome::AlreadyGone) => {}
+                Err(e) => error!("Failed to cancel stale order {} on {}: {}", order_id, symbol, e),
+            }
+        }
+        Ok(canceled)
+    }
+
+    /// Currently open OCO order lists across all symbols
+    /// Their legs also appear in `get_open_orders`, with `order_list_id` set
+    pub async fn get_open_oco_orders(&self) -> Result<Vec<OcoOrderResponse>, TradingError> {
+        let params = HashMap::new();
+        let response_text = self.send_signed(Method::GET, "/api/v3/openOrderList", &params).await?;
+        info!("Open order lists API response: {}", response_text);
+
+        let lists: Vec<OcoOrderResponse> = parse_response("open order lists", &response_text)?;
+
+        Ok(lists)
+    }
+
+    /// Cancel an OCO list by its `orderListId`, which cancels both legs
+    pub async fn cancel_oco_order(&self, symbol: &str, order_list_id: i64) -> Result<OcoOrderResponse, TradingError> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+        params.insert("orderListId".to_string(), order_list_id.to_string());
+
+        info!("Canceling order list {} for {} on testnet", order_list_id, symbol);
+        let response_text = self.send_signed(Method::DELETE, "/api/v3/orderList", &params).await?;
+
+        let list: OcoOrderResponse = parse_response("canceled order list", &response_text)?;
+        info!("Order list {} canceled successfully", order_list_id);
+        Ok(list)
+    }
+
+    /// Our own trade history for a symbol, with the commission charged on each fill
+    /// `limit` defaults to 500 on Binance's side (max 1000)
+    pub async fn get_my_trades(&self, symbol: &str, limit: Option<u32>) -> Result<Vec<AccountTrade>, TradingError> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+        if let Some(l) = limit {
+            params.insert("limit".to_string(), l.to_string());
+        }
+        self.fetch_my_trades(&params).await
+    }
+
+    /// All of our fills on `symbol` between `start_time` and `end_time` (ms, inclusive),
+    /// in 24h windows as Binance requires, paging by trade id within a busy window
+    pub async fn get_my_trades_range(&self, symbol: &str, start_time: u64, end_time: u64) -> Result<Vec<AccountTrade>, TradingError> {
+        collect_windowed_pages(
+            |cursor| {
+                let mut params = HashMap::new();
+                params.insert("symbol".to_string(), symbol.to_string());
+                params.insert("limit".to_string(), MAX_PAGE_SIZE.to_string());
+                match cursor {
+                    PageCursor::FromId(id) => {
+                        params.insert("fromId".to_string(), id.to_string());
+                    }
+                    PageCursor::Window { start, end } => {
+                        params.insert("startTime".to_string(), start.to_string());
+                        params.insert("endTime".to_string(), end.to_string());
+                    }
+                }
+                async move { self.fetch_my_trades(&params).await }
+            },
+            |trade| trade.id,
+            |trade| trade.time,
+            start_time,
+            end_time,
+            MAX_TIME_WINDOW,
+            MAX_PAGE_SIZE as usize,
+        ).await
+    }
+
+    async fn fetch_my_trades(&self, params: &HashMap<String, String>) -> Result<Vec<AccountTrade>, TradingError> {
+        let response_text = self.send_signed(Method::GET, "/api/v3/myTrades", params).await?;
+        info!("My trades API response: {}", response_text);
+
+        let trades: Vec<AccountTrade> = parse_response("trades", &response_text)?;
+
+        Ok(trades)
+    }
+
+    /// Orders on `symbol` in every status (open, filled, canceled...), oldest first
+    /// A range given with both ends must span at most 24 hours; `limit` defaults to 500, max 1000
+    pub async fn get_all_orders(&self, symbol: &str, start_time: Option<u64>, end_time: Option<u64>, limit: Option<u32>) -> Result<Vec<OrderResponse>, TradingError> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+        if let Some(start_time) = start_time {
+            params.insert("startTime".to_string(), start_time.to_string());
+        }
+        if let Some(end_time) = end_time {
+            params.insert("endTime".to_string(), end_time.to_string());
+        }
+        if let Some(limit) = limit {
+            params.insert("limit".to_string(), limit.to_string());
+        }
+        self.fetch_all_orders(&params).await
+    }
+
+    /// Every order on `symbol` placed between `start_time` and `end_time` (ms, inclusive),
+    /// in 24h windows as Binance requires, paging by order id within a busy window
+    pub async fn get_all_orders_range(&self, symbol: &str, start_time: u64, end_time: u64) -> Result<Vec<OrderResponse>, TradingError> {
+        collect_windowed_pages(
+            |cursor| {
+                let mut params = HashMap::new();
+                params.insert("symbol".to_string(), symbol.to_string());
+                params.insert("limit".to_string(), MAX_PAGE_SIZE.to_string());
+                match cursor {
+                    PageCursor::FromId(order_id) => {
+                        params.insert("orderId".to_string(), order_id.to_string());
+                    }
+                    PageCursor::Window { start, end } => {
+                        params.insert("startTime".to_string(), start.to_string());
+                        params.insert("endTime".to_string(), end.to_string());
+                    }
+                }
+                async move { self.fetch_all_orders(&params).await }
+            },
+            |order| order.order_id.0,
+            |order| order.timestamp().unwrap_or(0),
+            start_time,
+            end_time,
+            MAX_TIME_WINDOW,
+            MAX_PAGE_SIZE as usize,
+        ).await
+    }
+
+    async fn fetch_all_orders(&self, params: &HashMap<String, String>) -> Result<Vec<OrderResponse>, TradingError> {
+        let response_text = self.send_signed(Method::GET, "/api/v3/allOrders", params).await?;
+        info!("All orders API response: {}", response_text);
+
+        let orders: Vec<OrderResponse> = parse_response("orders", &response_text)?;
 
-        let response_text = response.text().await?;
-        info!("Open orders API response: {}", response_text);
-        
-        let orders: Vec<OrderResponse> = serde_json::from_str(&response_text)
-            .map_err(|e| format!("Failed to parse open orders: {}. Response was: {}", e, response_text))?;
-        
         Ok(orders)
     }
 
-    pub async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<OrderResponse, Box<dyn std::error::Error>> {
-        let endpoint = "/api/v3/order";
-        let timestamp = chrono::Utc::now().timestamp_millis() as u64;
-        
+    pub async fn cancel_order(&self, symbol: &str, order_id: OrderId) -> Result<OrderResponse, TradingError> {
         let mut params = HashMap::new();
         params.insert("symbol".to_string(), symbol.to_string());
         params.insert("orderId".to_string(), order_id.to_string());
-        params.insert("timestamp".to_string(), timestamp.to_string());
-        
-        let query_string = self.build_query_string(&params);
-        let signature = self.sign(&query_string);
-        
-        let url = format!("{}{}", self.base_url, endpoint);
-        let body = format!("{}&signature={}", query_string, signature);
         
         info!("Canceling order {} for {} on testnet", order_id, symbol);
         
-        let response = self.client
-            .delete(&url)
-            .header("X-MBX-APIKEY", &self.api_key)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .body(body)
-            .send()
-            .await?;
+        // Errors keep the Binance error code so callers can tell "already gone" apart
+        let response_text = self.send_signed(Method::DELETE, "/api/v3/order", &params).await?;
 
-        if !response.status().is_success() {
-            let error_text = response.text().await?;
-            error!("Cancel order failed: {}", error_text);
-            return Err(format!("Cancel Error: {}", error_text).into());
+        let order_response: OrderResponse = serde_json::from_str(&response_text)?;
+        info!("Order {} canceled successfully", order_id);
+        self.log_order("canceled", &order_response);
+        Ok(order_response)
+    }
+
+    /// Cancel every open order on `symbol` in one request, returning the canceled orders
+    /// OCO lists are canceled too but not returned. No open orders is not an error
+    pub async fn cancel_all_orders(&self, symbol: &str) -> Result<Vec<OrderResponse>, TradingError> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+
+        info!("Canceling all open orders for {} on testnet", symbol);
+        let response_text = match self.send_signed(Method::DELETE, "/api/v3/openOrders", &params).await {
+            Ok(text) => text,
+            Err(TradingError::ApiError { code: TradingError::UNKNOWN_ORDER, .. }) => {
+                return Ok(Vec::new());  // Nothing was open
+            }
+            Err(e) => return Err(e),
+        };
+
+        let entries: Vec<Value> = parse_response("canceled orders", &response_text)?;
+        let mut canceled = Vec::new();
+        for entry in entries.into_iter().filter(|entry| entry.get("orderId").is_some()) {
+            let order: OrderResponse = serde_json::from_value(entry)?;
+            self.log_order("canceled", &order);
+            canceled.push(order);
         }
+        info!("Canceled {} open order(s) for {}", canceled.len(), symbol);
+        Ok(canceled)
+    }
+
+    /// Cancel an order, treating "it already filled/was canceled" as success
+    /// Binance answers -2011 "Unknown order sent." when the order isn't open anymore,
+    /// which is a normal race in reprice loops rather than a real failure
+    pub async fn cancel_order_if_open(&self, symbol: &str, order_id: OrderId) -> Result<CancelOutcome, TradingError> {
+        match self.cancel_order(symbol, order_id).await {
+            Ok(order_response) => Ok(CancelOutcome::Canceled(order_response)),
+            Err(TradingError::ApiError { code: TradingError::UNKNOWN_ORDER, .. }) => {
+                info!("Order {} for {} is no longer open, nothing to cancel", order_id, symbol);
+                Ok(CancelOutcome::AlreadyGone)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Current state of a single order
+    pub async fn get_order(&self, symbol: &str, order_id: OrderId) -> Result<OrderResponse, TradingError> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+        params.insert("orderId".to_string(), order_id.to_string());
+
+        let response_text = self.send_signed(Method::GET, "/api/v3/order", &params).await?;
+
+        let order_response: OrderResponse = parse_response("order", &response_text)?;
+
+        Ok(order_response)
+    }
+
+    /// Current state of an order, looked up by the clientOrderId it was placed with
+    pub async fn get_order_by_client_id(&self, symbol: &str, client_order_id: &str) -> Result<OrderResponse, TradingError> {
+        let mut params = HashMap::new();
+        params.insert("symbol".to_string(), symbol.to_string());
+        params.insert("origClientOrderId".to_string(), client_order_id.to_string());
+
+        let response_text = self.send_signed(Method::GET, "/api/v3/order", &params).await?;
+
+        let order_response: OrderResponse = parse_response("order", &response_text)?;
 
-        let order_response: OrderResponse = response.json().await?;
-        info!("Order {} canceled successfully", order_id);
         Ok(order_response)
     }
 
-    pub async fn get_current_price(&self, symbol: &str) -> Result<f64, Box<dyn std::error::Error>> {
+    /// Poll an order every `poll_interval` until it reaches a final status or `timeout` passes
+    /// Returns the last state seen either way - check `status.is_final()` to tell them apart
+    pub async fn wait_for_order(
+        &self,
+        symbol: &str,
+        order_id: OrderId,
+        timeout: Duration,
+        poll_interval: Duration,
+    ) -> Result<OrderResponse, TradingError> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let order = self.get_order(symbol, order_id).await?;
+            if order.status.is_final() || Instant::now() + poll_interval > deadline {
+                return Ok(order);
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Turn paying spot trading fees in BNB on or off
+    /// When turning it on, warns if there's no free BNB to pay with - fees then
+    /// come out of the traded asset at the full rate.
+    /// Note: this is a /sapi endpoint, which the spot testnet does not serve -
+    /// point `with_base_url` at the main API to use it
+    pub async fn set_bnb_fee_burn(&self, spot: bool) -> Result<BnbBurnStatus, TradingError> {
+        let mut params = HashMap::new();
+        params.insert("spotBNBBurn".to_string(), spot.to_string());
+
+        let response_text = self.send_signed(Method::POST, "/sapi/v1/bnbBurn", &params).await?;
+        let status: BnbBurnStatus = parse_response("BNB burn status", &response_text)?;
+
+        self.bnb_fee_burn.store(status.spot_bnb_burn, Ordering::SeqCst);
+        info!("Paying spot fees in BNB: {}", status.spot_bnb_burn);
+        if status.spot_bnb_burn {
+            self.bnb_discount_applies(&self.get_spot_account().await?);
+        }
+        Ok(status)
+    }
+
+    /// Current BNB-burn setting of the account (also a /sapi endpoint)
+    pub async fn get_bnb_burn_status(&self) -> Result<BnbBurnStatus, TradingError> {
+        let response_text = self.send_signed(Method::GET, "/sapi/v1/bnbBurn", &HashMap::new()).await?;
+        let status: BnbBurnStatus = parse_response("BNB burn status", &response_text)?;
+
+        self.bnb_fee_burn.store(status.spot_bnb_burn, Ordering::SeqCst);
+        Ok(status)
+    }
+
+    /// Last known spot BNB-burn setting, from `set_bnb_fee_burn` or `get_bnb_burn_status`
+    pub fn bnb_fee_burn_enabled(&self) -> bool {
+        self.bnb_fee_burn.load(Ordering::SeqCst)
+    }
+
+    /// Whether the BNB discount applies to `account`: burn is on and there's BNB to pay with
+    /// Warns when burn is on but the BNB balance is empty
+    fn bnb_discount_applies(&self, account: &AccountInfo) -> bool {
+        if !self.bnb_fee_burn_enabled() {
+            return false;
+        }
+        let free = account.balances.iter().find(|b| b.asset == "BNB").map_or(0.0, |b| b.free);
+        if free <= 0.0 {
+            warn!("Fees are set to be paid in BNB, but the BNB balance is empty - the discount won't apply");
+        }
+        free > 0.0
+    }
+
+    /// Fee estimator for this account's commission rates and BNB-burn state
+    /// The BNB discount only counts while burn is on and there's BNB to pay with.
+    /// None if the account response carried no commission rates
+    pub async fn fee_estimator(&self) -> Result<Option<FeeEstimator>, TradingError> {
+        let account = self.get_spot_account().await?;
+        Ok(FeeEstimator::from_account(&account, self.bnb_discount_applies(&account)))
+    }
+
+    /// Fetch daily balance snapshots (useful for plotting equity over time)
+    /// Note: this is a /sapi endpoint, which the spot testnet does not serve -
+    /// point `with_base_url` at the main API to use it
+    pub async fn get_account_snapshots(
+        &self,
+        snapshot_type: SnapshotType,
+        start_time: Option<u64>,
+        end_time: Option<u64>,
+        limit: Option<u32>,  // Binance accepts 7-30 days, default 7
+    ) -> Result<Vec<DailyAccountSnapshot>, TradingError> {
+        let mut params = HashMap::new();
+        params.insert("type".to_string(), snapshot_type.as_str().to_string());
+        if let Some(start) = start_time {
+            params.insert("startTime".to_string(), start.to_string());
+        }
+        if let Some(end) = end_time {
+            params.insert("endTime".to_string(), end.to_string());
+        }
+        if let Some(l) = limit {
+            params.insert("limit".to_string(), l.to_string());
+        }
+        
+        let response_text = self.send_signed(Method::GET, "/sapi/v1/accountSnapshot", &params).await?;
+        
+        let snapshot_response: AccountSnapshotResponse = parse_response("account snapshot", &response_text)?;
+        
+        // Binance reports some failures with HTTP 200 and a non-200 "code" in the body
+        if snapshot_response.code != 200 {
+            return Err(TradingError::ApiError { code: snapshot_response.code, msg: snapshot_response.msg });
+        }
+        
+        Ok(snapshot_response.snapshot_vos)
+    }
+
+    pub async fn get_current_price(&self, symbol: &str) -> Result<f64, TradingError> {
         let url = format!("{}/api/v3/ticker/price?symbol={}", self.base_url, symbol);
         
-        let response = self.client.get(&url).send().await?;
-        let data: Value = response.json().await?;
+        let (status, response_text) = self.send_public(self.client.get(&url)).await?;
+        if !status.is_success() {
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("API Error: {}", response_text).into(),
+            });
+        }
+        let data: Value = serde_json::from_str(&response_text)?;
         
         if let Some(price_str) = data["price"].as_str() {
-            Ok(price_str.parse()?)
+            Ok(price_str.parse().map_err(|e| format!("Failed to parse price '{}': {}", price_str, e))?)
         } else {
             Err("Could not parse price".into())
         }
     }
 
+    /// Latest prices for many symbols in a single request (one request weight instead of one per symbol)
+    /// An empty slice returns every symbol on the exchange
+    pub async fn get_prices(&self, symbols: &[&str]) -> Result<HashMap<String, f64>, TradingError> {
+        let url = format!("{}/api/v3/ticker/price", self.base_url);
+        let mut request = self.client.get(&url);
+        if !symbols.is_empty() {
+            // Binance expects a JSON array, e.g. symbols=["BTCUSDT","ETHUSDT"]
+            request = request.query(&[("symbols", serde_json::to_string(symbols)?)]);
+        }
+
+        let (status, response_text) = self.send_public(request).await?;
+        if !status.is_success() {
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("API Error: {}", response_text).into(),
+            });
+        }
+
+        let prices: Vec<SymbolPrice> = parse_response("prices", &response_text)?;
+        Ok(prices.into_iter().map(|p| (p.symbol, p.price)).collect())
+    }
+
+    /// Recent candles for `symbol` (oldest first), with every field of the REST kline row
+    /// `interval` is Binance's notation ("1m", "15m", "1h", "1d"...); `limit` defaults to 500, max 1000
+    pub async fn get_klines(&self, symbol: &str, interval: &str, limit: Option<u32>) -> Result<Vec<Kline>, TradingError> {
+        let mut query = vec![("symbol", symbol.to_string()), ("interval", interval.to_string())];
+        if let Some(limit) = limit {
+            query.push(("limit", limit.to_string()));
+        }
+        self.fetch_klines(symbol, interval, &query).await
+    }
+
+    /// Every candle opened between `start_time` and `end_time` (ms, inclusive),
+    /// paging by open time as far as needed (a month of 1m candles is ~44 pages)
+    pub async fn get_klines_range(&self, symbol: &str, interval: &str, start_time: u64, end_time: u64) -> Result<Vec<Kline>, TradingError> {
+        collect_pages(
+            |cursor| {
+                let query = vec![
+                    ("symbol", symbol.to_string()),
+                    ("interval", interval.to_string()),
+                    ("startTime", cursor.unwrap_or(start_time).to_string()),
+                    ("endTime", end_time.to_string()),
+                    ("limit", MAX_PAGE_SIZE.to_string()),
+                ];
+                async move { self.fetch_klines(symbol, interval, &query).await }
+            },
+            |kline| kline.open_time,
+            |kline| kline.open_time,
+            end_time,
+            MAX_PAGE_SIZE as usize,
+        ).await
+    }
+
+    async fn fetch_klines(&self, symbol: &str, interval: &str, query: &[(&str, String)]) -> Result<Vec<Kline>, TradingError> {
+        let url = format!("{}/api/v3/klines", self.base_url);
+        let (status, response_text) = self.send_public(self.client.get(&url).query(query)).await?;
+        if !status.is_success() {
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("API Error: {}", response_text).into(),
+            });
+        }
+
+        let rows: Vec<Vec<Value>> = parse_response("klines", &response_text)?;
+        let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+        rows.iter()
+            .map(|row| Kline::from_rest_row(symbol, interval, row, now_ms).map_err(|e| e.into()))
+            .collect()
+    }
+
+    /// Historical aggregate trades for `symbol` (oldest first), as `Trade`s
+    /// Without a time range Binance returns the most recent ones. A range given with
+    /// both ends must span less than an hour; `limit` defaults to 500, max 1000
+    pub async fn get_agg_trades(&self, symbol: &str, start_time: Option<u64>, end_time: Option<u64>, limit: Option<u32>) -> Result<Vec<Trade>, TradingError> {
+        let mut query = vec![("symbol", symbol.to_string())];
+        if let Some(start_time) = start_time {
+            query.push(("startTime", start_time.to_string()));
+        }
+        if let Some(end_time) = end_time {
+            query.push(("endTime", end_time.to_string()));
+        }
+        if let Some(limit) = limit {
+            query.push(("limit", limit.to_string()));
+        }
+
+        let agg_trades = self.fetch_agg_trades(&query).await?;
+        Ok(agg_trades.iter().map(|t| t.to_trade(symbol)).collect())
+    }
+
+    /// Aggregate trades starting at aggregate id `from_id` (inclusive)
+    /// Ids are kept so the next page can start at `last.id + 1`
+    pub async fn get_agg_trades_from_id(&self, symbol: &str, from_id: u64, limit: Option<u32>) -> Result<Vec<AggTrade>, TradingError> {
+        let mut query = vec![("symbol", symbol.to_string()), ("fromId", from_id.to_string())];
+        if let Some(limit) = limit {
+            query.push(("limit", limit.to_string()));
+        }
+        self.fetch_agg_trades(&query).await
+    }
+
+    /// Every aggregate trade between `start_time` and `end_time` (ms, inclusive), as `Trade`s
+    /// The first page is found by time, the rest by aggregate id, so the range can be longer
+    /// than the hour Binance allows for a single time-ranged request
+    pub async fn get_agg_trades_range(&self, symbol: &str, start_time: u64, end_time: u64) -> Result<Vec<Trade>, TradingError> {
+        let agg_trades = collect_pages(
+            |cursor| {
+                let query = vec![
+                    ("symbol", symbol.to_string()),
+                    match cursor {
+                        Some(id) => ("fromId", id.to_string()),
+                        None => ("startTime", start_time.to_string()),
+                    },
+                    ("limit", MAX_PAGE_SIZE.to_string()),
+                ];
+                async move { self.fetch_agg_trades(&query).await }
+            },
+            |trade| trade.id,
+            |trade| trade.timestamp,
+            end_time,
+            MAX_PAGE_SIZE as usize,
+        ).await?;
+        Ok(agg_trades.iter().map(|t| t.to_trade(symbol)).collect())
+    }
+
+    async fn fetch_agg_trades(&self, query: &[(&str, String)]) -> Result<Vec<AggTrade>, TradingError> {
+        let url = format!("{}/api/v3/aggTrades", self.base_url);
+        let (status, response_text) = self.send_public(self.client.get(&url).query(query)).await?;
+        if !status.is_success() {
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("API Error: {}", response_text).into(),
+            });
+        }
+
+        let agg_trades: Vec<AggTrade> = parse_response("aggregate trades", &response_text)?;
+        Ok(agg_trades)
+    }
+
+    /// Start a user data stream and return its listen key
+    /// The key stays valid for 60 minutes unless kept alive with `keepalive_listen_key`
+    pub async fn create_listen_key(&self) -> Result<String, TradingError> {
+        let url = format!("{}/api/v3/userDataStream", self.base_url);
+        self.throttle(false).await;
+        let response = self.client.post(&url)
+            .header("X-MBX-APIKEY", self.active_api_key())
+            .send()
+            .await?;
+        let status = response.status();
+        let response_text = response.text().await?;
+        if !status.is_success() {
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("Failed to create listen key ({}): {}", status, response_text).into(),
+            });
+        }
+
+        let data: Value = serde_json::from_str(&response_text)?;
+        data["listenKey"].as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("No listenKey in response: {}", response_text).into())
+    }
+
+    /// Extend a listen key's validity by another 60 minutes
+    pub async fn keepalive_listen_key(&self, listen_key: &str) -> Result<(), TradingError> {
+        let url = format!("{}/api/v3/userDataStream", self.base_url);
+        self.throttle(false).await;
+        let response = self.client.put(&url)
+            .header("X-MBX-APIKEY", self.active_api_key())
+            .query(&[("listenKey", listen_key)])
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let response_text = response.text().await?;
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("Failed to keep listen key alive ({}): {}", status, response_text).into(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether Binance is up or in a maintenance window
+    pub async fn get_system_status(&self) -> Result<SystemStatus, TradingError> {
+        let url = format!("{}/sapi/v1/system/status", self.base_url);
+        let (status, response_text) = self.send_public(self.client.get(&url)).await?;
+        if !status.is_success() {
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("API Error: {}", response_text).into(),
+            });
+        }
+
+        let system_status: SystemStatus = parse_response("system status", &response_text)?;
+        Ok(system_status)
+    }
+
+    /// A symbol's status from the cached exchange info ("TRADING", "HALT", "BREAK", ...)
+    /// None if the exchange doesn't list the symbol
+    pub async fn symbol_status(&self, symbol: &str) -> Result<Option<String>, TradingError> {
+        let info = self.cached_exchange_info().await?;
+        let symbol = self.resolve_symbol(symbol);
+        Ok(info.symbols.iter().find(|s| s.symbol == symbol).map(|s| s.status.clone()))
+    }
+
+    /// Whether a symbol is currently in TRADING status (cached, so cheap to call often)
+    /// Unknown symbols count as not trading
+    pub async fn is_symbol_trading(&self, symbol: &str) -> Result<bool, TradingError> {
+        Ok(self.symbol_status(symbol).await?.is_some_and(|status| status == "TRADING"))
+    }
+
+    /// Binance's current server time in milliseconds
+    pub async fn get_server_time(&self) -> Result<u64, TradingError> {
+        let url = format!("{}/api/v3/time", self.base_url);
+        let (status, response_text) = self.send_public(self.client.get(&url)).await?;
+        if !status.is_success() {
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("API Error: {}", response_text).into(),
+            });
+        }
+
+        let data: Value = parse_response("server time", &response_text)?;
+        data["serverTime"].as_u64()
+            .ok_or_else(|| format!("No serverTime in response: {}", response_text).into())
+    }
+
+    /// Measure the local clock's offset from Binance's and apply it to signed timestamps
+    ///
+    /// The offset is taken against the midpoint of the request, so network latency
+    /// mostly cancels out. If its magnitude exceeds the drift threshold a warning is
+    /// logged and the `on_clock_drift` hook runs. Returns the offset in ms
+    /// (positive = local clock is behind the server).
+    pub async fn sync_time(&self) -> Result<i64, TradingError> {
+        let sent_at = chrono::Utc::now().timestamp_millis();
+        let server_time = self.get_server_time().await? as i64;
+        let received_at = chrono::Utc::now().timestamp_millis();
+
+        let offset = server_time - (sent_at + received_at) / 2;
+        self.clock_offset_ms.store(offset, Ordering::SeqCst);
+        self.clock_synced.store(true, Ordering::SeqCst);
+        *self.last_time_sync.lock().unwrap() = Some(Instant::now());
+
+        if offset.unsigned_abs() as u128 > self.clock_drift_threshold.as_millis() {
+            warn!("Local clock is off from Binance by {}ms (threshold {:?}); signed requests may fail with -1021",
+                  offset, self.clock_drift_threshold);
+            if let Some(hook) = &self.on_clock_drift {
+                hook(offset);
+            }
+        } else {
+            info!("Clock offset from Binance: {}ms", offset);
+        }
+        Ok(offset)
+    }
+
+    /// Offset measured by the last `sync_time` in ms, or None if it never ran
+    /// Handy for health checks
+    pub fn clock_drift(&self) -> Option<i64> {
+        self.clock_synced.load(Ordering::SeqCst).then(|| self.clock_offset_ms.load(Ordering::SeqCst))
+    }
+
+    /// Whether the last measured drift exceeds the threshold
+    pub fn clock_drift_exceeded(&self) -> bool {
+        self.clock_drift().is_some_and(|offset| offset.unsigned_abs() as u128 > self.clock_drift_threshold.as_millis())
+    }
+
+    /// Trading rules for every symbol (status, assets and filters)
+    pub async fn get_exchange_info(&self) -> Result<ExchangeInfo, TradingError> {
+        let url = format!("{}/api/v3/exchangeInfo", self.base_url);
+        let (status, response_text) = self.send_public(self.client.get(&url)).await?;
+        if !status.is_success() {
+            return Err(match TradingError::from_response_body(&response_text) {
+                Some(api_error) => api_error,
+                None => format!("API Error: {}", response_text).into(),
+            });
+        }
+
+        let exchange_info: ExchangeInfo = parse_response("exchange info", &response_text)?;
+        self.apply_rate_limits(&exchange_info.rate_limits);
+
+        Ok(exchange_info)
+    }
+
+    /// Exchange info, fetched at most once per hour and shared between callers
+    pub async fn cached_exchange_info(&self) -> Result<Arc<ExchangeInfo>, TradingError> {
+        if let Some((fetched_at, info)) = self.exchange_info_cache.lock().unwrap().as_ref() {
+            if fetched_at.elapsed() < EXCHANGE_INFO_TTL {
+                return Ok(info.clone());
+            }
+        }
+
+        let info = Arc::new(self.get_exchange_info().await?);
+        *self.exchange_info_cache.lock().unwrap() = Some((Instant::now(), info.clone()));
+        Ok(info)
+    }
+
+    /// Drop the cached exchange info so the next lookup fetches it fresh
+    pub fn invalidate_exchange_info(&self) {
+        *self.exchange_info_cache.lock().unwrap() = None;
+    }
+
+    /// All symbols quoted in `quote` (e.g. every "USDT" pair) that are currently trading
+    /// Uses the cached exchange info, so calling this in a loop is cheap
+    pub async fn symbols_for_quote(&self, quote: &str) -> Result<Vec<String>, TradingError> {
+        let info = self.cached_exchange_info().await?;
+        let mut symbols: Vec<String> = info.symbols.iter()
+            .filter(|s| s.quote_asset == quote && s.is_trading())
+            .map(|s| s.symbol.clone())
+            .collect();
+        symbols.sort();
+        Ok(symbols)
+    }
+
+    /// Base quantity to buy/sell for a target notional (in quote asset) at the current price
+    ///
+    /// The quantity is rounded down to the symbol's step size (a precision override
+    /// wins over the cached exchange info), so the resulting notional never exceeds
+    /// the target. This is the base-quantity counterpart to a quoteOrderQty order.
+    pub async fn quantity_for_notional(&self, symbol: &str, notional: f64) -> Result<NotionalQuantity, TradingError> {
+        let price = self.get_current_price(symbol).await?;
+        if price <= 0.0 {
+            return Err(format!("No usable price for {}", symbol).into());
+        }
+
+        let step_size = match self.precision_override(symbol) {
+            Some((qty_decimals, _)) => 10f64.powi(-(qty_decimals as i32)),
+            None => self.lookup_symbol_info(symbol).await?.market_lot_size().1,
+        };
+
+        let quantity = round_to_step(notional / price, step_size, RoundingMode::Down);
+        Ok(NotionalQuantity { quantity, price, notional: quantity * price })
+    }
+
+    /// Total value of the account (free and locked) in `asset`
+    ///
+    /// Each balance is converted with `PriceGraph`, so assets without a direct pair
+    /// are valued through the inverse pair or a bridge like BTC. Balances with no
+    /// conversion path at all are left out with a warning.
+    pub async fn total_value_in(&self, asset: &str) -> Result<f64, TradingError> {
+        let account = self.get_spot_account().await?;
+        let exchange_info = self.cached_exchange_info().await?;
+        let prices = self.get_prices(&[]).await?;
+        let graph = PriceGraph::from_exchange_info(&exchange_info, &prices);
+
+        let mut total = 0.0;
+        for balance in account.balances.iter().filter(|b| b.free + b.locked > 0.0) {
+            match graph.convert(balance.free + balance.locked, &balance.asset, asset) {
+                Some(value) => total += value,
+                None => warn!("No way to value {} in {}, leaving it out", balance.asset, asset),
+            }
+        }
+        Ok(total)
+    }
+
+    /// Market-sell every asset with a `{asset}{quote}` pair back into `quote`
+    ///
+    /// Only the free balance is sold (locked funds belong to open orders), rounded
+    /// down to the step size so the order can never exceed what we hold. Assets with
+    /// no trading pair, or too little to clear the minimum quantity/notional after
+    /// rounding, are skipped. A failed sell is recorded and the rest still go ahead.
+    pub async fn flatten_to(&self, quote: &str) -> Result<FlattenSummary, TradingError> {
+        let account = self.get_spot_account().await?;
+        let exchange_info = self.cached_exchange_info().await?;
+        let mut summary = FlattenSummary::default();
+
+        // Work out the routes first so prices can be fetched in one request
+        let mut routes = Vec::new();
+        for balance in account.balances.iter().filter(|b| b.free > 0.0 && b.asset != quote) {
+            let route = exchange_info.symbols.iter()
+                .find(|s| s.base_asset == balance.asset && s.quote_asset == quote && s.is_trading());
+            match route {
+                Some(symbol_info) => routes.push((balance, symbol_info)),
+                None => summary.skipped.push(FlattenSkip {
+                    asset: balance.asset.clone(),
+                    reason: FlattenSkipReason::NoRoute,
+                }),
+            }
+        }
+        if routes.is_empty() {
+            return Ok(summary);
+        }
+
+        let symbols: Vec<&str> = routes.iter().map(|(_, s)| s.symbol.as_str()).collect();
+        let prices = self.get_prices(&symbols).await?;
+
+        for (balance, symbol_info) in routes {
+            let (min_qty, step_size) = symbol_info.market_lot_size();
+            let quantity = round_to_step(balance.free, step_size, RoundingMode::Down);
+            let price = prices.get(&symbol_info.symbol).copied().unwrap_or(0.0);
+
+            if quantity <= 0.0 || quantity < min_qty || quantity * price < symbol_info.min_notional() {
+                info!("Skipping {} {}: below the minimum order size for {}",
+                      balance.free, balance.asset, symbol_info.symbol);
+                summary.skipped.push(FlattenSkip { asset: balance.asset.clone(), reason: FlattenSkipReason::Dust });
+                continue;
+            }
+
+            match self.place_market_order(&symbol_info.symbol, OrderSide::Sell, quantity).await {
+                Ok(order) => summary.sold.push(FlattenSale {
+                    asset: balance.asset.clone(),
+                    symbol: symbol_info.symbol.clone(),
+                    quantity,
+                    order,
+                }),
+                Err(e) => {
+                    warn!("Failed to flatten {} via {}: {}", balance.asset, symbol_info.symbol, e);
+                    summary.skipped.push(FlattenSkip {
+                        asset: balance.asset.clone(),
+                        reason: FlattenSkipReason::OrderFailed(e.to_string()),
+                    });
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
     pub fn build_query_string(&self, params: &std::collections::HashMap<String, String>) -> String {
         let mut sorted_params: Vec<_> = params.iter().collect();
         sorted_params.sort_by_key(|&(k, _)| k);
@@ -264,8 +1959,51 @@ impl TestnetTrader {
             .join("&")
     }
 
+    /// Offline self-test of the signing path, no network involved
+    /// Checks the HMAC-SHA256 wiring against the worked example from the Binance docs,
+    /// that the active secret has no stray whitespace (a common .env copy/paste mistake),
+    /// and that a signature made with the active secret verifies against the same key bytes
+    pub fn verify_signing(&self) -> bool {
+        if Self::sign_with(DOC_EXAMPLE_SECRET, DOC_EXAMPLE_QUERY) != DOC_EXAMPLE_SIGNATURE {
+            error!("Signing self-test failed: HMAC-SHA256 doesn't match the Binance docs example");
+            return false;
+        }
+
+        let credentials = &self.credentials[self.active_key_index()];
+        let secret = &credentials.secret_key;
+        if secret.is_empty() || secret.trim() != secret {
+            error!("Signing self-test failed: secret for {} is empty or has surrounding whitespace",
+                   credentials.masked_api_key());
+            return false;
+        }
+
+        let signature = match hex::decode(self.sign(DOC_EXAMPLE_QUERY)) {
+            Ok(bytes) => bytes,
+            Err(_) => return false,
+        };
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+            .expect("HMAC can take key of any size");
+        mac.update(DOC_EXAMPLE_QUERY.as_bytes());
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    /// Make a real signed `/api/v3/account` call and report whether authentication worked
+    /// Separates signature errors (-1022, wrong secret) from every other failure
+    pub async fn check_auth(&self) -> AuthCheck {
+        match self.get_spot_account().await {
+            Ok(_) => AuthCheck::Authenticated,
+            Err(TradingError::Signature(msg)) => AuthCheck::InvalidSignature(msg),
+            Err(e) => AuthCheck::Failed(e.to_string()),
+        }
+    }
+
+    /// Sign a query string with the active key pair's secret
     pub fn sign(&self, query_string: &str) -> String {
-        let mut mac = HmacSha256::new_from_slice(self.secret_key.as_bytes())
+        Self::sign_with(&self.credentials[self.active_key_index()].secret_key, query_string)
+    }
+
+    fn sign_with(secret_key: &str, query_string: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(secret_key.as_bytes())
             .expect("HMAC can take key of any size");
         mac.update(query_string.as_bytes());
         hex::encode(mac.finalize().into_bytes())
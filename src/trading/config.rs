@@ -0,0 +1,134 @@
+use super::client::{TestnetTrader, DEFAULT_RECV_WINDOW};
+use super::types::{SellGuard, TimeUnit};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Everything needed to build a `TestnetTrader`, in one place
+///
+/// Deserializes from any serde format (JSON, TOML, ...). Every field except the
+/// keys is optional and falls back to the same default `TestnetTrader::new` uses:
+///
+/// ```json
+/// { "api_key": "...", "secret_key": "...", "dry_run": true, "request_deadline_ms": 2000 }
+/// ```
+#[derive(Clone, Deserialize)]
+pub struct TraderConfig {
+    #[serde(deserialize_with = "non_empty_key")]
+    pub api_key: String,
+    #[serde(deserialize_with = "non_empty_key")]
+    pub secret_key: String,
+    #[serde(default)]
+    pub backup_keys: Vec<KeyPair>,         // Failover key pairs, tried in order
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_user_agent")]
+    pub user_agent: String,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub max_order_notional: Option<f64>,   // In quote asset
+    #[serde(default)]
+    pub time_unit: TimeUnit,               // "millisecond" or "microsecond"
+    #[serde(default)]
+    pub request_deadline_ms: Option<u64>,  // Budget for one signed call, retries included
+    #[serde(default = "default_recv_window")]
+    pub recv_window_ms: u64,               // recvWindow of signed calls, at most 60000
+    #[serde(default)]
+    pub sell_guard: SellGuard,             // "off", "cap" or "strict"
+    #[serde(default)]
+    pub halt_check: bool,                  // Refuse orders for symbols that aren't TRADING
+}
+
+fn default_base_url() -> String {
+    "https://testnet.binance.vision".to_string()
+}
+
+fn default_user_agent() -> String {
+    crate::DEFAULT_USER_AGENT.to_string()
+}
+
+fn default_recv_window() -> u64 {
+    DEFAULT_RECV_WINDOW
+}
+
+/// Keys must be present and non-empty, so a bad config fails at load time instead of
+/// on the first signed call (-2014/-1022)
+fn non_empty_key<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let key = String::deserialize(deserializer)?;
+    if key.trim().is_empty() {
+        return Err(serde::de::Error::custom("API key and secret must not be empty"));
+    }
+    Ok(key)
+}
+
+/// An extra API key pair in a `TraderConfig`
+#[derive(Clone, Deserialize)]
+pub struct KeyPair {
+    #[serde(deserialize_with = "non_empty_key")]
+    pub api_key: String,
+    #[serde(deserialize_with = "non_empty_key")]
+    pub secret_key: String,
+}
+
+impl Default for TraderConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            secret_key: String::new(),
+            backup_keys: Vec::new(),
+            base_url: default_base_url(),
+            user_agent: default_user_agent(),
+            dry_run: false,
+            max_order_notional: None,
+            time_unit: TimeUnit::default(),
+            request_deadline_ms: None,
+            recv_window_ms: default_recv_window(),
+            sell_guard: SellGuard::default(),
+            halt_check: false,
+        }
+    }
+}
+
+// Hand-written so secrets can't end up in logs via {:?}
+impl std::fmt::Debug for TraderConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TraderConfig")
+            .field("api_key", &self.api_key.chars().take(8).collect::<String>())
+            .field("backup_keys", &self.backup_keys.len())
+            .field("base_url", &self.base_url)
+            .field("user_agent", &self.user_agent)
+            .field("dry_run", &self.dry_run)
+            .field("max_order_notional", &self.max_order_notional)
+            .field("time_unit", &self.time_unit)
+            .field("request_deadline_ms", &self.request_deadline_ms)
+            .field("recv_window_ms", &self.recv_window_ms)
+            .field("sell_guard", &self.sell_guard)
+            .field("halt_check", &self.halt_check)
+            .finish()
+    }
+}
+
+impl TestnetTrader {
+    /// Build a trader from a `TraderConfig` (same result as the equivalent `with_*` calls)
+    pub fn from_config(config: TraderConfig) -> Self {
+        let mut trader = TestnetTrader::new(config.api_key, config.secret_key)
+            .with_base_url(config.base_url)
+            .with_user_agent(config.user_agent)
+            .with_time_unit(config.time_unit)
+            .with_dry_run(config.dry_run)
+            .with_recv_window(config.recv_window_ms)
+            .with_sell_guard(config.sell_guard)
+            .with_halt_check(config.halt_check);
+
+        for key in config.backup_keys {
+            trader = trader.with_additional_credentials(key.api_key, key.secret_key);
+        }
+        if let Some(limit) = config.max_order_notional {
+            trader = trader.with_max_order_notional(limit);
+        }
+        if let Some(ms) = config.request_deadline_ms {
+            trader = trader.with_request_deadline(Duration::from_millis(ms));
+        }
+        trader
+    }
+}
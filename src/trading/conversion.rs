@@ -0,0 +1,118 @@
+// Converting amounts between assets using whichever trading pairs exist.
+// Binance lists each pair one way only (BTCUSDT, never USDTBTC), so valuing USDT in BTC
+// means inverting a price, and assets with no pair to the target go through a bridge.
+
+use super::types::ExchangeInfo;
+use std::collections::HashMap;
+
+/// Bridge assets tried first for one-hop conversions, most liquid first
+pub const DEFAULT_BRIDGE_ASSETS: [&str; 4] = ["USDT", "BTC", "ETH", "BNB"];
+
+/// How one conversion leg uses its pair
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConversionLeg {
+    Direct { symbol: String, price: f64 },   // from = base, to = quote: multiply by the price
+    Inverse { symbol: String, price: f64 },  // from = quote, to = base: divide by the price
+}
+
+impl ConversionLeg {
+    fn rate(&self) -> f64 {
+        match self {
+            ConversionLeg::Direct { price, .. } => *price,
+            ConversionLeg::Inverse { price, .. } => 1.0 / price,
+        }
+    }
+}
+
+/// A way to turn one asset into another: one leg, or two through a bridge asset
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionPath {
+    pub legs: Vec<ConversionLeg>,
+    pub rate: f64,  // Units of the target asset per unit of the source asset
+}
+
+/// Pairs and their last prices, for finding conversion rates between any two assets
+#[derive(Debug, Clone, Default)]
+pub struct PriceGraph {
+    pairs: HashMap<(String, String), (String, f64)>,  // (base, quote) -> (symbol, price)
+    bridges: Vec<String>,  // Tried in order before any other intermediate asset
+}
+
+impl PriceGraph {
+    pub fn new() -> Self {
+        Self {
+            pairs: HashMap::new(),
+            bridges: DEFAULT_BRIDGE_ASSETS.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// Every trading symbol in `exchange_info` that has a price in `prices`
+    pub fn from_exchange_info(exchange_info: &ExchangeInfo, prices: &HashMap<String, f64>) -> Self {
+        let mut graph = Self::new();
+        for symbol_info in exchange_info.symbols.iter().filter(|s| s.is_trading()) {
+            if let Some(&price) = prices.get(&symbol_info.symbol) {
+                graph.add_pair(&symbol_info.symbol, &symbol_info.base_asset, &symbol_info.quote_asset, price);
+            }
+        }
+        graph
+    }
+
+    /// Bridge assets to prefer for one-hop conversions, in order
+    pub fn with_bridges(mut self, bridges: &[&str]) -> Self {
+        self.bridges = bridges.iter().map(|a| a.to_string()).collect();
+        self
+    }
+
+    /// Add or update a pair: one `base` costs `price` units of `quote`
+    /// Non-positive prices are ignored, since they can't be inverted
+    pub fn add_pair(&mut self, symbol: &str, base: &str, quote: &str, price: f64) {
+        if price > 0.0 {
+            self.pairs.insert((base.to_string(), quote.to_string()), (symbol.to_string(), price));
+        }
+    }
+
+    /// The single leg between two assets, using the pair either way round
+    fn leg(&self, from: &str, to: &str) -> Option<ConversionLeg> {
+        if let Some((symbol, price)) = self.pairs.get(&(from.to_string(), to.to_string())) {
+            return Some(ConversionLeg::Direct { symbol: symbol.clone(), price: *price });
+        }
+        self.pairs.get(&(to.to_string(), from.to_string()))
+            .map(|(symbol, price)| ConversionLeg::Inverse { symbol: symbol.clone(), price: *price })
+    }
+
+    /// How to convert `from` into `to`: direct, inverse, or one hop through a bridge
+    /// The configured bridges are tried first, then any other asset both sides trade against
+    pub fn path(&self, from: &str, to: &str) -> Option<ConversionPath> {
+        if from == to {
+            return Some(ConversionPath { legs: Vec::new(), rate: 1.0 });
+        }
+        if let Some(leg) = self.leg(from, to) {
+            return Some(ConversionPath { rate: leg.rate(), legs: vec![leg] });
+        }
+
+        let mut others: Vec<&String> = self.pairs.keys()
+            .flat_map(|(base, quote)| [base, quote])
+            .filter(|asset| !self.bridges.contains(asset))
+            .collect();
+        others.sort();
+        others.dedup();
+
+        self.bridges.iter().chain(others)
+            .filter(|bridge| bridge.as_str() != from && bridge.as_str() != to)
+            .find_map(|bridge| {
+                let first = self.leg(from, bridge)?;
+                let second = self.leg(bridge, to)?;
+                Some(ConversionPath { rate: first.rate() * second.rate(), legs: vec![first, second] })
+            })
+    }
+
+    /// Units of `to` per unit of `from`, if the pairs connect them
+    pub fn rate(&self, from: &str, to: &str) -> Option<f64> {
+        self.path(from, to).map(|path| path.rate)
+    }
+
+    /// `amount` of `from` expressed in `to`
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        self.rate(from, to).map(|rate| amount * rate)
+    }
+}
@@ -0,0 +1,58 @@
+use super::types::{AccountInfo, CommissionRates, OrderSide};
+
+/// Discount Binance applies to commissions paid in BNB (25%)
+pub const BNB_FEE_DISCOUNT: f64 = 0.25;
+
+/// Estimates the commission an order will pay before it's placed
+///
+/// Rates come from the account's `CommissionRates` (so they follow the VIP tier),
+/// and are reduced by `BNB_FEE_DISCOUNT` when fees are paid in BNB.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeEstimator {
+    rates: CommissionRates,
+    bnb_fee_enabled: bool,  // Fees are paid in BNB, so the discount applies
+    bnb_discount: f64,
+}
+
+impl FeeEstimator {
+    pub fn new(rates: CommissionRates, bnb_fee_enabled: bool) -> Self {
+        Self {
+            rates,
+            bnb_fee_enabled,
+            bnb_discount: BNB_FEE_DISCOUNT,
+        }
+    }
+
+    /// Build from fetched account info; None if the response had no commission rates
+    pub fn from_account(account: &AccountInfo, bnb_fee_enabled: bool) -> Option<Self> {
+        account.commission_rates.map(|rates| Self::new(rates, bnb_fee_enabled))
+    }
+
+    /// Override the BNB discount, e.g. if Binance changes it (0.25 = 25% off)
+    pub fn with_bnb_discount(mut self, discount: f64) -> Self {
+        self.bnb_discount = discount;
+        self
+    }
+
+    /// Effective commission rate for a fill, as a fraction of its notional
+    /// Maker or taker rate plus the side-specific rate, less the BNB discount if enabled
+    pub fn effective_fee(&self, side: OrderSide, is_maker: bool) -> f64 {
+        let base = if is_maker { self.rates.maker } else { self.rates.taker };
+        let side_rate = match side {
+            OrderSide::Buy => self.rates.buyer,
+            OrderSide::Sell => self.rates.seller,
+        };
+
+        let rate = base + side_rate;
+        if self.bnb_fee_enabled {
+            rate * (1.0 - self.bnb_discount)
+        } else {
+            rate
+        }
+    }
+
+    /// Expected commission in quote terms for a fill of `notional`
+    pub fn estimate(&self, side: OrderSide, is_maker: bool, notional: f64) -> f64 {
+        notional * self.effective_fee(side, is_maker)
+    }
+}
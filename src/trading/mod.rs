@@ -1,5 +1,19 @@
 pub mod client;
 pub mod types;
+pub mod precision;
+pub mod config;
+pub mod fees;
+pub mod conversion;
+pub mod rate_limit;
+pub mod pagination;
+pub mod account_stream;
 
 pub use client::TestnetTrader;
-pub use types::*;
\ No newline at end of file
+pub use account_stream::AccountWatcher;
+pub use config::{KeyPair, TraderConfig};
+pub use fees::{FeeEstimator, BNB_FEE_DISCOUNT};
+pub use rate_limit::RateLimiter;
+pub use pagination::{MAX_PAGE_SIZE, MAX_TIME_WINDOW};
+pub use conversion::{ConversionLeg, ConversionPath, PriceGraph, DEFAULT_BRIDGE_ASSETS};
+pub use types::*;
+pub use precision::{Amount, RoundingMode};
\ No newline at end of file
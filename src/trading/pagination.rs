@@ -0,0 +1,113 @@
+// Paging through historical endpoints that return at most `MAX_PAGE_SIZE` rows per call.
+// Every page is a normal request, so it goes through the client's rate limiter.
+
+use crate::error::TradingError;
+use std::future::Future;
+
+/// Most rows Binance returns per page on klines, aggTrades, allOrders and myTrades
+pub const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Fetch pages until `end_time` is passed or a page comes back short
+///
+/// `fetch(None)` returns the first page; `fetch(Some(cursor))` the page starting at
+/// `cursor` inclusive, where the cursor is the `key` (open time or id) of the last row
+/// kept. Starting at the last row rather than after it means nothing at the boundary is
+/// missed; the repeated row is dropped, as is anything timed after `end_time`.
+pub(crate) async fn collect_pages<T, F, Fut>(
+    mut fetch: F,
+    key: impl Fn(&T) -> u64,
+    time: impl Fn(&T) -> u64,
+    end_time: u64,
+    page_size: usize,
+) -> Result<Vec<T>, TradingError>
+where
+    F: FnMut(Option<u64>) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, TradingError>>,
+{
+    let mut rows = Vec::new();
+    let mut cursor = None;
+    let mut last_key: Option<u64> = None;
+
+    loop {
+        let page = fetch(cursor).await?;
+        let full = page.len() >= page_size;
+        let mut past_end = false;
+        for row in page {
+            if time(&row) > end_time {
+                past_end = true;
+                break;
+            }
+            let row_key = key(&row);
+            if last_key.is_some_and(|last| row_key <= last) {
+                continue;  // Boundary row, already kept from the previous page
+            }
+            last_key = Some(row_key);
+            rows.push(row);
+        }
+
+        if past_end || !full {
+            return Ok(rows);
+        }
+        match last_key {
+            Some(next) if cursor != Some(next) => cursor = Some(next),
+            _ => return Ok(rows),  // No progress, the next page would be the same
+        }
+    }
+}
+
+/// Longest startTime..endTime span (ms) myTrades and allOrders accept in one call
+pub const MAX_TIME_WINDOW: u64 = 24 * 60 * 60 * 1000;
+
+/// Where the next page of a time-windowed endpoint starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PageCursor {
+    Window { start: u64, end: u64 },  // startTime/endTime, at most `MAX_TIME_WINDOW` apart
+    FromId(u64),                      // fromId/orderId, inclusive
+}
+
+/// `collect_pages` for endpoints that only take a limited time window (myTrades, allOrders)
+///
+/// Walks `start_time..=end_time` in windows of at most `window` ms. A full page continues
+/// by id from the last row kept; a short id page means there is nothing newer at all.
+/// A short window page moves on to the next window until `end_time` is reached.
+pub(crate) async fn collect_windowed_pages<T, F, Fut>(
+    mut fetch: F,
+    key: impl Fn(&T) -> u64,
+    time: impl Fn(&T) -> u64,
+    start_time: u64,
+    end_time: u64,
+    window: u64,
+    page_size: usize,
+) -> Result<Vec<T>, TradingError>
+where
+    F: FnMut(PageCursor) -> Fut,
+    Fut: Future<Output = Result<Vec<T>, TradingError>>,
+{
+    let window_at = |start: u64| PageCursor::Window { start, end: start.saturating_add(window).min(end_time) };
+    let mut rows = Vec::new();
+    let mut cursor = window_at(start_time);
+    let mut last_key: Option<u64> = None;
+
+    loop {
+        let page = fetch(cursor).await?;
+        let full = page.len() >= page_size;
+        for row in page {
+            if time(&row) > end_time {
+                return Ok(rows);
+            }
+            let row_key = key(&row);
+            if last_key.is_some_and(|last| row_key <= last) {
+                continue;  // Boundary row, already kept from the previous page
+            }
+            last_key = Some(row_key);
+            rows.push(row);
+        }
+
+        cursor = match (cursor, full, last_key) {
+            (PageCursor::FromId(from), true, Some(next)) if next != from => PageCursor::FromId(next),
+            (PageCursor::Window { .. }, true, Some(next)) => PageCursor::FromId(next),
+            (PageCursor::Window { end, .. }, false, _) if end < end_time => window_at(end + 1),
+            _ => return Ok(rows),
+        };
+    }
+}
@@ -0,0 +1,172 @@
+// Helpers for snapping quantities and prices onto Binance's step/tick grids.
+// Binance rejects orders whose quantity isn't a multiple of the symbol's stepSize
+// (LOT_SIZE filter), so amounts must be rounded before they're sent.
+
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
+
+/// How to snap a value onto the step grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    Down,     // Toward negative infinity - never exceeds the input (use for sells so you never oversell)
+    Up,       // Toward positive infinity - never below the input
+    Nearest,  // Half-up to the closest step (good for display)
+}
+
+// f64 division like 0.3 / 0.1 gives 2.9999999999999996, which a plain floor()
+// would turn into 2. Treat anything this close to a whole step as being on it.
+const STEP_EPSILON: f64 = 1e-9;
+
+/// Number of decimal places implied by a step size, e.g. 0.00001 -> 5, 0.5 -> 1, 1.0 -> 0
+pub fn step_decimals(step_size: f64) -> usize {
+    // f64's Display never uses scientific notation, so this is safe for tiny steps
+    let formatted = format!("{}", step_size);
+    match formatted.split_once('.') {
+        Some((_, fraction)) => fraction.trim_end_matches('0').len(),
+        None => 0,
+    }
+}
+
+/// Round `value` to a multiple of `step_size` using the given mode
+/// A non-positive step size means "no constraint" and returns the value unchanged
+pub fn round_to_step(value: f64, step_size: f64, mode: RoundingMode) -> f64 {
+    if step_size <= 0.0 {
+        return value;
+    }
+
+    let steps = value / step_size;
+    let whole_steps = match mode {
+        RoundingMode::Down => (steps + STEP_EPSILON).floor(),
+        RoundingMode::Up => (steps - STEP_EPSILON).ceil(),
+        RoundingMode::Nearest => (steps + STEP_EPSILON).round(),
+    };
+
+    // Multiplying back reintroduces float noise (3 * 0.1 = 0.30000000000000004),
+    // so clean it up at the step's precision
+    let decimals = step_decimals(step_size);
+    let factor = 10f64.powi(decimals as i32);
+    (whole_steps * step_size * factor).round() / factor
+}
+
+/// Round a quantity to the step size and format it with exactly the step's decimals
+/// This is the string that should go into the `quantity` order parameter
+pub fn format_quantity(quantity: f64, step_size: f64, mode: RoundingMode) -> String {
+    let rounded = round_to_step(quantity, step_size, mode);
+    format!("{:.*}", step_decimals(step_size), rounded)
+}
+
+// Significant digits `format_decimal` keeps for values below 1
+const SIGNIFICANT_DIGITS: i32 = 8;
+
+// Binance rejects anything finer than this with -1111 ("too much precision")
+const MAX_DECIMALS: usize = 8;
+
+/// Format `value` with at least `min_decimals` places, without losing small values
+///
+/// `{:.8}` pads a SHIB-sized 1.5e-7 to "0.00000015"; `{}` could print it as "1.5e-7".
+/// Values below 1 get enough places for `SIGNIFICANT_DIGITS` significant digits, with
+/// trailing zeros trimmed back to `min_decimals`, but never more than `MAX_DECIMALS`
+/// since the result can end up in a request. Never uses scientific notation.
+pub fn format_decimal(value: f64, min_decimals: usize) -> String {
+    let min_decimals = min_decimals.min(MAX_DECIMALS);
+    if value == 0.0 || !value.is_finite() || value.abs() >= 1.0 {
+        return format!("{:.*}", min_decimals, value);
+    }
+
+    let magnitude = value.abs().log10().floor() as i32;  // 1.5e-7 -> -7
+    let decimals = ((SIGNIFICANT_DIGITS - 1 - magnitude) as usize).clamp(min_decimals, MAX_DECIMALS);
+    let formatted = format!("{:.*}", decimals, value);
+
+    // Trim zeros the extra places added, but keep at least min_decimals
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+    let trimmed = fraction.trim_end_matches('0');
+    let kept = if trimmed.len() < min_decimals { &fraction[..min_decimals] } else { trimmed };
+    if kept.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, kept)
+    }
+}
+
+/// An order quantity or price, carried as an exact decimal until it's formatted for the API
+///
+/// The order methods take `impl Into<Amount>`, so `f64` callers keep working while a
+/// `Decimal` (e.g. read from config) reaches the request string with no f64 detour.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Amount(Decimal);
+
+impl Amount {
+    /// The exact decimal value
+    pub fn decimal(self) -> Decimal {
+        self.0
+    }
+
+    /// Approximate value, for checks that work in f64 (notional limits, balances)
+    pub fn to_f64(self) -> f64 {
+        self.0.to_f64().unwrap_or(0.0)
+    }
+}
+
+impl From<Decimal> for Amount {
+    fn from(value: Decimal) -> Self {
+        Amount(value)
+    }
+}
+
+impl From<f64> for Amount {
+    /// Uses the shortest decimal that round-trips (0.1 -> 0.1, not 0.1000000000000000055...)
+    /// Non-finite values become zero, which Binance rejects
+    fn from(value: f64) -> Self {
+        Amount(Decimal::from_f64(value).unwrap_or_default())
+    }
+}
+
+impl std::fmt::Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// `format_quantity` for exact decimals - snaps to the step with no f64 rounding
+/// A non-positive step size means "no constraint" and formats the value as is
+pub fn format_decimal_to_step(value: Decimal, step_size: Decimal, mode: RoundingMode) -> String {
+    if step_size <= Decimal::ZERO {
+        return value.normalize().to_string();
+    }
+
+    let steps = value / step_size;
+    let whole_steps = match mode {
+        RoundingMode::Down => steps.floor(),
+        RoundingMode::Up => steps.ceil(),
+        RoundingMode::Nearest => steps.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero),
+    };
+
+    let decimals = step_size.normalize().scale() as usize;
+    format!("{:.*}", decimals, whole_steps * step_size)
+}
+
+/// Snap a price to the nearest multiple of `tick_size` and format it with the tick's decimals
+/// Works for any tick, not just powers of ten: with a 0.5 tick, 100.3 becomes "100.5".
+/// This is the string that should go into the `price` order parameter
+pub fn format_price_to_tick(price: impl Into<Amount>, tick_size: f64) -> String {
+    let tick_size = Decimal::from_f64(tick_size).unwrap_or_default();
+    format_decimal_to_step(price.into().decimal(), tick_size, RoundingMode::Nearest)
+}
+
+/// `format_decimal` for exact decimals: every significant digit, padded to `min_decimals`
+pub fn format_exact_decimal(value: Decimal, min_decimals: usize) -> String {
+    let value = value.normalize();
+    if (value.scale() as usize) < min_decimals {
+        format!("{:.*}", min_decimals, value)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Round `value` half-up to `decimals` places, for amounts shown to people (P&L, fees)
+/// Also turns -0.0 into 0.0, so a tiny negative loss doesn't print as "-0.00"
+pub fn round_to_decimals(value: f64, decimals: usize) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    let rounded = (value * factor).round() / factor;
+    if rounded == 0.0 { 0.0 } else { rounded }
+}
@@ -0,0 +1,135 @@
+// Client-side enforcement of the caps Binance publishes in exchangeInfo `rateLimits`,
+// so the bot waits a little instead of collecting 429s (and eventually an IP ban).
+
+use super::types::{RateLimit, RateLimitType, RateLimits};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Usage inside one limit's sliding window
+#[derive(Debug)]
+struct Window {
+    limit: RateLimit,
+    used: u32,
+    events: VecDeque<(Instant, u32)>,  // When each counted request happened and what it cost
+}
+
+impl Window {
+    fn new(limit: RateLimit) -> Self {
+        Self { limit, used: 0, events: VecDeque::new() }
+    }
+
+    fn expire(&mut self, now: Instant) {
+        let window = self.limit.window();
+        while let Some(&(at, cost)) = self.events.front() {
+            if now.duration_since(at) < window {
+                break;
+            }
+            self.used -= cost;
+            self.events.pop_front();
+        }
+    }
+
+    /// What the request costs against this limit (0 = not counted here)
+    fn cost(&self, weight: u32, is_order: bool) -> u32 {
+        match self.limit.rate_limit_type {
+            RateLimitType::RequestWeight => weight,
+            RateLimitType::RawRequests => 1,
+            RateLimitType::Orders if is_order => 1,
+            _ => 0,
+        }
+    }
+
+    /// How long until `cost` more fits; zero if it fits now
+    /// A cost above the whole limit only has to wait for an empty window
+    fn wait_for(&self, cost: u32, now: Instant) -> Duration {
+        let mut used = self.used;
+        let mut events = self.events.iter();
+        while used > 0 && used + cost > self.limit.limit {
+            let Some(&(at, freed)) = events.next() else { break };
+            if used + cost - freed <= self.limit.limit || used == freed {
+                return (at + self.limit.window()).saturating_duration_since(now);
+            }
+            used -= freed;
+        }
+        Duration::ZERO
+    }
+}
+
+/// Sliding-window limiter for REQUEST_WEIGHT, ORDERS and RAW_REQUESTS caps
+///
+/// `acquire` waits until a request fits under every limit, then counts it. The limits
+/// can be swapped with `set_limits` (e.g. after fetching exchange info); usage of limits
+/// that didn't change is kept.
+#[derive(Debug)]
+pub struct RateLimiter {
+    windows: Mutex<Vec<Window>>,
+}
+
+impl RateLimiter {
+    pub fn new(limits: &RateLimits) -> Self {
+        Self {
+            windows: Mutex::new(limits.all().copied().map(Window::new).collect()),
+        }
+    }
+
+    /// The limits currently enforced
+    pub fn limits(&self) -> RateLimits {
+        self.windows.lock().unwrap().iter().map(|w| w.limit).collect::<Vec<_>>().into()
+    }
+
+    /// Replace the enforced limits, keeping the usage of any that stay the same
+    pub fn set_limits(&self, limits: &RateLimits) {
+        let mut windows = self.windows.lock().unwrap();
+        let mut previous = std::mem::take(&mut *windows);
+        for limit in limits.all() {
+            let window = match previous.iter().position(|w| w.limit == *limit) {
+                Some(index) => previous.swap_remove(index),
+                None => Window::new(*limit),
+            };
+            windows.push(window);
+        }
+    }
+
+    /// Each limit with how much of it the current window has used
+    pub fn usage(&self) -> Vec<(RateLimit, u32)> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+        windows.iter_mut().map(|w| {
+            w.expire(now);
+            (w.limit, w.used)
+        }).collect()
+    }
+
+    /// Count the request if it fits under every limit, otherwise how long to wait
+    pub fn try_acquire(&self, weight: u32, is_order: bool) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut windows = self.windows.lock().unwrap();
+
+        let mut wait = Duration::ZERO;
+        for window in windows.iter_mut() {
+            window.expire(now);
+            wait = wait.max(window.wait_for(window.cost(weight, is_order), now));
+        }
+        if !wait.is_zero() {
+            return Err(wait);
+        }
+
+        for window in windows.iter_mut() {
+            let cost = window.cost(weight, is_order);
+            if cost > 0 {
+                window.used += cost;
+                window.events.push_back((now, cost));
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait until the request fits under every limit, then count it
+    pub async fn acquire(&self, weight: u32, is_order: bool) {
+        while let Err(wait) = self.try_acquire(weight, is_order) {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -1,7 +1,10 @@
+use crate::error::TradingError;
+use crate::market_data::{Trade, TradeSide};
 use serde::{Deserialize, Serialize, Deserializer};
+use std::collections::HashMap;
 
 // Helper function to parse string numbers to f64
-fn string_to_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
+pub(crate) fn string_to_f64<'de, D>(deserializer: D) -> Result<f64, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -27,9 +30,92 @@ pub struct AccountInfo {
     pub can_withdraw: bool,
     #[serde(rename = "canDeposit")]
     pub can_deposit: bool,
+    #[serde(rename = "commissionRates", default)]
+    pub commission_rates: Option<CommissionRates>,  // Missing on older API responses
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+/// The account's commission rates as fractions (0.001 = 0.1%), which depend on VIP tier
+/// `buyer`/`seller` are extra side-specific rates added on top of maker/taker
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CommissionRates {
+    #[serde(deserialize_with = "string_to_f64")]
+    pub maker: f64,
+    #[serde(deserialize_with = "string_to_f64")]
+    pub taker: f64,
+    #[serde(deserialize_with = "string_to_f64")]
+    pub buyer: f64,
+    #[serde(deserialize_with = "string_to_f64")]
+    pub seller: f64,
+}
+
+/// Binance wallet types
+/// Only Spot is implemented for now; other types are accepted so callers can be
+/// written against `CombinedBalances` today without a breaking change later
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AccountType {
+    Spot,
+    Margin,
+}
+
+/// Balances from one or more wallets, keyed by wallet type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CombinedBalances {
+    pub wallets: HashMap<AccountType, Vec<Balance>>,
+}
+
+impl CombinedBalances {
+    /// Balances of a single wallet, or None if it wasn't fetched/isn't supported yet
+    pub fn wallet(&self, account_type: AccountType) -> Option<&[Balance]> {
+        self.wallets.get(&account_type).map(|b| b.as_slice())
+    }
+
+    /// Total (free + locked) of an asset summed across all wallets
+    pub fn total(&self, asset: &str) -> f64 {
+        self.wallets
+            .values()
+            .flatten()
+            .filter(|b| b.asset == asset)
+            .map(|b| b.free + b.locked)
+            .sum()
+    }
+
+    /// Free amount of an asset summed across all wallets
+    pub fn free(&self, asset: &str) -> f64 {
+        self.wallets
+            .values()
+            .flatten()
+            .filter(|b| b.asset == asset)
+            .map(|b| b.free)
+            .sum()
+    }
+}
+
+/// Binance order id. A distinct type so it can't be mixed up with other u64s like timestamps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct OrderId(pub u64);
+
+impl From<u64> for OrderId {
+    fn from(id: u64) -> Self {
+        OrderId(id)
+    }
+}
+
+impl std::fmt::Display for OrderId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for OrderId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(OrderId)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OrderSide {
     #[serde(rename = "BUY")]
     Buy,
@@ -37,6 +123,45 @@ pub enum OrderSide {
     Sell,
 }
 
+impl OrderSide {
+    /// The other side: Buy <-> Sell
+    pub fn opposite(self) -> OrderSide {
+        match self {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+
+    /// The side as Binance spells it in parameters and responses
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderSide::Buy => "BUY",
+            OrderSide::Sell => "SELL",
+        }
+    }
+}
+
+impl std::str::FromStr for OrderSide {
+    type Err = TradingError;
+
+    /// Case-insensitive, surrounding whitespace ignored: "BUY", "buy", " Sell "
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_uppercase().as_str() {
+            "BUY" => Ok(OrderSide::Buy),
+            "SELL" => Ok(OrderSide::Sell),
+            _ => Err(TradingError::InvalidParameter(format!("unknown order side '{}'", s))),
+        }
+    }
+}
+
+impl TryFrom<&str> for OrderSide {
+    type Error = TradingError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OrderType {
     #[serde(rename = "MARKET")]
@@ -45,6 +170,16 @@ pub enum OrderType {
     Limit,
 }
 
+impl OrderType {
+    /// The type as Binance spells it in parameters
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OrderType::Market => "MARKET",
+            OrderType::Limit => "LIMIT",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TimeInForce {
     #[serde(rename = "GTC")]
@@ -55,6 +190,17 @@ pub enum TimeInForce {
     FillOrKill,
 }
 
+impl TimeInForce {
+    /// The time in force as Binance spells it in parameters
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeInForce::GoodTillCanceled => "GTC",
+            TimeInForce::ImmediateOrCancel => "IOC",
+            TimeInForce::FillOrKill => "FOK",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OrderStatus {
     #[serde(rename = "NEW")]
@@ -69,6 +215,27 @@ pub enum OrderStatus {
     Rejected,
     #[serde(rename = "EXPIRED")]
     Expired,
+    #[serde(rename = "PENDING_CANCEL")]
+    PendingCancel,
+    #[serde(rename = "EXPIRED_IN_MATCH")]
+    ExpiredInMatch,   // Expired by self-trade prevention
+    #[serde(other)]
+    Unknown,          // Any status Binance adds later - better than failing the whole response
+}
+
+impl OrderStatus {
+    /// True once the order can't change anymore (filled, canceled, rejected or expired)
+    /// `Unknown` counts as not final, so callers keep polling rather than give up early
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            OrderStatus::Filled
+                | OrderStatus::Canceled
+                | OrderStatus::Rejected
+                | OrderStatus::Expired
+                | OrderStatus::ExpiredInMatch
+        )
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -89,16 +256,23 @@ pub struct NewOrderRequest {
     pub timestamp: Option<u64>,
 }
 
+// Binance uses -1 for "not part of an order list"
+fn no_order_list() -> i64 {
+    -1
+}
+
+// Note: none of the response types use `deny_unknown_fields`, so new fields
+// Binance adds to its responses are ignored instead of breaking parsing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrderResponse {
     pub symbol: String,
     #[serde(rename = "orderId")]
-    pub order_id: u64,
-    #[serde(rename = "orderListId")]
-    pub order_list_id: i64,
+    pub order_id: OrderId,
+    #[serde(rename = "orderListId", default = "no_order_list")]
+    pub order_list_id: i64,  // -1 unless the order is part of an OCO list
     #[serde(rename = "clientOrderId")]
     pub client_order_id: String,
-    #[serde(rename = "transactTime", skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "transactTime", default, skip_serializing_if = "Option::is_none")]
     pub transact_time: Option<u64>,  // Optional for open orders
     pub price: String,  // Keep as string since API returns string
     #[serde(rename = "origQty")]
@@ -113,6 +287,699 @@ pub struct OrderResponse {
     #[serde(rename = "type")]
     pub order_type: String,
     pub side: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub time: Option<u64>,  // Alternative time field that might be present
-}
\ No newline at end of file
+    #[serde(rename = "rejectReason", default, skip_serializing_if = "Option::is_none")]
+    pub rejection: Option<String>,  // Only sent for some rejected orders, e.g. "INSUFFICIENT_BALANCE"
+}
+
+impl OrderResponse {
+    /// When the order was placed, in milliseconds
+    /// Order placement returns `transactTime`, while order queries/listings return `time`,
+    /// so this picks whichever is present (preferring `transactTime`)
+    pub fn timestamp(&self) -> Option<u64> {
+        self.transact_time.or(self.time)
+    }
+
+    /// Why the order was rejected, if it was
+    /// Returns None unless the status is REJECTED. Binance doesn't always say why,
+    /// in which case a generic explanation is returned so logs never just say "Rejected"
+    pub fn reject_reason(&self) -> Option<&str> {
+        if self.status != OrderStatus::Rejected {
+            return None;
+        }
+        Some(
+            self.rejection
+                .as_deref()
+                .filter(|r| !r.is_empty() && *r != "NONE")
+                .unwrap_or("no reason provided by the exchange"),
+        )
+    }
+
+    /// Same as `timestamp()`, converted to a UTC DateTime
+    /// Assumes milliseconds; for a trader using `TimeUnit::Microsecond`,
+    /// convert with `TimeUnit::to_datetime` instead
+    pub fn datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.timestamp()
+            .and_then(|ms| chrono::DateTime::from_timestamp_millis(ms as i64))
+    }
+
+    /// The same order with every field parsed, see `NormalizedOrder`
+    pub fn normalize(&self) -> Result<NormalizedOrder, TradingError> {
+        NormalizedOrder::try_from(self)
+    }
+}
+
+/// An order with numbers parsed and a guaranteed timestamp, whatever endpoint it came from
+///
+/// `place_*` responses carry `transactTime` while `get_order`/`get_open_orders` carry
+/// `time`, and every amount is a string. Normalizing picks the timestamp that's there
+/// and parses the rest, so callers don't need to care where the order came from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NormalizedOrder {
+    pub symbol: String,
+    pub order_id: OrderId,
+    pub order_list_id: i64,  // -1 unless the order is part of an OCO list
+    pub client_order_id: String,
+    pub timestamp: u64,  // When the order was placed (transactTime or time)
+    pub side: OrderSide,
+    pub order_type: String,     // "LIMIT", "MARKET", "STOP_LOSS", ...
+    pub time_in_force: String,
+    pub status: OrderStatus,
+    pub price: f64,  // 0.0 for market orders
+    pub orig_qty: f64,
+    pub executed_qty: f64,
+    pub cummulative_quote_qty: f64,
+    pub reject_reason: Option<String>,
+}
+
+impl NormalizedOrder {
+    /// Quantity still waiting to fill
+    pub fn remaining_qty(&self) -> f64 {
+        (self.orig_qty - self.executed_qty).max(0.0)
+    }
+
+    /// Average price of the fills so far; None before anything filled
+    pub fn average_fill_price(&self) -> Option<f64> {
+        if self.executed_qty > 0.0 {
+            Some(self.cummulative_quote_qty / self.executed_qty)
+        } else {
+            None
+        }
+    }
+}
+
+impl TryFrom<&OrderResponse> for NormalizedOrder {
+    type Error = TradingError;
+
+    fn try_from(order: &OrderResponse) -> Result<Self, Self::Error> {
+        let number = |field: &str, value: &str| {
+            value.parse::<f64>().map_err(|_| {
+                TradingError::Other(format!("Order {} has an invalid {}: '{}'", order.order_id, field, value))
+            })
+        };
+
+        Ok(NormalizedOrder {
+            symbol: order.symbol.clone(),
+            order_id: order.order_id,
+            order_list_id: order.order_list_id,
+            client_order_id: order.client_order_id.clone(),
+            timestamp: order.timestamp()
+                .ok_or_else(|| TradingError::Other(format!("Order {} has no transactTime or time", order.order_id)))?,
+            side: order.side.parse()?,
+            order_type: order.order_type.clone(),
+            time_in_force: order.time_in_force.clone(),
+            status: order.status.clone(),
+            price: number("price", &order.price)?,
+            orig_qty: number("origQty", &order.orig_qty)?,
+            executed_qty: number("executedQty", &order.executed_qty)?,
+            cummulative_quote_qty: number("cummulativeQuoteQty", &order.cummulative_quote_qty)?,
+            reject_reason: order.reject_reason().map(str::to_string),
+        })
+    }
+}
+
+/// Result of `cancel_order_if_open`
+/// (short-lived return value, so the size difference between variants doesn't matter)
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum CancelOutcome {
+    Canceled(OrderResponse),  // The order was open and is now canceled
+    AlreadyGone,              // The order had already filled, been canceled, or never existed
+}
+
+/// An OCO order list from `/api/v3/openOrderList` (also returned when canceling one)
+/// The legs only carry ids - match them against `get_open_orders` for prices and
+/// quantities. Canceling needs `order_list_id`, which cancels both legs at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcoOrderResponse {
+    #[serde(rename = "orderListId")]
+    pub order_list_id: i64,
+    #[serde(rename = "contingencyType")]
+    pub contingency_type: String,  // "OCO"
+    #[serde(rename = "listStatusType")]
+    pub list_status_type: String,  // RESPONSE, EXEC_STARTED or ALL_DONE
+    #[serde(rename = "listOrderStatus")]
+    pub list_order_status: String,  // EXECUTING, ALL_DONE or REJECT
+    #[serde(rename = "listClientOrderId")]
+    pub list_client_order_id: String,
+    #[serde(rename = "transactionTime")]
+    pub transaction_time: u64,
+    pub symbol: String,
+    pub orders: Vec<OcoLeg>,
+}
+
+impl OcoOrderResponse {
+    /// Whether `order_id` is one of this list's legs
+    pub fn contains(&self, order_id: OrderId) -> bool {
+        self.orders.iter().any(|leg| leg.order_id == order_id)
+    }
+}
+
+/// One order in an OCO list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OcoLeg {
+    pub symbol: String,
+    #[serde(rename = "orderId")]
+    pub order_id: OrderId,
+    #[serde(rename = "clientOrderId")]
+    pub client_order_id: String,
+}
+
+/// One of our own executions from `/api/v3/myTrades`, including the commission paid
+#[derive(Debug, Clone, Deserialize)]
+pub struct AccountTrade {
+    pub symbol: String,
+    pub id: u64,  // Trade id (unique per symbol)
+    #[serde(rename = "orderId")]
+    pub order_id: OrderId,
+    #[serde(deserialize_with = "string_to_f64")]
+    pub price: f64,
+    #[serde(deserialize_with = "string_to_f64")]
+    pub qty: f64,
+    #[serde(rename = "quoteQty", deserialize_with = "string_to_f64")]
+    pub quote_qty: f64,
+    #[serde(deserialize_with = "string_to_f64")]
+    pub commission: f64,
+    #[serde(rename = "commissionAsset")]
+    pub commission_asset: String,  // Often BNB, the quote asset, or the base asset
+    pub time: u64,  // Milliseconds
+    #[serde(rename = "isBuyer")]
+    pub is_buyer: bool,
+    #[serde(rename = "isMaker")]
+    pub is_maker: bool,
+}
+
+/// One row of `/api/v3/aggTrades`: fills at the same price from one taker order, merged
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AggTrade {
+    #[serde(rename = "a")]
+    pub id: u64,  // Aggregate trade id, the cursor for `fromId` paging
+    #[serde(rename = "p", deserialize_with = "string_to_f64")]
+    pub price: f64,
+    #[serde(rename = "q", deserialize_with = "string_to_f64")]
+    pub quantity: f64,
+    #[serde(rename = "f")]
+    pub first_trade_id: u64,
+    #[serde(rename = "l")]
+    pub last_trade_id: u64,
+    #[serde(rename = "T")]
+    pub timestamp: u64,  // Milliseconds
+    #[serde(rename = "m")]
+    pub buyer_is_maker: bool,
+}
+
+impl AggTrade {
+    /// As a market data `Trade`, with the side of the aggressor
+    pub fn to_trade(&self, symbol: &str) -> Trade {
+        Trade {
+            symbol: symbol.to_string(),
+            price: self.price,
+            quantity: self.quantity,
+            side: if self.buyer_is_maker { TradeSide::Sell } else { TradeSide::Buy },
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// What to do with a SELL for more than the free balance of the base asset
+/// Spot can't go short, so such orders would only be rejected by Binance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SellGuard {
+    #[default]
+    Off,     // Send the order as-is
+    Cap,     // Reduce the quantity to the free balance
+    Strict,  // Refuse the order with `TradingError::InsufficientHoldings`
+}
+
+/// Precision of timestamps sent to and returned by the REST API
+/// Binance switches response timestamps to microseconds when the
+/// `X-MBX-TIME-UNIT: MICROSECOND` header is sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeUnit {
+    #[default]
+    Millisecond,
+    Microsecond,
+}
+
+impl TimeUnit {
+    /// Value for the `X-MBX-TIME-UNIT` header
+    pub fn header_value(&self) -> &'static str {
+        match self {
+            TimeUnit::Millisecond => "MILLISECOND",
+            TimeUnit::Microsecond => "MICROSECOND",
+        }
+    }
+
+    /// Current time in this unit, for the signed `timestamp` parameter
+    pub fn now(&self) -> u64 {
+        let now = chrono::Utc::now();
+        match self {
+            TimeUnit::Millisecond => now.timestamp_millis() as u64,
+            TimeUnit::Microsecond => now.timestamp_micros() as u64,
+        }
+    }
+
+    /// Convert a timestamp in this unit to a UTC DateTime
+    pub fn to_datetime(&self, value: u64) -> Option<chrono::DateTime<chrono::Utc>> {
+        match self {
+            TimeUnit::Millisecond => chrono::DateTime::from_timestamp_millis(value as i64),
+            TimeUnit::Microsecond => chrono::DateTime::from_timestamp_micros(value as i64),
+        }
+    }
+}
+
+/// Response of `/api/v3/exchangeInfo` (only the parts we use)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExchangeInfo {
+    #[serde(rename = "rateLimits", default)]
+    pub rate_limits: RateLimits,
+    pub symbols: Vec<SymbolInfo>,
+}
+
+/// What a rate limit counts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RateLimitType {
+    RequestWeight,  // Sum of the weights of all requests
+    Orders,         // New orders placed
+    RawRequests,    // Requests of any kind, each counting 1
+    #[serde(other)]
+    Other,
+}
+
+/// Unit of a rate limit window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum RateLimitInterval {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+impl RateLimitInterval {
+    pub fn as_duration(self) -> std::time::Duration {
+        std::time::Duration::from_secs(match self {
+            RateLimitInterval::Second => 1,
+            RateLimitInterval::Minute => 60,
+            RateLimitInterval::Hour => 3600,
+            RateLimitInterval::Day => 86_400,
+        })
+    }
+}
+
+/// One cap from `rateLimits`, e.g. 6000 REQUEST_WEIGHT per 1 MINUTE
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RateLimit {
+    #[serde(rename = "rateLimitType")]
+    pub rate_limit_type: RateLimitType,
+    pub interval: RateLimitInterval,
+    #[serde(rename = "intervalNum")]
+    pub interval_num: u32,
+    pub limit: u32,
+}
+
+impl RateLimit {
+    /// Length of the window the limit applies to
+    pub fn window(&self) -> std::time::Duration {
+        self.interval.as_duration() * self.interval_num.max(1)
+    }
+}
+
+impl std::fmt::Display for RateLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.rate_limit_type {
+            RateLimitType::RequestWeight => "request weight",
+            RateLimitType::Orders => "orders",
+            RateLimitType::RawRequests => "requests",
+            RateLimitType::Other => "(other)",
+        };
+        write!(f, "{} {} per {:?}", self.limit, kind, self.window())
+    }
+}
+
+/// The account's request and order caps, grouped by what they count
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "Vec<RateLimit>", into = "Vec<RateLimit>")]
+pub struct RateLimits {
+    pub request_weight: Vec<RateLimit>,
+    pub orders: Vec<RateLimit>,
+    pub raw_requests: Vec<RateLimit>,
+}
+
+impl RateLimits {
+    /// Every limit, in the order weight, orders, raw requests
+    pub fn all(&self) -> impl Iterator<Item = &RateLimit> {
+        self.request_weight.iter().chain(&self.orders).chain(&self.raw_requests)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.all().next().is_none()
+    }
+}
+
+impl From<Vec<RateLimit>> for RateLimits {
+    fn from(limits: Vec<RateLimit>) -> Self {
+        let mut grouped = RateLimits::default();
+        for limit in limits {
+            match limit.rate_limit_type {
+                RateLimitType::RequestWeight => grouped.request_weight.push(limit),
+                RateLimitType::Orders => grouped.orders.push(limit),
+                RateLimitType::RawRequests => grouped.raw_requests.push(limit),
+                RateLimitType::Other => {}
+            }
+        }
+        grouped
+    }
+}
+
+impl From<RateLimits> for Vec<RateLimit> {
+    fn from(limits: RateLimits) -> Self {
+        limits.all().copied().collect()
+    }
+}
+
+/// Trading rules for one symbol
+#[derive(Debug, Clone, Deserialize)]
+pub struct SymbolInfo {
+    pub symbol: String,
+    pub status: String,  // "TRADING" when orders are accepted
+    #[serde(rename = "baseAsset")]
+    pub base_asset: String,
+    #[serde(rename = "quoteAsset")]
+    pub quote_asset: String,
+    #[serde(rename = "quoteAssetPrecision", default)]
+    pub quote_asset_precision: Option<u32>,  // Most decimals a quote amount (quoteOrderQty) may have
+    #[serde(default)]
+    pub filters: Vec<SymbolFilter>,
+}
+
+/// The symbol filters we act on; the rest are kept as `Other` so new ones don't break parsing
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "filterType")]
+pub enum SymbolFilter {
+    #[serde(rename = "PRICE_FILTER")]
+    PriceFilter {
+        #[serde(rename = "tickSize", deserialize_with = "string_to_f64")]
+        tick_size: f64,
+    },
+    #[serde(rename = "LOT_SIZE")]
+    LotSize {
+        #[serde(rename = "minQty", deserialize_with = "string_to_f64")]
+        min_qty: f64,
+        #[serde(rename = "stepSize", deserialize_with = "string_to_f64")]
+        step_size: f64,
+    },
+    #[serde(rename = "MARKET_LOT_SIZE")]
+    MarketLotSize {
+        #[serde(rename = "minQty", deserialize_with = "string_to_f64")]
+        min_qty: f64,
+        #[serde(rename = "stepSize", deserialize_with = "string_to_f64")]
+        step_size: f64,
+    },
+    #[serde(rename = "MIN_NOTIONAL")]
+    MinNotional {
+        #[serde(rename = "minNotional", deserialize_with = "string_to_f64")]
+        min_notional: f64,
+    },
+    #[serde(rename = "NOTIONAL")]
+    Notional {
+        #[serde(rename = "minNotional", deserialize_with = "string_to_f64")]
+        min_notional: f64,
+    },
+    #[serde(other)]
+    Other,
+}
+
+impl SymbolInfo {
+    pub fn is_trading(&self) -> bool {
+        self.status == "TRADING"
+    }
+
+    /// (min_qty, step_size) for market orders: MARKET_LOT_SIZE when it sets a step,
+    /// otherwise LOT_SIZE. (0, 0) if the symbol has neither
+    pub fn market_lot_size(&self) -> (f64, f64) {
+        let lot = self.filters.iter().find_map(|f| match f {
+            SymbolFilter::LotSize { min_qty, step_size } => Some((*min_qty, *step_size)),
+            _ => None,
+        });
+        let market_lot = self.filters.iter().find_map(|f| match f {
+            SymbolFilter::MarketLotSize { min_qty, step_size } if *step_size > 0.0 => Some((*min_qty, *step_size)),
+            _ => None,
+        });
+        market_lot.or(lot).unwrap_or((0.0, 0.0))
+    }
+
+    /// LOT_SIZE step for limit orders, if the symbol has one
+    pub fn lot_step_size(&self) -> Option<f64> {
+        self.filters.iter().find_map(|f| match f {
+            SymbolFilter::LotSize { step_size, .. } if *step_size > 0.0 => Some(*step_size),
+            _ => None,
+        })
+    }
+
+    /// PRICE_FILTER tick size, if the symbol has one
+    pub fn tick_size(&self) -> Option<f64> {
+        self.filters.iter().find_map(|f| match f {
+            SymbolFilter::PriceFilter { tick_size } if *tick_size > 0.0 => Some(*tick_size),
+            _ => None,
+        })
+    }
+
+    /// Smallest order value in quote asset (NOTIONAL or the older MIN_NOTIONAL filter)
+    pub fn min_notional(&self) -> f64 {
+        self.filters.iter().find_map(|f| match f {
+            SymbolFilter::Notional { min_notional } | SymbolFilter::MinNotional { min_notional } => Some(*min_notional),
+            _ => None,
+        }).unwrap_or(0.0)
+    }
+}
+
+/// Response of `/sapi/v1/system/status`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemStatus {
+    pub status: u8,   // 0 = normal, 1 = system maintenance
+    pub msg: String,  // "normal" or "system_maintenance"
+}
+
+impl SystemStatus {
+    pub fn is_normal(&self) -> bool {
+        self.status == 0
+    }
+}
+
+/// Whether fees are paid with BNB, from `/sapi/v1/bnbBurn`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BnbBurnStatus {
+    #[serde(rename = "spotBNBBurn")]
+    pub spot_bnb_burn: bool,  // Spot trading fees are paid in BNB (at a discount)
+    #[serde(rename = "interestBNBBurn", default)]
+    pub interest_bnb_burn: bool,  // Margin interest is paid in BNB
+}
+
+/// Result of `quantity_for_notional`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NotionalQuantity {
+    pub quantity: f64,  // Base quantity, rounded down to the symbol's step size
+    pub price: f64,     // Price the quantity was computed at
+    pub notional: f64,  // quantity * price - at most the requested notional
+}
+
+/// Result of `flatten_to`
+#[derive(Debug, Clone, Default)]
+pub struct FlattenSummary {
+    pub sold: Vec<FlattenSale>,
+    pub skipped: Vec<FlattenSkip>,
+}
+
+/// One market sell placed by `flatten_to`
+#[derive(Debug, Clone)]
+pub struct FlattenSale {
+    pub asset: String,
+    pub symbol: String,
+    pub quantity: f64,  // Rounded down to the step size, so it never exceeds the free balance
+    pub order: OrderResponse,
+}
+
+/// An asset `flatten_to` left alone, and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlattenSkip {
+    pub asset: String,
+    pub reason: FlattenSkipReason,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FlattenSkipReason {
+    NoRoute,              // No trading pair against the quote asset
+    Dust,                 // Below the minimum quantity or notional after rounding
+    OrderFailed(String),  // The sell was rejected or failed to send
+}
+
+/// `executionReport` event from the user data stream: one update to one of our orders
+/// Only the fields we use; Binance's single-letter keys are mapped to readable names
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExecutionReport {
+    #[serde(rename = "E")]
+    pub event_time: u64,
+    #[serde(rename = "s")]
+    pub symbol: String,
+    #[serde(rename = "c")]
+    pub client_order_id: String,
+    #[serde(rename = "S")]
+    pub side: OrderSide,
+    #[serde(rename = "o")]
+    pub order_type: String,
+    #[serde(rename = "q", deserialize_with = "string_to_f64")]
+    pub quantity: f64,
+    #[serde(rename = "p", deserialize_with = "string_to_f64")]
+    pub price: f64,
+    #[serde(rename = "x")]
+    pub execution_type: String,  // NEW, CANCELED, REPLACED, REJECTED, TRADE, EXPIRED
+    #[serde(rename = "X")]
+    pub order_status: OrderStatus,
+    #[serde(rename = "r", default)]
+    pub reject_reason: String,   // "NONE" unless rejected
+    #[serde(rename = "i")]
+    pub order_id: OrderId,
+    #[serde(rename = "l", deserialize_with = "string_to_f64")]
+    pub last_filled_qty: f64,
+    #[serde(rename = "z", deserialize_with = "string_to_f64")]
+    pub cumulative_filled_qty: f64,
+    #[serde(rename = "L", deserialize_with = "string_to_f64")]
+    pub last_filled_price: f64,
+    #[serde(rename = "n", deserialize_with = "string_to_f64")]
+    pub commission: f64,
+    #[serde(rename = "N", default)]
+    pub commission_asset: Option<String>,  // null until there's a fill
+    #[serde(rename = "T")]
+    pub transaction_time: u64,
+    #[serde(rename = "t")]
+    pub trade_id: i64,  // -1 when the report isn't a trade
+}
+
+/// One entry of `/api/v3/ticker/price`
+#[derive(Debug, Clone, Deserialize)]
+pub struct SymbolPrice {
+    pub symbol: String,
+    #[serde(deserialize_with = "string_to_f64")]
+    pub price: f64,
+}
+
+/// Result of `check_auth`
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthCheck {
+    Authenticated,             // The signed account call succeeded
+    InvalidSignature(String),  // Binance returned -1022: the secret key is wrong or mangled
+    Failed(String),            // Anything else: bad api key, IP whitelist, network, ...
+}
+
+/// `outboundAccountPosition` event from the user data stream: new balances for
+/// every asset that changed (only those assets are included)
+#[derive(Debug, Clone, Deserialize)]
+pub struct OutboundAccountPosition {
+    #[serde(rename = "E")]
+    pub event_time: u64,
+    #[serde(rename = "u")]
+    pub last_update_time: u64,
+    #[serde(rename = "B")]
+    pub balances: Vec<BalanceUpdate>,
+}
+
+/// One asset's balance in an `outboundAccountPosition` event
+#[derive(Debug, Clone, Deserialize)]
+pub struct BalanceUpdate {
+    #[serde(rename = "a")]
+    pub asset: String,
+    #[serde(rename = "f", deserialize_with = "string_to_f64")]
+    pub free: f64,
+    #[serde(rename = "l", deserialize_with = "string_to_f64")]
+    pub locked: f64,
+}
+
+/// Current spot balances by asset, kept up to date by `AccountWatcher`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountSnapshot {
+    pub balances: HashMap<String, Balance>,
+    pub updated_at: u64,  // Time of the last applied update in ms (0 = straight from REST)
+}
+
+impl AccountSnapshot {
+    /// Snapshot of the balances in a REST account response
+    pub fn from_account_info(account: &AccountInfo) -> Self {
+        Self {
+            balances: account.balances.iter().map(|b| (b.asset.clone(), b.clone())).collect(),
+            updated_at: 0,
+        }
+    }
+
+    /// Apply a stream update; returns whether any balance actually changed
+    /// Updates older than the last applied one are ignored
+    pub fn apply(&mut self, update: &OutboundAccountPosition) -> bool {
+        if update.last_update_time < self.updated_at {
+            return false;
+        }
+
+        let mut changed = false;
+        for b in &update.balances {
+            let balance = Balance { asset: b.asset.clone(), free: b.free, locked: b.locked };
+            if self.balances.get(&b.asset) != Some(&balance) {
+                self.balances.insert(b.asset.clone(), balance);
+                changed = true;
+            }
+        }
+        self.updated_at = update.last_update_time;
+        changed
+    }
+
+    /// Free balance of `asset` (0 if we hold none)
+    pub fn free(&self, asset: &str) -> f64 {
+        self.balances.get(asset).map_or(0.0, |b| b.free)
+    }
+}
+
+/// Wallet type for `/sapi/v1/accountSnapshot`
+/// Only SPOT is supported for now - MARGIN and FUTURES snapshots have a different data shape
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SnapshotType {
+    #[serde(rename = "SPOT")]
+    Spot,
+}
+
+impl SnapshotType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SnapshotType::Spot => "SPOT",
+        }
+    }
+}
+
+/// Top-level response of `/sapi/v1/accountSnapshot`
+/// The actual daily snapshots are nested under `snapshotVos`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSnapshotResponse {
+    pub code: i32,
+    #[serde(default)]
+    pub msg: String,
+    #[serde(rename = "snapshotVos", default)]
+    pub snapshot_vos: Vec<DailyAccountSnapshot>,  // Missing when there's no history yet
+}
+
+/// One daily balance snapshot (Binance takes one per day at 23:59:59 UTC)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyAccountSnapshot {
+    #[serde(rename = "type")]
+    pub snapshot_type: String,
+    #[serde(rename = "updateTime")]
+    pub update_time: u64,
+    pub data: SpotSnapshotData,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotSnapshotData {
+    pub balances: Vec<Balance>,
+    #[serde(rename = "totalAssetOfBtc", deserialize_with = "string_to_f64")]
+    pub total_asset_of_btc: f64,  // Total account value expressed in BTC
+}
@@ -21,7 +21,7 @@ async fn test_real_testnet_connection() {
     let trader = TestnetTrader::new(api_key, secret_key);
 
     // Test getting account info
-    let account_info = trader.get_account_info().await.unwrap();
+    let account_info = trader.get_spot_account().await.unwrap();
     assert!(account_info.can_trade);
 
     // Test getting current price